@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use annimate_core::{AnnimateError, ImportCorpusResult, ImportStatusEvent};
+use clap::Args;
+
+use crate::output::Output;
+use crate::{StorageArgs, cancel_requested};
+
+#[derive(Args)]
+pub(crate) struct ImportArgs {
+    /// Files or directories to import corpora from
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct ListArgs {
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct DeleteArgs {
+    /// Names of the corpora to delete
+    #[arg(required = true)]
+    corpus_names: Vec<String>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct ClearCacheArgs {
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+pub(crate) fn import(args: ImportArgs, output: Output) -> Result<(), AnnimateError> {
+    let storage = args.storage.open()?;
+
+    let file_names: RefCell<Vec<String>> = RefCell::new(Vec::new());
+    let file_name = |index: usize| file_names.borrow()[index].clone();
+
+    let imported_corpus_names = storage.import_corpora(
+        args.paths,
+        |status_event| {
+            output.status(&status_event, || match &status_event {
+                ImportStatusEvent::Started => Some("Searching for corpora".into()),
+                ImportStatusEvent::CorporaFound { corpora } => {
+                    file_names.replace(
+                        corpora
+                            .iter()
+                            .map(|corpus| corpus.file_name().to_string())
+                            .collect(),
+                    );
+                    Some(format!("Found {} corpora", corpora.len()))
+                }
+                ImportStatusEvent::CorpusImportStarted { index } => Some(format!(
+                    "Importing {} ({}/{})",
+                    file_name(*index),
+                    index + 1,
+                    file_names.borrow().len()
+                )),
+                ImportStatusEvent::CorpusImportFinished { index, result } => match result {
+                    ImportCorpusResult::Imported { name } => {
+                        Some(format!("Imported corpus {name}"))
+                    }
+                    ImportCorpusResult::Failed { message, .. } => {
+                        Some(format!("Failed to import {}: {message}", file_name(*index)))
+                    }
+                },
+                ImportStatusEvent::Message { message, .. } => Some(format!("  {message}")),
+            });
+        },
+        cancel_requested,
+    )?;
+
+    if cancel_requested() {
+        return Err(AnnimateError::Cancelled);
+    }
+
+    output.confirm(&format!(
+        "Imported {} of {} corpora",
+        imported_corpus_names.len(),
+        file_names.borrow().len()
+    ));
+
+    Ok(())
+}
+
+pub(crate) fn list(args: ListArgs, output: Output) -> Result<(), AnnimateError> {
+    let storage = args.storage.open()?;
+    let corpora = storage.corpora()?;
+
+    output.result(&corpora, || {
+        let mut text = String::new();
+
+        text.push_str("Corpora:\n");
+        for corpus in &corpora.corpora {
+            if corpus.included_in_sets.is_empty() {
+                text.push_str(&format!("  {}\n", corpus.name));
+            } else {
+                text.push_str(&format!(
+                    "  {} [{}]\n",
+                    corpus.name,
+                    corpus.included_in_sets.join(", ")
+                ));
+            }
+        }
+
+        text.push_str("Corpus sets:\n");
+        for set in &corpora.sets {
+            text.push_str(&format!("  {set}\n"));
+        }
+
+        text
+    });
+
+    Ok(())
+}
+
+pub(crate) fn delete(args: DeleteArgs, output: Output) -> Result<(), AnnimateError> {
+    let storage = args.storage.open()?;
+
+    for corpus_name in args.corpus_names {
+        storage.delete_corpus(&corpus_name)?;
+        output.confirm(&format!("Deleted corpus {corpus_name}"));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn clear_cache(args: ClearCacheArgs, output: Output) -> Result<(), AnnimateError> {
+    let storage = args.storage.open()?;
+    storage.clear_cache()?;
+    output.confirm("Cleared cache");
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+
+use annimate_core::{
+    AnnimateError, BatchExportStatusEvent, BatchExportTarget, ExportConfig, ExportFormat,
+    ExportSampling, ExportStatusEvent, FrequencySplit, Parallelism, TableExportMode,
+};
+use clap::{Args, ValueEnum};
+
+use crate::output::Output;
+use crate::{StorageArgs, cancel_requested};
+
+#[derive(Args)]
+pub(crate) struct ExportArgs {
+    /// Project file (.anmt) containing the query, corpora, columns and format to export
+    project_file: PathBuf,
+
+    /// File to write the exported matches to
+    ///
+    /// For a project with several queries, this is a directory to write one file per query to,
+    /// unless `--workbook` is given. Defaults to the output path stored in the project file.
+    #[arg(short, long)]
+    output_file: Option<PathBuf>,
+
+    /// Export all queries of the project into a single XLSX workbook with one data worksheet per
+    /// query (only for XLSX)
+    #[arg(long)]
+    workbook: bool,
+
+    #[command(flatten)]
+    sampling: SamplingArgs,
+
+    #[command(flatten)]
+    frequencies: FrequencyArgs,
+
+    /// Whether to search corpora and collect the data of matches on multiple threads
+    ///
+    /// Defaults to `parallel` on Linux with multiple CPU cores, where this is usually faster, and to
+    /// `sequential` otherwise, since `parallel` can be much slower on Windows.
+    #[arg(long)]
+    parallelism: Option<ParallelismArg>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Sampling")]
+struct SamplingArgs {
+    /// Number of matches to skip
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+
+    /// Maximum number of matches to export
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Maximum number of matches to export per corpus
+    #[arg(long)]
+    limit_per_corpus: Option<usize>,
+
+    /// Seed for exporting a random sample of the matches instead of the first ones
+    #[arg(long)]
+    random_seed: Option<u64>,
+}
+
+impl From<SamplingArgs> for ExportSampling {
+    fn from(args: SamplingArgs) -> ExportSampling {
+        ExportSampling {
+            offset: args.offset,
+            limit: args.limit,
+            limit_per_corpus: args.limit_per_corpus,
+            random_seed: args.random_seed,
+        }
+    }
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Frequency analysis")]
+struct FrequencyArgs {
+    /// Export the frequencies of the values of the annotation columns instead of the matches
+    /// (only for CSV and XLSX)
+    #[arg(long)]
+    frequencies: bool,
+
+    /// Count frequencies separately for each corpus or document
+    #[arg(long, requires = "frequencies")]
+    split_by: Option<SplitBy>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SplitBy {
+    Corpus,
+    Document,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ParallelismArg {
+    Sequential,
+    Parallel,
+}
+
+impl From<ParallelismArg> for Parallelism {
+    fn from(arg: ParallelismArg) -> Parallelism {
+        match arg {
+            ParallelismArg::Sequential => Parallelism::Sequential,
+            ParallelismArg::Parallel => Parallelism::Parallel,
+        }
+    }
+}
+
+impl From<FrequencyArgs> for TableExportMode {
+    fn from(args: FrequencyArgs) -> TableExportMode {
+        if args.frequencies {
+            TableExportMode::Frequencies(match args.split_by {
+                None => FrequencySplit::None,
+                Some(SplitBy::Corpus) => FrequencySplit::Corpus,
+                Some(SplitBy::Document) => FrequencySplit::Document,
+            })
+        } else {
+            TableExportMode::Matches
+        }
+    }
+}
+
+pub(crate) fn run(args: ExportArgs, output: Output) -> Result<(), AnnimateError> {
+    let project = annimate_core::load_project(&args.project_file)?;
+    let output_file = args
+        .output_file
+        .or_else(|| project.output_path.clone())
+        .ok_or(AnnimateError::MissingOutputPath)?;
+    let sampling = args.sampling.into();
+    let mode = args.frequencies.into();
+
+    let storage = args
+        .storage
+        .open()?
+        .with_parallelism(args.parallelism.map(Into::into).unwrap_or_default());
+
+    let last_reported_percentage = Cell::new(None);
+
+    if project.queries.len() <= 1 && !args.workbook {
+        let mut export_config = project.into_export_config()?;
+        configure(&mut export_config, sampling, mode)?;
+
+        storage.export_matches(
+            export_config,
+            &output_file,
+            |status_event| {
+                output.status(&status_event, || {
+                    status_text(&status_event, &last_reported_percentage)
+                });
+            },
+            cancel_requested,
+        )?;
+
+        output.confirm(&format!("Exported matches to {}", output_file.display()));
+    } else {
+        let mut export_configs = project.into_export_configs()?;
+        for named_export_config in &mut export_configs {
+            configure(&mut named_export_config.config, sampling, mode)?;
+        }
+
+        let target = if args.workbook {
+            BatchExportTarget::Workbook(output_file)
+        } else {
+            fs::create_dir_all(&output_file)?;
+            BatchExportTarget::Directory(output_file)
+        };
+
+        let output_files = storage.export_batch(
+            export_configs,
+            target,
+            |status_event| {
+                output.status(&status_event, || {
+                    let BatchExportStatusEvent {
+                        query_index,
+                        query_count,
+                        event,
+                    } = &status_event;
+
+                    status_text(event, &last_reported_percentage)
+                        .map(|text| format!("Query {}/{query_count}: {text}", query_index + 1))
+                });
+            },
+            cancel_requested,
+        )?;
+
+        for output_file in output_files {
+            output.confirm(&format!("Exported matches to {}", output_file.display()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the sampling and mode given on the command line to an export configuration.
+fn configure(
+    export_config: &mut ExportConfig,
+    sampling: ExportSampling,
+    mode: TableExportMode,
+) -> Result<(), AnnimateError> {
+    export_config.sampling = sampling;
+
+    match &mut export_config.format {
+        ExportFormat::Csv(config) => config.mode = mode,
+        ExportFormat::Xlsx(config) => config.mode = mode,
+        ExportFormat::Ods(config) => config.mode = mode,
+        ExportFormat::Jsonl(_) | ExportFormat::Html(_) if mode != TableExportMode::Matches => {
+            return Err(AnnimateError::FrequenciesNotSupported);
+        }
+        ExportFormat::Jsonl(_) | ExportFormat::Html(_) => {}
+    }
+
+    Ok(())
+}
+
+fn status_text(
+    status_event: &ExportStatusEvent,
+    last_reported_percentage: &Cell<Option<usize>>,
+) -> Option<String> {
+    match *status_event {
+        ExportStatusEvent::Started => {
+            last_reported_percentage.set(None);
+            Some("Searching corpora".into())
+        }
+        ExportStatusEvent::CorporaSearched { count, total_count } => {
+            Some(format!("Searched {count}/{total_count} corpora"))
+        }
+        ExportStatusEvent::MatchesExported { count, total_count } => {
+            // Matches are reported one by one, so only print when the percentage changes
+            let percentage = (count * 100).checked_div(total_count).unwrap_or(100);
+            (last_reported_percentage.replace(Some(percentage)) != Some(percentage))
+                .then(|| format!("Exported {count}/{total_count} matches ({percentage}%)"))
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! This is a command-line interface for Annimate.
+
+#![deny(missing_docs)]
+
+mod corpora;
+mod export;
+mod output;
+mod sets;
+
+use std::path::PathBuf;
+use std::process::{self, ExitCode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use annimate_core::{AnnimateError, Storage};
+use clap::{Args, Parser, Subcommand};
+use output::Output;
+
+const THREAD_STACK_SIZE_MB: usize = 64;
+
+/// Exit code used when the process is terminated by a repeated Ctrl-C, following the shell convention `128 + SIGINT`
+const EXIT_CODE_INTERRUPTED: i32 = 130;
+
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Annimate - Your Friendly ANNIS Match Exporter
+#[derive(Parser)]
+#[command(name = "annimate-cli", version)]
+struct Cli {
+    /// Print results and status events as JSON lines instead of human-readable text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Imports corpora from files, directories or ZIP files
+    Import(corpora::ImportArgs),
+
+    /// Lists all corpora and corpus sets
+    List(corpora::ListArgs),
+
+    /// Deletes corpora
+    Delete(corpora::DeleteArgs),
+
+    /// Manages corpus sets
+    Sets {
+        #[command(subcommand)]
+        command: sets::SetsCommand,
+    },
+
+    /// Clears the cache for all corpora
+    ClearCache(corpora::ClearCacheArgs),
+
+    /// Exports the matches of a query as configured in a project file
+    Export(export::ExportArgs),
+}
+
+#[derive(Args)]
+struct StorageArgs {
+    /// Directory containing the corpus data
+    #[arg(long, env = "ANNIMATE_DB_DIR")]
+    db_dir: PathBuf,
+}
+
+impl StorageArgs {
+    fn open(self) -> Result<Storage, AnnimateError> {
+        Storage::from_db_dir(self.db_dir)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    ctrlc::set_handler(|| {
+        if CANCEL_REQUESTED.swap(true, Ordering::Relaxed) {
+            process::exit(EXIT_CODE_INTERRUPTED);
+        }
+
+        eprintln!("Cancelling, press Ctrl-C again to terminate immediately");
+    })
+    .expect("Ctrl-C handler should be set");
+
+    let output = Output::new(cli.json);
+
+    // Run on a thread with a larger stack size (default is 2 MB) to avoid stack overflows when
+    // analyzing queries, same as in the desktop app
+    let result = thread::Builder::new()
+        .stack_size(THREAD_STACK_SIZE_MB * 1024 * 1024)
+        .spawn(move || run(cli.command, output))
+        .expect("thread should spawn")
+        .join()
+        .expect("thread should not panic");
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, output: Output) -> Result<(), AnnimateError> {
+    match command {
+        Command::Import(args) => corpora::import(args, output),
+        Command::List(args) => corpora::list(args, output),
+        Command::Delete(args) => corpora::delete(args, output),
+        Command::Sets { command } => sets::run(command, output),
+        Command::ClearCache(args) => corpora::clear_cache(args, output),
+        Command::Export(args) => export::run(args, output),
+    }
+}
+
+fn cancel_requested() -> bool {
+    CANCEL_REQUESTED.load(Ordering::Relaxed)
+}
@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+/// Determines how results and status events are printed.
+#[derive(Clone, Copy)]
+pub(crate) struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub(crate) fn new(json: bool) -> Self {
+        Self { json }
+    }
+
+    /// Prints a status event.
+    ///
+    /// In JSON mode, the event is printed to stdout as a JSON line. Otherwise, the text returned by `text` (if any) is
+    /// printed to stderr.
+    pub(crate) fn status<T, F>(self, event: &T, text: F)
+    where
+        T: Serialize,
+        F: FnOnce() -> Option<String>,
+    {
+        if self.json {
+            print_json_line(event);
+        } else if let Some(text) = text() {
+            eprintln!("{text}");
+        }
+    }
+
+    /// Prints a result.
+    ///
+    /// In JSON mode, the result is printed to stdout as a JSON line. Otherwise, the text returned by `text` is printed
+    /// to stdout.
+    pub(crate) fn result<T, F>(self, result: &T, text: F)
+    where
+        T: Serialize,
+        F: FnOnce() -> String,
+    {
+        if self.json {
+            print_json_line(result);
+        } else {
+            print!("{}", text());
+        }
+    }
+
+    /// Prints a message confirming that an operation was performed.
+    ///
+    /// Nothing is printed in JSON mode. Otherwise, the message is printed to stderr.
+    pub(crate) fn confirm(self, message: &str) {
+        if !self.json {
+            eprintln!("{message}");
+        }
+    }
+}
+
+fn print_json_line<T>(value: &T)
+where
+    T: Serialize,
+{
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("value should be serializable")
+    );
+}
@@ -0,0 +1,124 @@
+use annimate_core::AnnimateError;
+use clap::{Args, Subcommand};
+
+use crate::StorageArgs;
+use crate::output::Output;
+
+#[derive(Subcommand)]
+pub(crate) enum SetsCommand {
+    /// Creates a new, empty corpus set
+    Create(CreateArgs),
+
+    /// Renames a corpus set
+    Rename(RenameArgs),
+
+    /// Deletes a corpus set
+    Delete(DeleteArgs),
+
+    /// Adds corpora to a corpus set, creating the set if it doesn't exist
+    Add(AddArgs),
+
+    /// Adds a corpus to a corpus set or removes it if it is already included
+    Toggle(ToggleArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct CreateArgs {
+    /// Name of the corpus set
+    set_name: String,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct RenameArgs {
+    /// Current name of the corpus set
+    set_name: String,
+
+    /// New name of the corpus set
+    new_set_name: String,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct DeleteArgs {
+    /// Name of the corpus set
+    set_name: String,
+
+    /// Also delete all corpora included in the set
+    #[arg(long)]
+    delete_corpora: bool,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct AddArgs {
+    /// Name of the corpus set
+    set_name: String,
+
+    /// Names of the corpora to add
+    #[arg(required = true)]
+    corpus_names: Vec<String>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct ToggleArgs {
+    /// Name of the corpus set
+    set_name: String,
+
+    /// Name of the corpus to add or remove
+    corpus_name: String,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+pub(crate) fn run(command: SetsCommand, output: Output) -> Result<(), AnnimateError> {
+    match command {
+        SetsCommand::Create(args) => {
+            let storage = args.storage.open()?;
+            storage.create_corpus_set(args.set_name.clone())?;
+            output.confirm(&format!("Created corpus set {}", args.set_name));
+        }
+        SetsCommand::Rename(args) => {
+            let storage = args.storage.open()?;
+            storage.rename_corpus_set(&args.set_name, args.new_set_name.clone())?;
+            output.confirm(&format!(
+                "Renamed corpus set {} to {}",
+                args.set_name, args.new_set_name
+            ));
+        }
+        SetsCommand::Delete(args) => {
+            let storage = args.storage.open()?;
+            storage.delete_corpus_set(args.set_name.clone(), args.delete_corpora)?;
+            output.confirm(&format!("Deleted corpus set {}", args.set_name));
+        }
+        SetsCommand::Add(args) => {
+            let storage = args.storage.open()?;
+            storage.add_corpora_to_set(args.set_name.clone(), &args.corpus_names)?;
+            output.confirm(&format!(
+                "Added {} corpora to corpus set {}",
+                args.corpus_names.len(),
+                args.set_name
+            ));
+        }
+        SetsCommand::Toggle(args) => {
+            let storage = args.storage.open()?;
+            storage.toggle_corpus_in_set(&args.set_name, &args.corpus_name)?;
+            output.confirm(&format!(
+                "Toggled corpus {} in corpus set {}",
+                args.corpus_name, args.set_name
+            ));
+        }
+    }
+
+    Ok(())
+}
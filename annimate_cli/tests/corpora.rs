@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../annimate_core/tests/data");
+const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/corpora/db");
+
+#[test]
+fn import_list_delete() {
+    let db_dir = fresh_db_dir("import_list_delete");
+
+    let output = annimate_cli(
+        &db_dir,
+        [
+            "import",
+            "--json",
+            &data_file("subtok.demo_relANNIS.zip"),
+            &data_file("empty_graphml.zip"),
+        ],
+    );
+    assert!(output.status.success());
+
+    let event_types: Vec<_> = json_lines(&output)
+        .into_iter()
+        .filter(|event| event["type"] != "message")
+        .map(|event| {
+            let result_type = &event["result"]["type"];
+            match result_type.as_str() {
+                Some(result_type) => format!("{} ({result_type})", event["type"].as_str().unwrap()),
+                None => event["type"].as_str().unwrap().to_string(),
+            }
+        })
+        .collect();
+    assert_eq!(
+        event_types,
+        [
+            "started",
+            "corpora_found",
+            "corpus_import_started",
+            "corpus_import_finished (imported)",
+            "corpus_import_started",
+            "corpus_import_finished (imported)",
+        ]
+    );
+
+    let output = annimate_cli(&db_dir, ["sets", "add", "set1", "subtok.demo", "empty"]);
+    assert!(output.status.success());
+
+    let output = annimate_cli(&db_dir, ["list"]);
+    assert!(output.status.success());
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r"
+    Corpora:
+      empty [set1]
+      subtok.demo [set1]
+    Corpus sets:
+      set1
+    ");
+
+    let output = annimate_cli(&db_dir, ["delete", "empty"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Deleted corpus empty\n"
+    );
+
+    let output = annimate_cli(&db_dir, ["list", "--json"]);
+    assert!(output.status.success());
+    assert_eq!(
+        json_lines(&output),
+        [serde_json::json!({
+            "corpora": [{ "name": "subtok.demo", "includedInSets": ["set1"] }],
+            "sets": ["set1"],
+        })]
+    );
+
+    let output = annimate_cli(&db_dir, ["clear-cache"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn sets() {
+    let db_dir = fresh_db_dir("sets");
+
+    let output = annimate_cli(&db_dir, ["import", &data_file("subtok.demo_relANNIS.zip")]);
+    assert!(output.status.success());
+
+    for args in [
+        ["sets", "create", "set1"].as_slice(),
+        &["sets", "create", "set2"],
+        &["sets", "toggle", "set1", "subtok.demo"],
+        &["sets", "toggle", "set2", "subtok.demo"],
+        &["sets", "toggle", "set2", "subtok.demo"],
+        &["sets", "rename", "set2", "set3"],
+        &["sets", "create", "set4"],
+        &["sets", "delete", "set4"],
+    ] {
+        let output = annimate_cli(&db_dir, args.iter().copied());
+        assert!(output.status.success());
+    }
+
+    let output = annimate_cli(&db_dir, ["sets", "create", "set1"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: Corpus set already exists\n"
+    );
+
+    let output = annimate_cli(&db_dir, ["list", "--json"]);
+    assert!(output.status.success());
+    assert_eq!(
+        json_lines(&output),
+        [serde_json::json!({
+            "corpora": [{ "name": "subtok.demo", "includedInSets": ["set1"] }],
+            "sets": ["set1", "set3"],
+        })]
+    );
+
+    let output = annimate_cli(&db_dir, ["sets", "delete", "set1", "--delete-corpora"]);
+    assert!(output.status.success());
+
+    let output = annimate_cli(&db_dir, ["list", "--json"]);
+    assert!(output.status.success());
+    assert_eq!(
+        json_lines(&output),
+        [serde_json::json!({ "corpora": [], "sets": ["set3"] })]
+    );
+}
+
+fn fresh_db_dir(name: &str) -> PathBuf {
+    let db_dir = Path::new(DB_DIR).join(name);
+    let _ = fs::remove_dir_all(&db_dir);
+    db_dir
+}
+
+fn data_file(file_name: &str) -> String {
+    Path::new(DATA_DIR)
+        .join(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn annimate_cli<'a, I>(db_dir: &Path, args: I) -> Output
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .args(args)
+        .arg("--db-dir")
+        .arg(db_dir)
+        .output()
+        .unwrap()
+}
+
+fn json_lines(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
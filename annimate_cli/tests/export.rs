@@ -0,0 +1,326 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use annimate_core::Storage;
+
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../annimate_core/tests/data");
+const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/export/db");
+const OUTPUT_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/export/output");
+
+const PROJECT: &str = r#"
+format-version = 2
+
+[project]
+corpora = ["subtok.demo"]
+query = "pos=\"DT\""
+query-language = "aql"
+export-format = "csv"
+
+[[project.columns]]
+type = "number"
+
+[[project.columns]]
+type = "match-annotation"
+annotation = { ns = "grammar", name = "lemma" }
+node-index = 0
+
+[[project.columns]]
+type = "match-in-context"
+segmentation = ""
+annotation = "default"
+context = 2
+"#;
+
+#[test]
+fn export_project() {
+    let db_dir = Path::new(DB_DIR).join("export_project");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    {
+        let storage = Storage::from_db_dir(db_dir.clone()).unwrap();
+        storage
+            .import_corpora(
+                vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+                |_| (),
+                || false,
+            )
+            .unwrap();
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project.anmt");
+    let output_file = Path::new(OUTPUT_DIR).join("export_project.csv");
+    let _ = fs::remove_file(&output_file);
+    fs::write(&project_file, PROJECT).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--output-file")
+        .arg(&output_file)
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let output = fs::read_to_string(output_file).unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn export_project_with_sampling() {
+    let db_dir = Path::new(DB_DIR).join("export_project_with_sampling");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    {
+        let storage = Storage::from_db_dir(db_dir.clone()).unwrap();
+        storage
+            .import_corpora(
+                vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+                |_| (),
+                || false,
+            )
+            .unwrap();
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project_with_sampling.anmt");
+    let output_file = Path::new(OUTPUT_DIR).join("export_project_with_sampling.csv");
+    let _ = fs::remove_file(&output_file);
+    fs::write(&project_file, PROJECT).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--output-file")
+        .arg(&output_file)
+        .args(["--offset", "1", "--limit", "2"])
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let output = fs::read_to_string(output_file).unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn export_project_with_frequencies() {
+    let db_dir = Path::new(DB_DIR).join("export_project_with_frequencies");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    {
+        let storage = Storage::from_db_dir(db_dir.clone()).unwrap();
+        storage
+            .import_corpora(
+                vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+                |_| (),
+                || false,
+            )
+            .unwrap();
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project_with_frequencies.anmt");
+    let output_file = Path::new(OUTPUT_DIR).join("export_project_with_frequencies.csv");
+    let _ = fs::remove_file(&output_file);
+    fs::write(&project_file, PROJECT).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--output-file")
+        .arg(&output_file)
+        .args(["--frequencies", "--split-by", "document"])
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let output = fs::read_to_string(output_file).unwrap();
+    insta::assert_snapshot!(output);
+}
+
+const PROJECT_WITH_MULTIPLE_QUERIES: &str = r#"
+format-version = 3
+
+[project]
+corpora = ["subtok.demo"]
+export-format = "csv"
+
+[[project.queries]]
+name = "determiners"
+query = "pos=\"DT\""
+query-language = "aql"
+
+[[project.queries.columns]]
+type = "number"
+
+[[project.queries.columns]]
+type = "match-annotation"
+annotation = { ns = "grammar", name = "lemma" }
+node-index = 0
+
+[[project.queries]]
+name = "nouns"
+query = "pos=\"NN\""
+query-language = "aql"
+
+[[project.queries.columns]]
+type = "number"
+
+[[project.queries.columns]]
+type = "match-in-context"
+segmentation = ""
+annotation = "default"
+context = 1
+"#;
+
+#[test]
+fn export_project_with_multiple_queries() {
+    let db_dir = Path::new(DB_DIR).join("export_project_with_multiple_queries");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    {
+        let storage = Storage::from_db_dir(db_dir.clone()).unwrap();
+        storage
+            .import_corpora(
+                vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+                |_| (),
+                || false,
+            )
+            .unwrap();
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project_with_multiple_queries.anmt");
+    let output_dir = Path::new(OUTPUT_DIR).join("export_project_with_multiple_queries");
+    let _ = fs::remove_dir_all(&output_dir);
+    fs::write(&project_file, PROJECT_WITH_MULTIPLE_QUERIES).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--output-file")
+        .arg(&output_dir)
+        .arg("--limit")
+        .arg("3")
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let output = ["determiners.csv", "nouns.csv"]
+        .map(|file_name| fs::read_to_string(output_dir.join(file_name)).unwrap())
+        .join("\n");
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn export_incomplete_project() {
+    let db_dir = Path::new(DB_DIR).join("export_incomplete_project");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_incomplete_project.anmt");
+    let output_file = Path::new(OUTPUT_DIR).join("export_incomplete_project.csv");
+    let _ = fs::remove_file(&output_file);
+    fs::write(
+        &project_file,
+        PROJECT.replace("node-index = 0", "# node-index missing"),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--output-file")
+        .arg(&output_file)
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: Column 2 of query 1 is incomplete\n"
+    );
+    assert!(!output_file.try_exists().unwrap());
+}
+
+#[test]
+fn export_project_with_stored_output_path() {
+    let db_dir = Path::new(DB_DIR).join("export_project_with_stored_output_path");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    {
+        let storage = Storage::from_db_dir(db_dir.clone()).unwrap();
+        storage
+            .import_corpora(
+                vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+                |_| (),
+                || false,
+            )
+            .unwrap();
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project_with_stored_output_path.anmt");
+    let output_file = Path::new(OUTPUT_DIR)
+        .join("stored")
+        .join("export_project_with_stored_output_path.csv");
+    let _ = fs::remove_file(&output_file);
+    fs::create_dir_all(output_file.parent().unwrap()).unwrap();
+    fs::write(&project_file, PROJECT).unwrap();
+
+    let mut project = annimate_core::load_project(&project_file).unwrap();
+    project.output_path = Some(output_file.clone());
+    annimate_core::save_project(project, &project_file).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let output = fs::read_to_string(output_file).unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn export_project_without_output_path() {
+    let db_dir = Path::new(DB_DIR).join("export_project_without_output_path");
+    let _ = fs::remove_dir_all(&db_dir);
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("export_project_without_output_path.anmt");
+    fs::write(&project_file, PROJECT).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_annimate-cli"))
+        .arg("export")
+        .arg(&project_file)
+        .arg("--db-dir")
+        .arg(&db_dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: No output path given and none is stored in the project\n"
+    );
+}
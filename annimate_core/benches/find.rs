@@ -0,0 +1,106 @@
+//! Benchmarks of exports with sequential and parallel search and collection of matches.
+//!
+//! Run with `cargo bench -p annimate_core` on each platform before changing the default
+//! [`Parallelism`]. The parallel strategy only pays off with multiple CPU cores.
+
+use std::fs;
+use std::path::Path;
+
+use annimate_core::{
+    AnnoKeyOrDefault, CsvDialect, CsvExportConfig, ExportConfig, ExportData, ExportDataText,
+    ExportFormat, ExportSampling, Parallelism, QueryLanguage, Storage, TableExportColumn,
+    TableExportMode, TextFormatting, ValueType,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/benches/find/db");
+const OUTPUT_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/benches/find/output");
+
+const CORPUS_PATHS: [&str; 5] = [
+    "pcc2_v7_relANNIS.zip",
+    "subtok.demo_relANNIS.zip",
+    "subtok.demo2_relANNIS.zip",
+    "subtok.demo3_relANNIS.zip",
+    "subtok.demo4_relANNIS.zip",
+];
+
+const CORPUS_NAMES: [&str; 5] = [
+    "pcc2",
+    "subtok.demo",
+    "subtok.demo2",
+    "subtok.demo3",
+    "subtok.demo4",
+];
+
+fn find(c: &mut Criterion) {
+    let _ = fs::remove_dir_all(DB_DIR);
+    Storage::from_db_dir(DB_DIR.into())
+        .unwrap()
+        .import_corpora(
+            CORPUS_PATHS
+                .iter()
+                .map(|p| Path::new(DATA_DIR).join(p))
+                .collect(),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+
+    let mut group = c.benchmark_group("find");
+    group.sample_size(10);
+
+    for (name, parallelism) in [
+        ("sequential", Parallelism::Sequential),
+        ("parallel", Parallelism::Parallel),
+    ] {
+        let storage = Storage::from_db_dir(DB_DIR.into())
+            .unwrap()
+            .with_parallelism(parallelism);
+        let output_file = Path::new(OUTPUT_DIR).join(name).with_extension("csv");
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                storage
+                    .export_matches(export_config(), &output_file, |_| (), || false)
+                    .unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn export_config() -> ExportConfig {
+    ExportConfig {
+        corpus_names: CORPUS_NAMES.iter().map(|&s| s.into()).collect(),
+        aql_query: "tok".into(),
+        query_language: QueryLanguage::AQL,
+        timeout: None,
+        format: ExportFormat::Csv(CsvExportConfig {
+            columns: vec![
+                TableExportColumn::Number,
+                TableExportColumn::Data(
+                    ExportData::Text(ExportDataText {
+                        left_context: 5,
+                        right_context: 5,
+                        context_unit: None,
+                        segmentation: None,
+                        anno_key: AnnoKeyOrDefault::Default,
+                        primary_node_indices: None,
+                        formatting: TextFormatting::default(),
+                    }),
+                    ValueType::Auto,
+                ),
+            ],
+            mode: TableExportMode::Matches,
+            dialect: CsvDialect::default(),
+        }),
+        sampling: ExportSampling::default(),
+    }
+}
+
+criterion_group!(benches, find);
+criterion_main!(benches);
@@ -17,12 +17,18 @@ use crate::name;
 
 pub(crate) const DOC: &str = "doc";
 pub(crate) const TOK: &str = "tok";
+pub(crate) const TOK_WHITESPACE_AFTER: &str = "tok-whitespace-after";
 
 pub(crate) static TOKEN_ANNO_KEY: LazyLock<AnnoKey> = LazyLock::new(|| AnnoKey {
     ns: ANNIS_NS.into(),
     name: TOK.into(),
 });
 
+pub(crate) static TOK_WHITESPACE_AFTER_ANNO_KEY: LazyLock<AnnoKey> = LazyLock::new(|| AnnoKey {
+    ns: ANNIS_NS.into(),
+    name: TOK_WHITESPACE_AFTER.into(),
+});
+
 pub(crate) static DEFAULT_ORDERING_COMPONENT: LazyLock<AnnotationComponent> = LazyLock::new(|| {
     Component::new(
         AnnotationComponentType::Ordering,
@@ -431,6 +437,8 @@ fn get_node_anno_key_infos(
                 // Matches both the corpus node and all document nodes
                 query: "annis:node_type=\"corpus\"",
                 query_language: QueryLanguage::AQL,
+                // This is not the user's query and its result is cached, so the configured query
+                // timeout does not apply
                 timeout: None,
             },
             0,
@@ -593,6 +601,30 @@ pub struct ExportableAnnoKey {
     display_name: String,
 }
 
+impl ExportableNodeAnnoKeys {
+    pub(crate) fn corpus(&self) -> impl Iterator<Item = &AnnoKey> {
+        self.corpus.iter().map(|k| &k.anno_key)
+    }
+
+    pub(crate) fn doc(&self) -> impl Iterator<Item = &AnnoKey> {
+        self.doc.iter().map(|k| &k.anno_key)
+    }
+
+    pub(crate) fn node(&self) -> impl Iterator<Item = &AnnoKey> {
+        self.node.iter().map(|k| &k.anno_key)
+    }
+}
+
+impl ExportableEdgeType {
+    pub(crate) fn edge_type(&self) -> &EdgeType {
+        &self.edge_type
+    }
+
+    pub(crate) fn anno_keys(&self) -> impl Iterator<Item = &AnnoKey> {
+        self.anno_keys.iter().map(|k| &k.anno_key)
+    }
+}
+
 /// The annotation key to use for a "Match in context" column.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum AnnoKeyOrDefault {
@@ -0,0 +1,521 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use graphannis::graph::AnnoKey;
+use serde::Serialize;
+
+use crate::anno::{AnnoKeyOrDefault, EdgeType};
+use crate::aql::QueryAnalysisResult;
+use crate::error::AnnimateError;
+use crate::project::{Project, ProjectExportColumn, ProjectQuery};
+use crate::query::TraversalItem;
+use crate::{QueryNode, Storage};
+
+/// Result of checking a project against the corpora in a [`Storage`].
+///
+/// See [`Storage::check_project`].
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiagnostics {
+    /// Problems that make an export of the project fail.
+    pub errors: Vec<ProjectDiagnostic>,
+
+    /// Problems that don't make an export of the project fail, but likely lead to missing values.
+    pub warnings: Vec<ProjectDiagnostic>,
+}
+
+impl ProjectDiagnostics {
+    /// Returns whether no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    /// Returns whether any errors were found.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    fn error(
+        &mut self,
+        query_index: Option<usize>,
+        column_index: Option<usize>,
+        kind: ProjectDiagnosticKind,
+    ) {
+        self.errors.push(ProjectDiagnostic {
+            query_index,
+            column_index,
+            kind,
+        });
+    }
+
+    fn warning(
+        &mut self,
+        query_index: Option<usize>,
+        column_index: Option<usize>,
+        kind: ProjectDiagnosticKind,
+    ) {
+        self.warnings.push(ProjectDiagnostic {
+            query_index,
+            column_index,
+            kind,
+        });
+    }
+}
+
+/// Problem found when checking a project.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiagnostic {
+    /// Index of the query the problem refers to, if any.
+    pub query_index: Option<usize>,
+
+    /// Index of the column within its query the problem refers to, if any.
+    pub column_index: Option<usize>,
+
+    /// Kind of the problem.
+    pub kind: ProjectDiagnosticKind,
+}
+
+impl Display for ProjectDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.query_index, self.column_index) {
+            (Some(query_index), Some(column_index)) => write!(
+                f,
+                "Column {} of query {}: {}",
+                column_index + 1,
+                query_index + 1,
+                self.kind
+            ),
+            (Some(query_index), None) => write!(f, "Query {}: {}", query_index + 1, self.kind),
+            _ => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// Kind of a problem found when checking a project.
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ProjectDiagnosticKind {
+    /// The selected corpus set doesn't exist.
+    CorpusSetNotFound {
+        /// Name of the corpus set.
+        corpus_set: String,
+    },
+
+    /// A selected corpus doesn't exist.
+    CorpusNotFound {
+        /// Name of the corpus.
+        corpus_name: String,
+    },
+
+    /// No corpora are selected.
+    NoCorpora,
+
+    /// The query is empty.
+    EmptyQuery,
+
+    /// The query is invalid.
+    InvalidQuery {
+        /// Message describing why the query is invalid.
+        message: String,
+    },
+
+    /// The column misses a setting that is required for the export.
+    IncompleteColumn,
+
+    /// The column refers to a query node that doesn't exist.
+    NodeIndexOutOfBounds {
+        /// Index of the query node referred to.
+        node_index: u32,
+
+        /// Number of nodes of the query.
+        node_count: usize,
+    },
+
+    /// The column refers to an annotation that doesn't exist in any of the selected corpora.
+    AnnoKeyNotFound {
+        /// Key of the annotation.
+        anno_key: AnnoKey,
+    },
+
+    /// The column refers to an edge type that doesn't exist in any of the selected corpora.
+    EdgeTypeNotFound {
+        /// Edge type.
+        edge_type: EdgeType,
+    },
+
+    /// The column refers to a segmentation that doesn't exist in all of the selected corpora.
+    SegmentationNotFound {
+        /// Name of the segmentation.
+        segmentation: String,
+    },
+}
+
+impl Display for ProjectDiagnosticKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectDiagnosticKind::CorpusSetNotFound { corpus_set } => {
+                write!(f, "Corpus set {corpus_set} does not exist")
+            }
+            ProjectDiagnosticKind::CorpusNotFound { corpus_name } => {
+                write!(f, "Corpus {corpus_name} does not exist")
+            }
+            ProjectDiagnosticKind::NoCorpora => write!(f, "No corpora selected"),
+            ProjectDiagnosticKind::EmptyQuery => write!(f, "Query is empty"),
+            ProjectDiagnosticKind::InvalidQuery { message } => {
+                write!(f, "Query is invalid: {message}")
+            }
+            ProjectDiagnosticKind::IncompleteColumn => write!(f, "Column is incomplete"),
+            ProjectDiagnosticKind::NodeIndexOutOfBounds {
+                node_index,
+                node_count,
+            } => write!(
+                f,
+                "Query node #{} does not exist, the query has {node_count} node(s)",
+                node_index + 1
+            ),
+            ProjectDiagnosticKind::AnnoKeyNotFound { anno_key } => {
+                write!(f, "Annotation ")?;
+                if !anno_key.ns.is_empty() {
+                    write!(f, "{}:", anno_key.ns)?;
+                }
+                write!(f, "{} not found in the selected corpora", anno_key.name)
+            }
+            ProjectDiagnosticKind::EdgeTypeNotFound { edge_type } => write!(
+                f,
+                "Edge type {} not found in the selected corpora",
+                edge_type.operator()
+            ),
+            ProjectDiagnosticKind::SegmentationNotFound { segmentation } => {
+                write!(
+                    f,
+                    "Segmentation {segmentation} not found in all of the selected corpora"
+                )
+            }
+        }
+    }
+}
+
+pub(crate) fn check_project(
+    storage: &Storage,
+    project: &Project,
+) -> Result<ProjectDiagnostics, AnnimateError> {
+    let mut diagnostics = ProjectDiagnostics::default();
+    let corpora = storage.corpora()?;
+
+    if let Some(corpus_set) = &project.corpus_set
+        && !corpora.sets.contains(corpus_set)
+    {
+        diagnostics.warning(
+            None,
+            None,
+            ProjectDiagnosticKind::CorpusSetNotFound {
+                corpus_set: corpus_set.clone(),
+            },
+        );
+    }
+
+    let mut corpus_names = Vec::new();
+    for corpus_name in &project.corpus_names {
+        if corpora.corpora.iter().any(|c| c.name == *corpus_name) {
+            corpus_names.push(corpus_name.as_str());
+        } else {
+            diagnostics.error(
+                None,
+                None,
+                ProjectDiagnosticKind::CorpusNotFound {
+                    corpus_name: corpus_name.clone(),
+                },
+            );
+        }
+    }
+
+    if project.corpus_names.is_empty() {
+        diagnostics.warning(None, None, ProjectDiagnosticKind::NoCorpora);
+    }
+
+    // Without any existing corpora, nothing is available, so checking availability would only
+    // produce noise
+    let available = if corpus_names.is_empty() {
+        None
+    } else {
+        Some(Available::new(storage, &corpus_names)?)
+    };
+
+    for (query_index, query) in project.queries.iter().enumerate() {
+        check_query(
+            storage,
+            query_index,
+            query,
+            available.as_ref(),
+            &mut diagnostics,
+        )?;
+    }
+
+    Ok(diagnostics)
+}
+
+fn check_query(
+    storage: &Storage,
+    query_index: usize,
+    query: &ProjectQuery,
+    available: Option<&Available>,
+    diagnostics: &mut ProjectDiagnostics,
+) -> Result<(), AnnimateError> {
+    let node_count = if query.aql_query.is_empty() {
+        diagnostics.error(Some(query_index), None, ProjectDiagnosticKind::EmptyQuery);
+        None
+    } else {
+        match storage.validate_query(&query.aql_query, query.query_language)? {
+            QueryAnalysisResult::Valid(()) => storage
+                .query_nodes(&query.aql_query, query.query_language)?
+                .valid()
+                .map(|query_nodes| Vec::<Vec<QueryNode>>::from(query_nodes).len()),
+            QueryAnalysisResult::Invalid(err) => {
+                diagnostics.error(
+                    Some(query_index),
+                    None,
+                    ProjectDiagnosticKind::InvalidQuery {
+                        message: err.message,
+                    },
+                );
+                None
+            }
+        }
+    };
+
+    for (column_index, column) in query.export_columns.iter().enumerate() {
+        if column.clone().into_table_export_column().is_none() {
+            diagnostics.error(
+                Some(query_index),
+                Some(column_index),
+                ProjectDiagnosticKind::IncompleteColumn,
+            );
+        }
+
+        if let Some(node_count) = node_count {
+            for node_index in node_indices(column) {
+                if usize::try_from(node_index).is_ok_and(|i| i < node_count) {
+                    continue;
+                }
+
+                diagnostics.error(
+                    Some(query_index),
+                    Some(column_index),
+                    ProjectDiagnosticKind::NodeIndexOutOfBounds {
+                        node_index,
+                        node_count,
+                    },
+                );
+            }
+        }
+
+        for kind in available
+            .map(|a| a.check_column(column))
+            .unwrap_or_default()
+        {
+            // A missing segmentation makes the export fail, while missing annotations and edge
+            // types only lead to empty values
+            if let ProjectDiagnosticKind::SegmentationNotFound { .. } = kind {
+                diagnostics.error(Some(query_index), Some(column_index), kind);
+            } else {
+                diagnostics.warning(Some(query_index), Some(column_index), kind);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn node_indices(column: &ProjectExportColumn) -> Vec<u32> {
+    match column {
+        ProjectExportColumn::Number
+        | ProjectExportColumn::AnnoCorpus { .. }
+        | ProjectExportColumn::AnnoDocument { .. } => Vec::new(),
+        ProjectExportColumn::AnnoMatch { node_index, .. }
+        | ProjectExportColumn::CoveredText { node_index, .. }
+        | ProjectExportColumn::MatchPosition { node_index, .. } => {
+            node_index.iter().copied().collect()
+        }
+        ProjectExportColumn::QueryNodeProperty {
+            match_node_index, ..
+        } => match_node_index.iter().copied().collect(),
+        ProjectExportColumn::AnnoEdge {
+            source_node_index,
+            target_node_index,
+            ..
+        } => source_node_index
+            .iter()
+            .chain(target_node_index)
+            .copied()
+            .collect(),
+        ProjectExportColumn::MatchInContext {
+            primary_node_indices,
+            ..
+        } => primary_node_indices.clone(),
+        ProjectExportColumn::TraversalAnno {
+            node_index,
+            target_node_index,
+            ..
+        } => node_index
+            .iter()
+            .chain(target_node_index)
+            .copied()
+            .collect(),
+    }
+}
+
+/// Annotation keys, edge types and segmentations available in the selected corpora.
+struct Available {
+    corpus_anno_keys: HashSet<AnnoKey>,
+    doc_anno_keys: HashSet<AnnoKey>,
+    node_anno_keys: HashSet<AnnoKey>,
+    edge_anno_keys: HashMap<EdgeType, HashSet<AnnoKey>>,
+    segmentations: HashSet<String>,
+}
+
+impl Available {
+    fn new(storage: &Storage, corpus_names: &[&str]) -> Result<Self, AnnimateError> {
+        let node_anno_keys = storage.exportable_node_anno_keys(corpus_names)?;
+        let edge_types = storage.exportable_edge_types(corpus_names)?;
+
+        Ok(Self {
+            corpus_anno_keys: node_anno_keys.corpus().cloned().collect(),
+            doc_anno_keys: node_anno_keys.doc().cloned().collect(),
+            node_anno_keys: node_anno_keys.node().cloned().collect(),
+            edge_anno_keys: edge_types
+                .iter()
+                .map(|e| (e.edge_type().clone(), e.anno_keys().cloned().collect()))
+                .collect(),
+            segmentations: storage.segmentations(corpus_names)?.into_iter().collect(),
+        })
+    }
+
+    /// Returns the problems with the annotation keys, edge types and segmentations used by a
+    /// column.
+    fn check_column(&self, column: &ProjectExportColumn) -> Vec<ProjectDiagnosticKind> {
+        let mut problems = Vec::new();
+
+        match column {
+            ProjectExportColumn::Number
+            | ProjectExportColumn::QueryNodeProperty { .. }
+            | ProjectExportColumn::MatchPosition { .. } => {}
+            ProjectExportColumn::AnnoCorpus { anno_key, .. } => {
+                check_anno_key(&mut problems, &self.corpus_anno_keys, anno_key.as_ref());
+            }
+            ProjectExportColumn::AnnoDocument { anno_key, .. } => {
+                check_anno_key(&mut problems, &self.doc_anno_keys, anno_key.as_ref());
+            }
+            ProjectExportColumn::AnnoMatch { anno_key, .. } => {
+                check_anno_key(&mut problems, &self.node_anno_keys, anno_key.as_ref());
+            }
+            ProjectExportColumn::AnnoEdge {
+                edge_type,
+                anno_key,
+                ..
+            } => {
+                if let Some(edge_anno_keys) =
+                    self.check_edge_type(&mut problems, edge_type.as_ref())
+                {
+                    check_anno_key(&mut problems, edge_anno_keys, anno_key.as_ref());
+                }
+            }
+            ProjectExportColumn::MatchInContext {
+                segmentation,
+                anno_key,
+                context_unit,
+                ..
+            } => {
+                self.check_segmentation(&mut problems, segmentation.as_deref());
+                check_anno_key(&mut problems, &self.node_anno_keys, anno_key_of(anno_key));
+                check_anno_key(&mut problems, &self.node_anno_keys, context_unit.as_ref());
+            }
+            ProjectExportColumn::CoveredText {
+                segmentation,
+                anno_key,
+                ..
+            } => {
+                self.check_segmentation(&mut problems, segmentation.as_deref());
+                check_anno_key(&mut problems, &self.node_anno_keys, anno_key_of(anno_key));
+            }
+            ProjectExportColumn::TraversalAnno {
+                edge_type,
+                item,
+                anno_key,
+                ..
+            } => {
+                if let Some(edge_anno_keys) =
+                    self.check_edge_type(&mut problems, edge_type.as_ref())
+                {
+                    let anno_keys = match item {
+                        TraversalItem::Nodes => &self.node_anno_keys,
+                        TraversalItem::Edges => edge_anno_keys,
+                    };
+
+                    check_anno_key(&mut problems, anno_keys, anno_key.as_ref());
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Returns the annotation keys of the given edge type if it is available.
+    fn check_edge_type(
+        &self,
+        problems: &mut Vec<ProjectDiagnosticKind>,
+        edge_type: Option<&EdgeType>,
+    ) -> Option<&HashSet<AnnoKey>> {
+        let edge_type = edge_type?;
+        let edge_anno_keys = self.edge_anno_keys.get(edge_type);
+
+        if edge_anno_keys.is_none() {
+            problems.push(ProjectDiagnosticKind::EdgeTypeNotFound {
+                edge_type: edge_type.clone(),
+            });
+        }
+
+        edge_anno_keys
+    }
+
+    fn check_segmentation(
+        &self,
+        problems: &mut Vec<ProjectDiagnosticKind>,
+        segmentation: Option<&str>,
+    ) {
+        // An empty segmentation refers to tokens, which are always available
+        if let Some(segmentation) = segmentation
+            && !segmentation.is_empty()
+            && !self.segmentations.contains(segmentation)
+        {
+            problems.push(ProjectDiagnosticKind::SegmentationNotFound {
+                segmentation: segmentation.into(),
+            });
+        }
+    }
+}
+
+fn check_anno_key(
+    problems: &mut Vec<ProjectDiagnosticKind>,
+    anno_keys: &HashSet<AnnoKey>,
+    anno_key: Option<&AnnoKey>,
+) {
+    if let Some(anno_key) = anno_key
+        && !anno_keys.contains(anno_key)
+    {
+        problems.push(ProjectDiagnosticKind::AnnoKeyNotFound {
+            anno_key: anno_key.clone(),
+        });
+    }
+}
+
+fn anno_key_of(anno_key: &Option<AnnoKeyOrDefault>) -> Option<&AnnoKey> {
+    match anno_key {
+        Some(AnnoKeyOrDefault::AnnoKey(anno_key)) => Some(anno_key),
+        Some(AnnoKeyOrDefault::Default) | None => None,
+    }
+}
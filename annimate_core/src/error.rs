@@ -11,6 +11,10 @@ use zip::result::ZipError;
 /// Error during an operation provided by Annimate.
 #[derive(Debug, Error)]
 pub enum AnnimateError {
+    /// A batch export into a single workbook was requested for a format other than XLSX.
+    #[error("Exporting several queries into a single workbook is only supported for XLSX exports")]
+    BatchWorkbookRequiresXlsx,
+
     /// Operation was cancelled.
     #[error("Cancelled")]
     Cancelled,
@@ -23,6 +27,13 @@ pub enum AnnimateError {
     #[error("Corpus set already exists")]
     CorpusSetAlreadyExists,
 
+    /// Project was expected to contain a single query.
+    #[error("Expected a project with a single query, but found {count} queries")]
+    ExpectedSingleQuery {
+        /// Number of queries in the project.
+        count: usize,
+    },
+
     /// Failed to delete corpora.
     #[error("Failed to delete corpora: {0}")]
     FailedToDeleteCorpora(AnnimateErrorCorpusNames),
@@ -49,6 +60,20 @@ pub enum AnnimateError {
     #[error("Failed to write XLSX workbook: {0}")]
     FailedToWriteXlsxWorkbook(#[from] XlsxError),
 
+    /// Frequency analysis was requested for an export format that doesn't support it.
+    #[error("Frequency analysis is only supported for CSV and XLSX exports")]
+    FrequenciesNotSupported,
+
+    /// Export column is missing a setting required for the export.
+    #[error("Column {} of query {} is incomplete", .index + 1, .query_index + 1)]
+    IncompleteExportColumn {
+        /// Index of the query the column belongs to.
+        query_index: usize,
+
+        /// Index of the column.
+        index: usize,
+    },
+
     /// Match node index out of bounds.
     #[error("Match node index {index} out of bounds")]
     MatchNodeIndexOutOfBounds {
@@ -64,6 +89,17 @@ pub enum AnnimateError {
     #[error("Annotation corresponding to segmentation {0} not found")]
     MissingAnnotationForSegmentation(String),
 
+    /// No output path was given for an export and none is configured in the project.
+    #[error("No output path given and none is stored in the project")]
+    MissingOutputPath,
+
+    /// Query took longer than the configured timeout.
+    #[error("Query timed out in corpus {corpus_name}")]
+    QueryTimedOut {
+        /// Name of the corpus in which the query timed out.
+        corpus_name: String,
+    },
+
     /// Wrapper for [`GraphAnnisError`].
     #[error(transparent)]
     Annis(#[from] GraphAnnisError),
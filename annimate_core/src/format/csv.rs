@@ -1,6 +1,8 @@
 use std::io::Write;
 
-use super::table::{self, TableExportColumn, TableWriter};
+use serde::{Deserialize, Serialize};
+
+use super::table::{self, TableCell, TableExportColumn, TableExportMode, TableWriter};
 use super::{AnnoKeyFormats, Exporter, QueryInfo};
 use crate::error::AnnimateError;
 use crate::query::{ExportData, Match};
@@ -13,18 +15,89 @@ pub(super) struct CsvExporter;
 pub struct CsvExportConfig {
     /// Columns to export.
     pub columns: Vec<TableExportColumn>,
+
+    /// What to export in the rows of the table.
+    pub mode: TableExportMode,
+
+    /// Dialect of the CSV file to produce.
+    pub dialect: CsvDialect,
+}
+
+/// Dialect of a CSV file, i.e. the details of how records and fields are written.
+///
+/// The default dialect produces files according to RFC 4180, with fields that spreadsheet
+/// applications would interpret as formulas escaped.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CsvDialect {
+    /// Character separating the fields of a record.
+    pub delimiter: CsvDelimiter,
+
+    /// When to put fields in quotes.
+    pub quote_style: CsvQuoteStyle,
+
+    /// Character sequence terminating each record.
+    pub line_terminator: CsvLineTerminator,
+
+    /// Whether to start the file with a UTF-8 byte order mark (BOM).
+    ///
+    /// This helps Microsoft Excel to detect the encoding of the file.
+    pub bom: bool,
+
+    /// Whether to escape fields starting with a character that makes spreadsheet applications
+    /// interpret them as formulas, such as `=` or `-`, by prepending a single quote (`'`).
+    ///
+    /// This prevents tokens such as `-` or annotation values such as `--` from being turned into
+    /// formulas when opening the file in Microsoft Excel, which corrupts the data and can be
+    /// exploited to execute commands. It is disabled by default, since it changes the values for
+    /// all other tools.
+    pub escape_formulas: bool,
+}
+
+/// Character separating the fields of a CSV record.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvDelimiter {
+    /// Comma (`,`)
+    #[default]
+    Comma,
+
+    /// Semicolon (`;`)
+    Semicolon,
+
+    /// Tab character, producing tab-separated values (TSV)
+    Tab,
+}
+
+/// When to put fields of a CSV record in quotes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvQuoteStyle {
+    /// Only quote fields containing special characters such as the delimiter, quotes or line breaks.
+    #[default]
+    Necessary,
+
+    /// Quote all fields.
+    Always,
+}
+
+/// Character sequence terminating a CSV record.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvLineTerminator {
+    /// Carriage return followed by line feed (`\r\n`), as used on Windows
+    #[default]
+    Crlf,
+
+    /// Line feed (`\n`), as used on Linux and macOS
+    Lf,
 }
 
 impl Exporter for CsvExporter {
     type Config = CsvExportConfig;
 
     fn get_export_data(config: &CsvExportConfig) -> Vec<ExportData> {
-        config
-            .columns
-            .iter()
-            .filter_map(TableExportColumn::data)
-            .cloned()
-            .collect()
+        table::get_export_data(&config.columns, config.mode)
     }
 
     fn export<F, G, I, S, W>(
@@ -45,26 +118,59 @@ impl Exporter for CsvExporter {
     {
         table::export(
             &config.columns,
+            config.mode,
+            false,
             matches_iter,
             query_info.nodes,
             anno_key_formats,
-            &mut CsvTableWriter::new(out),
+            &mut CsvTableWriter::new(out, config.dialect)?,
             on_matches_exported,
             cancel_requested,
         )
     }
 }
 
-struct CsvTableWriter<W>(csv::Writer<W>)
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Characters that make spreadsheet applications interpret a field as a formula when it starts
+/// with one of them.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+struct CsvTableWriter<W>
 where
-    W: Write;
+    W: Write,
+{
+    writer: csv::Writer<W>,
+    escape_formulas: bool,
+}
 
 impl<W> CsvTableWriter<W>
 where
     W: Write,
 {
-    fn new(inner: W) -> CsvTableWriter<W> {
-        CsvTableWriter(csv::Writer::from_writer(inner))
+    fn new(mut inner: W, dialect: CsvDialect) -> Result<CsvTableWriter<W>, AnnimateError> {
+        if dialect.bom {
+            inner.write_all(UTF8_BOM)?;
+        }
+
+        Ok(CsvTableWriter {
+            writer: csv::WriterBuilder::new()
+                .delimiter(match dialect.delimiter {
+                    CsvDelimiter::Comma => b',',
+                    CsvDelimiter::Semicolon => b';',
+                    CsvDelimiter::Tab => b'\t',
+                })
+                .quote_style(match dialect.quote_style {
+                    CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+                    CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+                })
+                .terminator(match dialect.line_terminator {
+                    CsvLineTerminator::Crlf => csv::Terminator::CRLF,
+                    CsvLineTerminator::Lf => csv::Terminator::Any(b'\n'),
+                })
+                .from_writer(inner),
+            escape_formulas: dialect.escape_formulas,
+        })
     }
 }
 
@@ -74,13 +180,31 @@ where
 {
     fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
     where
-        I: IntoIterator<Item: AsRef<str>>,
+        I: IntoIterator<Item: Into<TableCell>>,
     {
-        Ok(self.0.write_record(record.into_iter().map(StrAsBytes))?)
+        Ok(self.writer.write_record(record.into_iter().map(|cell| {
+            let text = cell.into().into_text();
+            StrAsBytes(if self.escape_formulas {
+                escape_formula(text)
+            } else {
+                text
+            })
+        }))?)
     }
 
     fn flush(&mut self) -> Result<(), AnnimateError> {
-        Ok(self.0.flush()?)
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Prepends a single quote to the text if it would otherwise be interpreted as a formula.
+///
+/// Numbers such as `-1` are left untouched since they are not formulas.
+fn escape_formula(text: String) -> String {
+    if text.starts_with(FORMULA_PREFIXES) && !text.parse::<f64>().is_ok_and(f64::is_finite) {
+        format!("'{text}")
+    } else {
+        text
     }
 }
 
@@ -123,11 +247,103 @@ mod tests {
 
     #[test]
     fn csv_table_writer_flush_propagates_underlying_error() {
-        let mut writer = CsvTableWriter::new(FailOnFlush);
+        let mut writer = CsvTableWriter::new(FailOnFlush, CsvDialect::default()).unwrap();
         writer.write_record(["a", "b"]).unwrap();
 
         let result = TableWriter::flush(&mut writer);
 
         assert!(result.is_err(), "expected flush error to propagate");
     }
+
+    #[test]
+    fn csv_table_writer_default_dialect() {
+        let mut out = Vec::new();
+        let mut writer = CsvTableWriter::new(&mut out, CsvDialect::default()).unwrap();
+        writer.write_record(["a", "b;c", "d,e"]).unwrap();
+        writer.write_record(["1", "2", "3"]).unwrap();
+        TableWriter::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a,b;c,\"d,e\"\r\n1,2,3\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_table_writer_custom_dialect() {
+        let mut out = Vec::new();
+        let mut writer = CsvTableWriter::new(
+            &mut out,
+            CsvDialect {
+                delimiter: CsvDelimiter::Semicolon,
+                quote_style: CsvQuoteStyle::Always,
+                line_terminator: CsvLineTerminator::Lf,
+                bom: true,
+                escape_formulas: true,
+            },
+        )
+        .unwrap();
+        writer.write_record(["a", "b;c", "d,e"]).unwrap();
+        writer.write_record(["1", "2", "3"]).unwrap();
+        TableWriter::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\u{feff}\"a\";\"b;c\";\"d,e\"\n\"1\";\"2\";\"3\"\n"
+        );
+    }
+
+    #[test]
+    fn csv_table_writer_tab_delimiter() {
+        let mut out = Vec::new();
+        let mut writer = CsvTableWriter::new(
+            &mut out,
+            CsvDialect {
+                delimiter: CsvDelimiter::Tab,
+                ..CsvDialect::default()
+            },
+        )
+        .unwrap();
+        writer.write_record(["a", "b\tc", "d,e"]).unwrap();
+        TableWriter::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(String::from_utf8(out).unwrap(), "a\t\"b\tc\"\td,e\r\n");
+    }
+
+    #[test]
+    fn csv_table_writer_escapes_formulas() {
+        let mut out = Vec::new();
+        let mut writer = CsvTableWriter::new(
+            &mut out,
+            CsvDialect {
+                escape_formulas: true,
+                ..CsvDialect::default()
+            },
+        )
+        .unwrap();
+        writer
+            .write_record(["=1+2", "+a", "-", "--", "@b", "\tc", "-1", "1.5", "a=b"])
+            .unwrap();
+        TableWriter::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "'=1+2,'+a,'-,'--,'@b,'\tc,-1,1.5,a=b\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_table_writer_does_not_escape_formulas_by_default() {
+        let mut out = Vec::new();
+        let mut writer = CsvTableWriter::new(&mut out, CsvDialect::default()).unwrap();
+        writer.write_record(["=1+2", "-"]).unwrap();
+        TableWriter::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(String::from_utf8(out).unwrap(), "=1+2,-\r\n");
+    }
 }
@@ -0,0 +1,172 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use graphannis_core::graph::{ANNIS_NS, NODE_NAME};
+use graphannis_core::types::AnnoKey;
+
+use super::AnnoKeyFormats;
+use super::table::{self, TableCell, TableExportColumn, TableWriter, ValueType};
+use crate::aql::QueryNode;
+use crate::error::{self, AnnimateError};
+use crate::query::{ExportData, ExportDataValue, Match};
+
+/// How the frequencies of a frequency table are split up.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FrequencySplit {
+    /// Count matches across all corpora.
+    #[default]
+    None,
+
+    /// Count matches separately for each corpus.
+    Corpus,
+
+    /// Count matches separately for each document.
+    Document,
+}
+
+impl FrequencySplit {
+    fn value(self) -> Option<ExportDataValue> {
+        let anno_key = AnnoKey {
+            ns: ANNIS_NS.into(),
+            name: NODE_NAME.into(),
+        };
+
+        match self {
+            FrequencySplit::None => None,
+            FrequencySplit::Corpus => Some(ExportDataValue::CorpusAnno { anno_key }),
+            FrequencySplit::Document => Some(ExportDataValue::DocumentAnno { anno_key }),
+        }
+    }
+
+    fn header(self) -> Option<&'static str> {
+        match self {
+            FrequencySplit::None => None,
+            FrequencySplit::Corpus => Some("Corpus"),
+            FrequencySplit::Document => Some("Document"),
+        }
+    }
+}
+
+pub(super) fn get_export_data(
+    columns: &[TableExportColumn],
+    split: FrequencySplit,
+) -> Vec<ExportData> {
+    split
+        .value()
+        .into_iter()
+        .chain(values(columns).map(|(value, _)| value.clone()))
+        .map(ExportData::Value)
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn export<F, G, I, W>(
+    columns: &[TableExportColumn],
+    split: FrequencySplit,
+    matches_iter: I,
+    query_nodes: &[Vec<QueryNode>],
+    anno_key_formats: AnnoKeyFormats<'_>,
+    out: &mut W,
+    on_matches_exported: F,
+    cancel_requested: G,
+) -> Result<(), AnnimateError>
+where
+    F: Fn(usize),
+    G: Fn() -> bool,
+    I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+    W: TableWriter,
+{
+    let split_value = split.value();
+
+    // Only the distinct combinations of values are kept in memory, not the matches themselves
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    let mut match_count = 0;
+
+    for (i, m) in matches_iter.enumerate() {
+        error::cancel_if(&cancel_requested)?;
+        on_matches_exported(i);
+
+        let m = m?;
+        let get_value = |value| {
+            m.values
+                .get(value)
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let group = groups
+            .entry(split_value.as_ref().map(get_value).unwrap_or_default())
+            .or_default();
+        group.total_count += 1;
+        *group
+            .counts
+            .entry(values(columns).map(|(value, _)| get_value(value)).collect())
+            .or_default() += 1;
+
+        match_count = i + 1;
+    }
+
+    error::cancel_if(&cancel_requested)?;
+    on_matches_exported(match_count);
+
+    out.write_record(
+        split
+            .header()
+            .map(String::from)
+            .into_iter()
+            .chain(
+                values(columns)
+                    .map(|(value, _)| table::value_header(value, query_nodes, anno_key_formats)),
+            )
+            .chain(["Count".into(), "Relative frequency".into()]),
+    )?;
+
+    for (split_key, group) in groups {
+        error::cancel_if(&cancel_requested)?;
+
+        let mut counts: Vec<_> = group.counts.into_iter().collect();
+        counts.sort_unstable_by(|(values_a, count_a), (values_b, count_b)| {
+            (Reverse(count_a), values_a).cmp(&(Reverse(count_b), values_b))
+        });
+
+        for (row_values, count) in counts {
+            out.write_record(
+                split_value
+                    .is_some()
+                    .then(|| TableCell::Text(split_key.clone()))
+                    .into_iter()
+                    .chain(
+                        row_values
+                            .into_iter()
+                            .zip(values(columns))
+                            .map(|(value, (_, value_type))| TableCell::Value(value, value_type)),
+                    )
+                    .chain([
+                        TableCell::Value(count.to_string(), ValueType::Integer),
+                        TableCell::Value(
+                            format!("{:.4}", count as f64 / group.total_count as f64),
+                            ValueType::Float,
+                        ),
+                    ]),
+            )?;
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Matches sharing the same value for the [`FrequencySplit`].
+#[derive(Debug, Default)]
+struct Group {
+    total_count: usize,
+    counts: HashMap<Vec<String>, usize>,
+}
+
+fn values(columns: &[TableExportColumn]) -> impl Iterator<Item = (&ExportDataValue, ValueType)> {
+    columns.iter().filter_map(|c| match c {
+        TableExportColumn::Data(ExportData::Value(value), value_type) => Some((value, *value_type)),
+        _ => None,
+    })
+}
@@ -0,0 +1,348 @@
+use std::fmt::Write as _;
+use std::io::{BufWriter, Write};
+
+use super::table::{self, TableExportColumn, TextFragment, TextFragmentKind};
+use super::{AnnoKeyFormats, Exporter, GAP_COLOR, MATCH_COLORS, QueryInfo};
+use crate::anno::{AnnoKeyFormat, AnnoKeyOrDefault};
+use crate::error::{self, AnnimateError};
+use crate::query::{ExportData, ExportDataText, Match};
+use crate::version::VERSION_INFO;
+
+#[derive(Debug)]
+pub(super) struct HtmlExporter;
+
+/// Configuration of an export in the HTML format.
+///
+/// The export is a self-contained web page with information about the export and a sortable table
+/// with one row per match. Every "match in context" column is exported as three columns for the
+/// left context, the match and the right context, aligned in KWIC (keyword in context) style.
+#[derive(Debug)]
+pub struct HtmlExportConfig {
+    /// Columns to export.
+    pub columns: Vec<TableExportColumn>,
+}
+
+impl Exporter for HtmlExporter {
+    type Config = HtmlExportConfig;
+
+    fn get_export_data(config: &HtmlExportConfig) -> Vec<ExportData> {
+        config
+            .columns
+            .iter()
+            .filter_map(TableExportColumn::data)
+            .cloned()
+            .collect()
+    }
+
+    fn export<F, G, I, S, W>(
+        config: &HtmlExportConfig,
+        matches_iter: I,
+        query_info: QueryInfo<'_, S>,
+        anno_key_formats: AnnoKeyFormats<'_>,
+        out: W,
+        on_matches_exported: F,
+        cancel_requested: G,
+    ) -> Result<(), AnnimateError>
+    where
+        F: Fn(usize),
+        G: Fn() -> bool,
+        I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+        S: AsRef<str>,
+        W: Write + Send,
+    {
+        let mut out = BufWriter::new(out);
+
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, r#"<meta charset="utf-8">"#)?;
+        writeln!(
+            out,
+            r#"<meta name="generator" content="Annimate v{}">"#,
+            VERSION_INFO.annimate_version
+        )?;
+        writeln!(out, "<title>{}</title>", escape(query_info.aql_query))?;
+        writeln!(out, "<style>\n{}</style>", style())?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+
+        writeln!(out, r#"<table class="info">"#)?;
+        for (name, value) in query_info.info_rows() {
+            writeln!(
+                out,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(name),
+                escape(&value)
+            )?;
+        }
+        writeln!(out, "</table>")?;
+
+        writeln!(out, r#"<table class="data">"#)?;
+        writeln!(out, "<thead>")?;
+        write!(out, "<tr>")?;
+        for column in &config.columns {
+            match column {
+                TableExportColumn::Number => write!(out, "<th>Number</th>")?,
+                TableExportColumn::Data(ExportData::Value(value), _) => write!(
+                    out,
+                    "<th>{}</th>",
+                    escape(&table::value_header(
+                        value,
+                        query_info.nodes,
+                        anno_key_formats
+                    ))
+                )?,
+                TableExportColumn::Data(ExportData::Text(text), _) => {
+                    let suffix = text_header_suffix(text, anno_key_formats.node);
+
+                    write!(
+                        out,
+                        r#"<th data-sort="reverse">Left context{suffix}</th><th>Match{suffix}</th><th>Right context{suffix}</th>"#,
+                        suffix = escape(&suffix)
+                    )?;
+                }
+            }
+        }
+        writeln!(out, "</tr>")?;
+        writeln!(out, "</thead>")?;
+        writeln!(out, "<tbody>")?;
+
+        let mut count = 0;
+
+        for (i, m) in matches_iter.enumerate() {
+            error::cancel_if(&cancel_requested)?;
+            on_matches_exported(i);
+
+            let Match { values, texts } = m?;
+
+            write!(out, "<tr>")?;
+            for column in &config.columns {
+                match column {
+                    TableExportColumn::Number => write!(out, "<td>{}</td>", i + 1)?,
+                    TableExportColumn::Data(ExportData::Value(value), _) => write!(
+                        out,
+                        "<td>{}</td>",
+                        escape(values.get(value).map(|s| s.trim()).unwrap_or_default())
+                    )?,
+                    TableExportColumn::Data(ExportData::Text(text), _) => {
+                        let fragments = table::text_fragments(
+                            texts.get(text).map(Vec::as_slice).unwrap_or_default(),
+                            &text.formatting,
+                        );
+                        let (left, middle, right) = split_kwic(&fragments);
+
+                        write!(
+                            out,
+                            r#"<td class="left">{}</td><td class="match">{}</td><td class="right">{}</td>"#,
+                            render_fragments(left),
+                            render_fragments(middle),
+                            render_fragments(right)
+                        )?;
+                    }
+                }
+            }
+            writeln!(out, "</tr>")?;
+
+            count = i + 1;
+        }
+
+        error::cancel_if(&cancel_requested)?;
+        on_matches_exported(count);
+
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+        writeln!(out, "<script>\n{SCRIPT}</script>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+const STYLE: &str = r#"body { font-family: sans-serif; margin: 1em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.5em; text-align: left; vertical-align: top; }
+table.info { margin-bottom: 1em; }
+table.data th { background: #e8e8e8; cursor: pointer; position: sticky; top: 0; user-select: none; }
+table.data th[data-order="asc"]::after { content: " \25B2"; }
+table.data th[data-order="desc"]::after { content: " \25BC"; }
+table.data tbody tr:nth-child(even) { background: #f6f6f6; }
+td.left { text-align: right; white-space: nowrap; }
+td.match { text-align: center; white-space: nowrap; }
+td.right { white-space: nowrap; }
+.m { font-weight: bold; }
+"#;
+
+/// Sorts the data table by a column when clicking on its header, alternating between ascending and
+/// descending order.
+///
+/// Left context columns are sorted by the words closest to the match first.
+const SCRIPT: &str = r#"const collator = new Intl.Collator(undefined, { numeric: true });
+document.querySelectorAll("table.data th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const ascending = th.dataset.order !== "asc";
+    th.parentElement.querySelectorAll("th").forEach((other) => delete other.dataset.order);
+    th.dataset.order = ascending ? "asc" : "desc";
+    const key = (row) => {
+      const text = row.cells[column].textContent;
+      return th.dataset.sort === "reverse" ? text.split(/\s+/).reverse().join(" ") : text;
+    };
+    const tbody = th.closest("table").tBodies[0];
+    const rows = Array.from(tbody.rows);
+    rows.sort((a, b) => collator.compare(key(a), key(b)) * (ascending ? 1 : -1));
+    tbody.append(...rows);
+  });
+});
+"#;
+
+fn style() -> String {
+    let mut style = STYLE.to_string();
+
+    for (index, color) in MATCH_COLORS.iter().enumerate() {
+        writeln!(style, ".m{index} {{ color: #{color:06X}; }}").unwrap();
+    }
+
+    writeln!(
+        style,
+        ".gap {{ color: #{GAP_COLOR:06X}; font-style: italic; }}"
+    )
+    .unwrap();
+
+    style
+}
+
+fn text_header_suffix(text: &ExportDataText, node_anno_key_format: &AnnoKeyFormat) -> String {
+    format!(
+        "{} ({})",
+        match &text.anno_key {
+            AnnoKeyOrDefault::AnnoKey(anno_key) =>
+                format!(" {}", node_anno_key_format.display(anno_key)),
+            AnnoKeyOrDefault::Default => "".into(),
+        },
+        text.segmentation.as_deref().unwrap_or("tokens")
+    )
+}
+
+/// Splits the fragments of the text of a match into the left context, the part from the first to
+/// the last fragment belonging to a match node, and the right context.
+///
+/// Separators at the borders of the three parts are dropped.
+fn split_kwic(fragments: &[TextFragment]) -> (&[TextFragment], &[TextFragment], &[TextFragment]) {
+    let is_match = |f: &TextFragment| matches!(f.kind, TextFragmentKind::Match(_));
+
+    let (left, middle, right) = match (
+        fragments.iter().position(is_match),
+        fragments.iter().rposition(is_match),
+    ) {
+        (Some(first), Some(last)) => (
+            &fragments[..first],
+            &fragments[first..=last],
+            &fragments[last + 1..],
+        ),
+        _ => (fragments, &[][..], &[][..]),
+    };
+
+    (
+        trim_separators(left),
+        trim_separators(middle),
+        trim_separators(right),
+    )
+}
+
+fn trim_separators(mut fragments: &[TextFragment]) -> &[TextFragment] {
+    let is_separator = |f: &TextFragment| f.kind == TextFragmentKind::Separator;
+
+    while let [first, rest @ ..] = fragments
+        && is_separator(first)
+    {
+        fragments = rest;
+    }
+
+    while let [rest @ .., last] = fragments
+        && is_separator(last)
+    {
+        fragments = rest;
+    }
+
+    fragments
+}
+
+fn render_fragments(fragments: &[TextFragment]) -> String {
+    fragments
+        .iter()
+        .map(|fragment| {
+            let text = escape(&fragment.text);
+
+            match fragment.kind {
+                TextFragmentKind::Match(index) => format!(
+                    r#"<span class="m m{}">{text}</span>"#,
+                    index % MATCH_COLORS.len()
+                ),
+                TextFragmentKind::Gap => format!(r#"<span class="gap">{text}</span>"#),
+                TextFragmentKind::Context | TextFragmentKind::Separator => text,
+            }
+        })
+        .collect()
+}
+
+/// Escapes characters with a special meaning in HTML.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_replaces_special_characters() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn split_kwic_splits_at_first_and_last_match() {
+        let fragment = |kind, text: &str| TextFragment {
+            kind,
+            text: text.into(),
+        };
+
+        let fragments = [
+            fragment(TextFragmentKind::Context, "a"),
+            fragment(TextFragmentKind::Separator, " "),
+            fragment(TextFragmentKind::Match(0), "b"),
+            fragment(TextFragmentKind::Separator, " "),
+            fragment(TextFragmentKind::Context, "c"),
+            fragment(TextFragmentKind::Separator, " "),
+            fragment(TextFragmentKind::Match(1), "d"),
+            fragment(TextFragmentKind::Separator, " "),
+            fragment(TextFragmentKind::Gap, "(...)"),
+        ];
+
+        let (left, middle, right) = split_kwic(&fragments);
+
+        assert_eq!(render_fragments(left), "a");
+        assert_eq!(
+            render_fragments(middle),
+            r#"<span class="m m0">b</span> c <span class="m m1">d</span>"#
+        );
+        assert_eq!(render_fragments(right), r#"<span class="gap">(...)</span>"#);
+    }
+}
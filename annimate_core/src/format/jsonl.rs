@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufWriter, Write};
+
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use super::table::{self, TableExportColumn};
+use super::{AnnoKeyFormats, Exporter, QueryInfo};
+use crate::anno::{AnnoKeyFormat, AnnoKeyOrDefault};
+use crate::error::{self, AnnimateError};
+use crate::query::{ExportData, ExportDataText, Match, TextPart};
+
+#[derive(Debug)]
+pub(super) struct JsonlExporter;
+
+/// Configuration of an export in the JSON Lines format.
+///
+/// Every match is exported as a JSON object on its own line, with one field per column.
+#[derive(Debug)]
+pub struct JsonlExportConfig {
+    /// Columns to export as fields.
+    pub columns: Vec<TableExportColumn>,
+}
+
+impl Exporter for JsonlExporter {
+    type Config = JsonlExportConfig;
+
+    fn get_export_data(config: &JsonlExportConfig) -> Vec<ExportData> {
+        config
+            .columns
+            .iter()
+            .filter_map(TableExportColumn::data)
+            .cloned()
+            .collect()
+    }
+
+    fn export<F, G, I, S, W>(
+        config: &JsonlExportConfig,
+        matches_iter: I,
+        query_info: QueryInfo<'_, S>,
+        anno_key_formats: AnnoKeyFormats<'_>,
+        out: W,
+        on_matches_exported: F,
+        cancel_requested: G,
+    ) -> Result<(), AnnimateError>
+    where
+        F: Fn(usize),
+        G: Fn() -> bool,
+        I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+        S: AsRef<str>,
+        W: Write + Send,
+    {
+        let keys = deduplicate_keys(config.columns.iter().map(|c| match c {
+            TableExportColumn::Number => "Number".into(),
+            TableExportColumn::Data(ExportData::Value(value), _) => {
+                table::value_header(value, query_info.nodes, anno_key_formats)
+            }
+            TableExportColumn::Data(ExportData::Text(text), _) => {
+                text_key(text, anno_key_formats.node)
+            }
+        }));
+
+        let mut out = BufWriter::new(out);
+        let mut count = 0;
+
+        for (i, m) in matches_iter.enumerate() {
+            error::cancel_if(&cancel_requested)?;
+            on_matches_exported(i);
+
+            let Match { values, texts } = m?;
+
+            let record = JsonlRecord(
+                keys.iter()
+                    .zip(&config.columns)
+                    .map(|(key, c)| {
+                        let field = match c {
+                            TableExportColumn::Number => JsonlField::Number(i + 1),
+                            TableExportColumn::Data(ExportData::Value(value), _) => {
+                                JsonlField::Value(values.get(value).map(|s| s.trim()))
+                            }
+                            TableExportColumn::Data(ExportData::Text(text), _) => JsonlField::Text(
+                                texts.get(text).map(Vec::as_slice).unwrap_or_default(),
+                            ),
+                        };
+
+                        (key.as_str(), field)
+                    })
+                    .collect(),
+            );
+
+            serde_json::to_writer(&mut out, &record)?;
+            out.write_all(b"\n")?;
+
+            count = i + 1;
+        }
+
+        error::cancel_if(&cancel_requested)?;
+        on_matches_exported(count);
+
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Object representing a single match, serialized with its fields in the order of the columns.
+struct JsonlRecord<'a>(Vec<(&'a str, JsonlField<'a>)>);
+
+impl Serialize for JsonlRecord<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, field) in &self.0 {
+            map.serialize_entry(key, field)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum JsonlField<'a> {
+    Number(usize),
+    Value(Option<&'a str>),
+    Text(&'a [TextPart]),
+}
+
+fn text_key(text: &ExportDataText, node_anno_key_format: &AnnoKeyFormat) -> String {
+    format!(
+        "Match in context{} ({})",
+        match &text.anno_key {
+            AnnoKeyOrDefault::AnnoKey(anno_key) =>
+                format!(" {}", node_anno_key_format.display(anno_key)),
+            AnnoKeyOrDefault::Default => "".into(),
+        },
+        text.segmentation.as_deref().unwrap_or("tokens")
+    )
+}
+
+/// Makes keys unique by appending a running number to repeated keys, e.g. `key`, `key (2)`.
+fn deduplicate_keys<I>(keys: I) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut used_keys = HashSet::new();
+    let mut next_numbers = HashMap::new();
+
+    keys.into_iter()
+        .map(|key| {
+            let key = if used_keys.contains(&key) {
+                // Keys such as "a (2)" may occur themselves, so skip numbers that are already used
+                let number = next_numbers.entry(key.clone()).or_insert(2);
+                loop {
+                    let numbered_key = format!("{key} ({number})");
+                    *number += 1;
+                    if !used_keys.contains(&numbered_key) {
+                        break numbered_key;
+                    }
+                }
+            } else {
+                key
+            };
+
+            used_keys.insert(key.clone());
+            key
+        })
+        .collect()
+}
+
+impl From<serde_json::Error> for AnnimateError {
+    fn from(err: serde_json::Error) -> AnnimateError {
+        AnnimateError::Io(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicate_keys_appends_running_number() {
+        let keys = deduplicate_keys(["a", "b", "a", "a", "b", "c"].map(String::from));
+
+        assert_eq!(keys, ["a", "b", "a (2)", "a (3)", "b (2)", "c"]);
+
+        let keys = deduplicate_keys(["a", "a (2)", "a"].map(String::from));
+
+        assert_eq!(keys, ["a", "a (2)", "a (3)"]);
+
+        let keys = deduplicate_keys(["a", "a", "a (2)"].map(String::from));
+
+        assert_eq!(keys, ["a", "a (2)", "a (2) (2)"]);
+    }
+}
@@ -2,20 +2,35 @@ use std::io::Write;
 
 use csv::CsvExporter;
 use graphannis::corpusstorage::QueryLanguage;
+use html::HtmlExporter;
+use itertools::Itertools;
+use jsonl::JsonlExporter;
+use ods::OdsExporter;
 use xlsx::XlsxExporter;
 
+use crate::CorpusMatchCount;
 use crate::anno::{AnnoKeyFormat, EdgeType};
 use crate::aql::QueryNode;
 use crate::error::AnnimateError;
 use crate::query::{ExportData, Match};
+use crate::version::VERSION_INFO;
 
 mod csv;
+mod frequency;
+mod html;
+mod jsonl;
+mod ods;
 mod table;
 mod xlsx;
 
-pub use csv::CsvExportConfig;
-pub use table::TableExportColumn;
-pub use xlsx::XlsxExportConfig;
+pub use csv::{CsvDelimiter, CsvDialect, CsvExportConfig, CsvLineTerminator, CsvQuoteStyle};
+pub use frequency::FrequencySplit;
+pub use html::HtmlExportConfig;
+pub use jsonl::JsonlExportConfig;
+pub use ods::OdsExportConfig;
+pub use table::{TableExportColumn, TableExportMode, ValueType};
+pub(crate) use xlsx::XlsxSheets;
+pub use xlsx::{XlsxExportConfig, XlsxOptions};
 
 /// A format in which matches can be exported.
 #[derive(Debug)]
@@ -25,6 +40,15 @@ pub enum ExportFormat {
 
     /// XLSX (Excel)
     Xlsx(XlsxExportConfig),
+
+    /// JSON Lines (one JSON object per match)
+    Jsonl(JsonlExportConfig),
+
+    /// HTML (self-contained web page with a concordance table)
+    Html(HtmlExportConfig),
+
+    /// ODS (Open Document Spreadsheet)
+    Ods(OdsExportConfig),
 }
 
 impl ExportFormat {
@@ -32,6 +56,20 @@ impl ExportFormat {
         match self {
             ExportFormat::Csv(config) => CsvExporter::get_export_data(config),
             ExportFormat::Xlsx(config) => XlsxExporter::get_export_data(config),
+            ExportFormat::Jsonl(config) => JsonlExporter::get_export_data(config),
+            ExportFormat::Html(config) => HtmlExporter::get_export_data(config),
+            ExportFormat::Ods(config) => OdsExporter::get_export_data(config),
+        }
+    }
+
+    /// Returns the usual extension of files in this format.
+    pub(crate) fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv(_) => "csv",
+            ExportFormat::Xlsx(_) => "xlsx",
+            ExportFormat::Jsonl(_) => "jsonl",
+            ExportFormat::Html(_) => "html",
+            ExportFormat::Ods(_) => "ods",
         }
     }
 }
@@ -42,8 +80,53 @@ pub(crate) struct QueryInfo<'a, S> {
     pub(crate) aql_query: &'a str,
     pub(crate) query_language: QueryLanguage,
     pub(crate) nodes: &'a [Vec<QueryNode>],
+    pub(crate) random_seed: Option<u64>,
+    pub(crate) counts: Option<&'a [CorpusMatchCount]>,
+}
+
+impl<S> QueryInfo<'_, S>
+where
+    S: AsRef<str>,
+{
+    /// Returns information about the export that helps document and reproduce it, as pairs of a
+    /// name and a value.
+    fn info_rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![
+            ("Query", self.aql_query.to_string()),
+            (
+                "Query Language",
+                match self.query_language {
+                    QueryLanguage::AQL => "AQL (latest)",
+                    QueryLanguage::AQLQuirksV3 => "AQL (compatibility mode)",
+                }
+                .to_string(),
+            ),
+            (
+                "Corpora",
+                self.corpus_names.iter().map(|s| s.as_ref()).join(","),
+            ),
+            (
+                "Annimate version",
+                VERSION_INFO.annimate_version.to_string(),
+            ),
+        ];
+
+        if let Some(random_seed) = self.random_seed {
+            rows.push(("Random seed", random_seed.to_string()));
+        }
+
+        rows
+    }
 }
 
+/// Font colors of the parts of the text belonging to match nodes, by match index.
+///
+/// Colors are reused cyclically if there are more match nodes.
+const MATCH_COLORS: [u32; 6] = [0xC00000, 0x0070C0, 0x00B050, 0x7030A0, 0xED7D31, 0x808000];
+
+/// Font color of gaps in the text of a match.
+const GAP_COLOR: u32 = 0x808080;
+
 #[derive(Clone, Copy)]
 pub(crate) struct AnnoKeyFormats<'a> {
     pub(crate) node: &'a AnnoKeyFormat,
@@ -85,9 +168,72 @@ where
             on_matches_exported,
             cancel_requested,
         ),
+        ExportFormat::Jsonl(config) => JsonlExporter::export(
+            &config,
+            matches_iter,
+            query_info,
+            anno_key_formats,
+            out,
+            on_matches_exported,
+            cancel_requested,
+        ),
+        ExportFormat::Html(config) => HtmlExporter::export(
+            &config,
+            matches_iter,
+            query_info,
+            anno_key_formats,
+            out,
+            on_matches_exported,
+            cancel_requested,
+        ),
+        ExportFormat::Ods(config) => OdsExporter::export(
+            &config,
+            matches_iter,
+            query_info,
+            anno_key_formats,
+            out,
+            on_matches_exported,
+            cancel_requested,
+        ),
     }
 }
 
+/// Exports matches into XLSX worksheets, which can be saved into a single workbook together with
+/// those of other queries using [`save_xlsx_workbook`].
+pub(crate) fn export_xlsx_sheets<F, G, I, S>(
+    config: &XlsxExportConfig,
+    name: &str,
+    matches_iter: I,
+    query_info: &QueryInfo<'_, S>,
+    anno_key_formats: AnnoKeyFormats<'_>,
+    on_matches_exported: F,
+    cancel_requested: G,
+) -> Result<XlsxSheets, AnnimateError>
+where
+    F: Fn(usize),
+    G: Fn() -> bool,
+    I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+    S: AsRef<str>,
+{
+    XlsxExporter::export_sheets(
+        config,
+        name,
+        matches_iter,
+        query_info,
+        anno_key_formats,
+        on_matches_exported,
+        cancel_requested,
+    )
+}
+
+/// Saves the XLSX worksheets of one or more queries into a single workbook.
+pub(crate) fn save_xlsx_workbook<W>(sheets: Vec<XlsxSheets>, out: W) -> Result<(), AnnimateError>
+where
+    W: Write + Send,
+{
+    XlsxExporter::save_workbook(sheets, out)
+}
+
 trait Exporter {
     type Config;
 
@@ -0,0 +1,286 @@
+use std::io::{self, BufWriter, Seek, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::table::{self, TableCell, TableExportColumn, TableExportMode, TableWriter};
+use super::{AnnoKeyFormats, Exporter, QueryInfo};
+use crate::error::AnnimateError;
+use crate::query::{ExportData, Match};
+use crate::version::VERSION_INFO;
+
+#[derive(Debug)]
+pub(super) struct OdsExporter;
+
+/// Configuration of an export in the ODS (Open Document Spreadsheet) format.
+#[derive(Debug)]
+pub struct OdsExportConfig {
+    /// Columns to export.
+    pub columns: Vec<TableExportColumn>,
+
+    /// What to export in the rows of the table.
+    pub mode: TableExportMode,
+}
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">
+<manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+<manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+const CONTENT_START: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.3">
+<office:automatic-styles>
+<style:style style:name="header" style:family="table-cell"><style:text-properties fo:font-weight="bold"/></style:style>
+</office:automatic-styles>
+<office:body>
+<office:spreadsheet>
+"#;
+
+const CONTENT_END: &str = r#"</office:spreadsheet>
+</office:body>
+</office:document-content>
+"#;
+
+impl Exporter for OdsExporter {
+    type Config = OdsExportConfig;
+
+    fn get_export_data(config: &OdsExportConfig) -> Vec<ExportData> {
+        table::get_export_data(&config.columns, config.mode)
+    }
+
+    fn export<F, G, I, S, W>(
+        config: &OdsExportConfig,
+        matches_iter: I,
+        query_info: QueryInfo<'_, S>,
+        anno_key_formats: AnnoKeyFormats<'_>,
+        mut out: W,
+        on_matches_exported: F,
+        cancel_requested: G,
+    ) -> Result<(), AnnimateError>
+    where
+        F: Fn(usize),
+        G: Fn() -> bool,
+        I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+        S: AsRef<str>,
+        W: Write + Send,
+    {
+        // The archive is built in a temporary file because writing it to a non-seekable stream
+        // would add data descriptors to the `mimetype` entry, which the OpenDocument format forbids
+        let mut zip = ZipWriter::new(tempfile::tempfile()?);
+
+        zip.start_file(
+            "mimetype",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(MIMETYPE.as_bytes())?;
+
+        zip.start_file("META-INF/manifest.xml", SimpleFileOptions::default())?;
+        zip.write_all(MANIFEST.as_bytes())?;
+
+        zip.start_file("meta.xml", SimpleFileOptions::default())?;
+        write!(
+            zip,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" office:version="1.3">
+<office:meta><meta:generator>Annimate/{}</meta:generator></office:meta>
+</office:document-meta>
+"#,
+            VERSION_INFO.annimate_version
+        )?;
+
+        zip.start_file("content.xml", SimpleFileOptions::default())?;
+        {
+            let mut content = BufWriter::new(&mut zip);
+            content.write_all(CONTENT_START.as_bytes())?;
+
+            let mut ods_table_writer = OdsTableWriter::new(&mut content, "Data");
+
+            table::export(
+                &config.columns,
+                config.mode,
+                false,
+                matches_iter,
+                query_info.nodes,
+                anno_key_formats,
+                &mut ods_table_writer,
+                on_matches_exported,
+                cancel_requested,
+            )?;
+
+            ods_table_writer.finish()?;
+
+            let mut info_table_writer = OdsTableWriter::new(&mut content, "Information");
+            for (name, value) in query_info.info_rows() {
+                info_table_writer.write_row([name, value.as_str()], None)?;
+            }
+            info_table_writer.finish()?;
+
+            content.write_all(CONTENT_END.as_bytes())?;
+            content.flush()?;
+        }
+
+        let mut file = zip.finish()?;
+        file.rewind()?;
+        io::copy(&mut file, &mut out)?;
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Writes a table into the `content.xml` of an ODS file.
+///
+/// The first record is treated as a header row.
+struct OdsTableWriter<'a, W> {
+    inner: &'a mut W,
+    name: &'static str,
+    rows: usize,
+}
+
+impl<'a, W> OdsTableWriter<'a, W>
+where
+    W: Write,
+{
+    fn new(inner: &'a mut W, name: &'static str) -> OdsTableWriter<'a, W> {
+        OdsTableWriter {
+            inner,
+            name,
+            rows: 0,
+        }
+    }
+
+    fn write_row<I>(&mut self, cells: I, style: Option<&str>) -> Result<(), AnnimateError>
+    where
+        I: IntoIterator<Item: AsRef<str>>,
+    {
+        let cells: Vec<_> = cells.into_iter().collect();
+
+        if self.rows == 0 {
+            // The column count is only known once the first row arrives, but the column
+            // declarations must precede all rows
+            writeln!(
+                self.inner,
+                r#"<table:table table:name="{}"><table:table-column table:number-columns-repeated="{}"/>"#,
+                self.name,
+                cells.len().max(1)
+            )?;
+        }
+
+        write!(self.inner, "<table:table-row>")?;
+        for cell in cells {
+            write_cell(self.inner, cell.as_ref(), style)?;
+        }
+        writeln!(self.inner, "</table:table-row>")?;
+
+        self.rows += 1;
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), AnnimateError> {
+        if self.rows == 0 {
+            writeln!(
+                self.inner,
+                r#"<table:table table:name="{}"><table:table-column/>"#,
+                self.name
+            )?;
+        }
+
+        writeln!(self.inner, "</table:table>")?;
+
+        Ok(())
+    }
+}
+
+impl<W> TableWriter for OdsTableWriter<'_, W>
+where
+    W: Write,
+{
+    fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
+    where
+        I: IntoIterator<Item: Into<TableCell>>,
+    {
+        let style = (self.rows == 0).then_some("header");
+
+        self.write_row(
+            record.into_iter().map(|cell| cell.into().into_text()),
+            style,
+        )
+    }
+
+    fn flush(&mut self) -> Result<(), AnnimateError> {
+        Ok(self.inner.flush()?)
+    }
+}
+
+fn write_cell<W>(out: &mut W, text: &str, style: Option<&str>) -> Result<(), AnnimateError>
+where
+    W: Write,
+{
+    write!(out, "<table:table-cell")?;
+    if let Some(style) = style {
+        write!(out, r#" table:style-name="{style}""#)?;
+    }
+
+    if text.is_empty() {
+        write!(out, "/>")?;
+    } else {
+        write!(
+            out,
+            r#" office:value-type="string">{}</table:table-cell>"#,
+            text_paragraphs(text)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Converts text into ODS text paragraphs, one per line.
+///
+/// Since XML processors collapse whitespace, tabs and repeated spaces are encoded as elements of
+/// their own. Characters that are not allowed in XML are dropped.
+fn text_paragraphs(text: &str) -> String {
+    let mut paragraphs = String::with_capacity(text.len());
+
+    for line in text.split('\n') {
+        paragraphs.push_str("<text:p>");
+
+        let mut after_space = true;
+
+        for c in line.chars() {
+            match c {
+                ' ' if after_space => paragraphs.push_str("<text:s/>"),
+                '\t' => paragraphs.push_str("<text:tab/>"),
+                '&' => paragraphs.push_str("&amp;"),
+                '<' => paragraphs.push_str("&lt;"),
+                '>' => paragraphs.push_str("&gt;"),
+                c if c.is_control() => {}
+                c => paragraphs.push(c),
+            }
+
+            after_space = c == ' ';
+        }
+
+        paragraphs.push_str("</text:p>");
+    }
+
+    paragraphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_paragraphs_preserves_whitespace_and_escapes() {
+        assert_eq!(
+            text_paragraphs(" a  b\tc\r\n<d> & e"),
+            "<text:p><text:s/>a <text:s/>b<text:tab/>c</text:p><text:p>&lt;d&gt; &amp; e</text:p>"
+        );
+    }
+}
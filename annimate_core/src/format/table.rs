@@ -1,15 +1,21 @@
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Display;
+use std::io::{BufRead, BufReader, BufWriter, IntoInnerError, Seek, Write};
 use std::ops::Range;
 use std::vec;
 
 use itertools::{Itertools, PutBack};
+use serde::{Deserialize, Serialize};
 
 use super::AnnoKeyFormats;
+use super::frequency::{self, FrequencySplit};
 use crate::anno::{self, AnnoKeyOrDefault};
 use crate::aql::{QueryNode, QueryNodePropertyKey};
 use crate::error::{self, AnnimateError};
-use crate::query::{ExportData, ExportDataText, ExportDataValue, Match, TextPart};
+use crate::query::{
+    AnnoAggregation, ExportData, ExportDataText, ExportDataValue, Match, NodePosition,
+    TextFormatting, TextPart, Traversal, TraversalItem,
+};
 
 #[derive(Clone, Copy, Debug)]
 enum ColumnType {
@@ -20,34 +26,315 @@ enum ColumnType {
 use ColumnType::*;
 
 /// Configuration of what to export in a single table column.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub enum TableExportColumn {
     /// Number of the match, numbered sequentially starting from 1.
     Number,
 
-    /// Data of the match.
-    Data(ExportData),
+    /// Data of the match, together with the type of its values.
+    ///
+    /// The value type is ignored for [`ExportData::Text`], which is always exported as text.
+    Data(ExportData, ValueType),
 }
 
 impl TableExportColumn {
     pub(super) fn data(&self) -> Option<&ExportData> {
         match self {
-            TableExportColumn::Data(data) => Some(data),
+            TableExportColumn::Data(data, _) => Some(data),
             _ => None,
         }
     }
 }
 
+/// Type of the values of a table column.
+///
+/// This determines how values are written in formats distinguishing between types of cells, such
+/// as XLSX, so that e.g. sorting and filtering numbers works as expected. Values that cannot be
+/// interpreted as the given type are written as text. Other formats always contain the values as
+/// they are.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValueType {
+    /// Detect the type of each value: Plain integers (e.g. `42`) and decimal numbers (e.g.
+    /// `-0.5`) are numbers, dates of the form `YYYY-MM-DD` are dates and everything else is text.
+    ///
+    /// Numbers that a spreadsheet application would display differently, such as `007`, `1.10` or
+    /// `-0`, are considered text, since e.g. identifiers and version numbers would be changed.
+    #[default]
+    Auto,
+
+    /// Text.
+    Text,
+
+    /// Integer, e.g. `42` or `007`.
+    Integer,
+
+    /// Decimal number, e.g. `3.14` or `1e-3`.
+    Float,
+
+    /// Date of the form `YYYY-MM-DD`.
+    Date,
+}
+
+/// Value of a table cell interpreted according to a [`ValueType`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum TypedValue {
+    Number(f64),
+    Date { year: u16, month: u8, day: u8 },
+}
+
+/// Maximum number of significant digits of numbers, beyond which spreadsheet applications lose
+/// precision.
+const MAX_NUMBER_DIGITS: usize = 15;
+
+impl ValueType {
+    /// Interprets the given text as a value of this type.
+    ///
+    /// Returns [`None`] if the text is to be written as text.
+    pub(super) fn parse(self, text: &str) -> Option<TypedValue> {
+        match self {
+            ValueType::Auto => parse_plain_number(text).or_else(|| parse_date(text)),
+            ValueType::Text => None,
+            ValueType::Integer => {
+                let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+                (digits.len() <= MAX_NUMBER_DIGITS)
+                    .then(|| text.parse::<i64>().ok())
+                    .flatten()
+                    .map(|n| TypedValue::Number(n as f64))
+            }
+            ValueType::Float => text
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(TypedValue::Number),
+            ValueType::Date => parse_date(text),
+        }
+    }
+}
+
+/// Parses a number written as `-?(0|[1-9][0-9]*)(\.[0-9]+)?` with a limited number of digits.
+///
+/// Only numbers that are written the same way after parsing are accepted, so e.g. trailing zeros
+/// of decimals aren't lost.
+fn parse_plain_number(text: &str) -> Option<TypedValue> {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (unsigned, None),
+    };
+
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    let valid = is_digits(int)
+        && (int == "0" || !int.starts_with('0'))
+        && frac.is_none_or(is_digits)
+        && int.len() + frac.map_or(0, str::len) <= MAX_NUMBER_DIGITS;
+
+    valid
+        .then(|| text.parse::<f64>().ok())
+        .flatten()
+        .filter(|n| *n != 0.0 || n.is_sign_positive())
+        .filter(|n| n.to_string() == text)
+        .map(TypedValue::Number)
+}
+
+/// Parses a date written as `YYYY-MM-DD`.
+fn parse_date(text: &str) -> Option<TypedValue> {
+    let [year, month, day] = text.splitn(3, '-').collect_array()?;
+
+    let parse = |s: &str, len| -> Option<u16> {
+        (s.len() == len && s.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| s.parse().ok())
+            .flatten()
+    };
+
+    let year = parse(year, 4)?;
+    let month = parse(month, 2).filter(|m| (1..=12).contains(m))?;
+    let day = parse(day, 2).filter(|d| (1..=31).contains(d))?;
+
+    Some(TypedValue::Date {
+        year,
+        month: month as u8,
+        day: day as u8,
+    })
+}
+
+/// What to export in the rows of a table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TableExportMode {
+    /// One row per match.
+    #[default]
+    Matches,
+
+    /// One row per distinct combination of the values of the data columns, with the absolute and
+    /// relative number of matches having these values, similar to the frequency analysis in ANNIS.
+    ///
+    /// Number columns and text columns are ignored in this mode.
+    Frequencies(FrequencySplit),
+}
+
 pub(super) trait TableWriter {
     fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
     where
-        I: IntoIterator<Item: AsRef<str>>;
+        I: IntoIterator<Item: Into<TableCell>>;
 
     fn flush(&mut self) -> Result<(), AnnimateError>;
 }
 
+/// Cell of a table row to be written by a [`TableWriter`].
+#[derive(Debug)]
+pub(super) enum TableCell {
+    /// Plain text.
+    Text(String),
+
+    /// Text of a value that should be written as the given type if possible.
+    Value(String, ValueType),
+
+    /// The whole text of a match in a single cell ("keyword in context"), split into fragments so
+    /// that the fragments belonging to the match can be highlighted.
+    Kwic(Vec<TextFragment>),
+}
+
+impl TableCell {
+    /// Returns the content of the cell as plain text, without any highlighting.
+    pub(super) fn into_text(self) -> String {
+        match self {
+            TableCell::Text(text) | TableCell::Value(text, _) => text,
+            TableCell::Kwic(fragments) => fragments.into_iter().map(|f| f.text).collect(),
+        }
+    }
+}
+
+impl From<String> for TableCell {
+    fn from(text: String) -> Self {
+        TableCell::Text(text)
+    }
+}
+
+impl From<&str> for TableCell {
+    fn from(text: &str) -> Self {
+        TableCell::Text(text.into())
+    }
+}
+
+/// Fragment of the formatted text of a match.
+#[derive(Debug)]
+pub(super) struct TextFragment {
+    pub(super) kind: TextFragmentKind,
+    pub(super) text: String,
+}
+
+/// Kind of a [`TextFragment`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TextFragmentKind {
+    /// Segments belonging to the match node with the given index, including brackets.
+    Match(usize),
+
+    /// Segments of the context.
+    Context,
+
+    /// Gap marker.
+    Gap,
+
+    /// Separator between two of the other fragments.
+    Separator,
+}
+
+/// Formats the given parts of the text of a match, split into fragments.
+///
+/// Parts without segments are skipped.
+pub(super) fn text_fragments(parts: &[TextPart], formatting: &TextFormatting) -> Vec<TextFragment> {
+    let mut fragments = Vec::new();
+    let mut pending_separator: Option<&str> = None;
+
+    let mut push_fragment = |kind, text: &str| {
+        fragments.push(TextFragment {
+            kind,
+            text: text.into(),
+        });
+    };
+
+    for part in parts {
+        if let TextPart::Gap = part {
+            if pending_separator.is_some() {
+                push_fragment(TextFragmentKind::Separator, &formatting.segment_separator);
+            }
+
+            push_fragment(TextFragmentKind::Gap, &formatting.gap_marker);
+            pending_separator = Some(&formatting.segment_separator);
+            continue;
+        }
+
+        let mut segments = part.segments().peekable();
+        if segments.peek().is_none() {
+            continue;
+        }
+
+        if let Some(separator) = pending_separator.take() {
+            push_fragment(TextFragmentKind::Separator, separator);
+        }
+
+        let (kind, brackets) = match part {
+            TextPart::Match { index, .. } => (
+                TextFragmentKind::Match(*index),
+                formatting.match_brackets.as_ref(),
+            ),
+            _ => (TextFragmentKind::Context, None),
+        };
+
+        let mut text = String::new();
+
+        if let Some((open, _)) = brackets {
+            text.push_str(open);
+        }
+
+        for (segment, whitespace) in segments {
+            if let Some(separator) = pending_separator.take() {
+                text.push_str(separator);
+            }
+
+            text.push_str(segment);
+            pending_separator = Some(whitespace.unwrap_or(&formatting.segment_separator));
+        }
+
+        if let Some((_, close)) = brackets {
+            text.push_str(close);
+        }
+
+        push_fragment(kind, &text);
+    }
+
+    fragments
+}
+
+/// Formats the given parts of the text of a match as plain text.
+fn format_text(parts: &[TextPart], formatting: &TextFormatting) -> String {
+    text_fragments(parts, formatting)
+        .into_iter()
+        .map(|f| f.text)
+        .collect()
+}
+
+pub(super) fn get_export_data(
+    columns: &[TableExportColumn],
+    mode: TableExportMode,
+) -> Vec<ExportData> {
+    match mode {
+        TableExportMode::Matches => columns
+            .iter()
+            .filter_map(TableExportColumn::data)
+            .cloned()
+            .collect(),
+        TableExportMode::Frequencies(split) => frequency::get_export_data(columns, split),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(super) fn export<F, G, I, W>(
     columns: &[TableExportColumn],
+    mode: TableExportMode,
+    kwic: bool,
     matches_iter: I,
     query_nodes: &[Vec<QueryNode>],
     anno_key_formats: AnnoKeyFormats<'_>,
@@ -61,75 +348,228 @@ where
     I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
     W: TableWriter,
 {
-    let AnnoKeyFormats {
-        node: node_anno_key_format,
-        edge: edge_anno_key_format,
-    } = anno_key_formats;
+    match mode {
+        TableExportMode::Matches => export_matches(
+            columns,
+            kwic,
+            matches_iter,
+            query_nodes,
+            anno_key_formats,
+            out,
+            on_matches_exported,
+            cancel_requested,
+        ),
+        TableExportMode::Frequencies(split) => frequency::export(
+            columns,
+            split,
+            matches_iter,
+            query_nodes,
+            anno_key_formats,
+            out,
+            on_matches_exported,
+            cancel_requested,
+        ),
+    }
+}
 
-    let (matches, max_match_parts_by_text) = {
-        let mut max_match_parts_by_text = HashMap::new();
-
-        let matches: Vec<_> = matches_iter
-            .enumerate()
-            .map(|(i, m)| {
-                error::cancel_if(&cancel_requested)?;
-                on_matches_exported(i);
-
-                m.inspect(|m| {
-                    for (text, parts) in &m.texts {
-                        let match_parts = parts.iter().filter(|p| p.is_match()).count();
-                        let max_match_parts =
-                            max_match_parts_by_text.entry(text.clone()).or_insert(0);
-                        *max_match_parts = (*max_match_parts).max(match_parts);
-                    }
-                })
-            })
-            .collect::<Result<_, AnnimateError>>()?;
+#[allow(clippy::too_many_arguments)]
+fn export_matches<F, G, I, W>(
+    columns: &[TableExportColumn],
+    kwic: bool,
+    matches_iter: I,
+    query_nodes: &[Vec<QueryNode>],
+    anno_key_formats: AnnoKeyFormats<'_>,
+    out: &mut W,
+    on_matches_exported: F,
+    cancel_requested: G,
+) -> Result<(), AnnimateError>
+where
+    F: Fn(usize),
+    G: Fn() -> bool,
+    I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+    W: TableWriter,
+{
+    let node_anno_key_format = anno_key_formats.node;
+
+    // The header depends on the maximum number of match parts per text across all matches, so all
+    // matches need to be processed before the first row can be written. In order to keep memory
+    // usage bounded regardless of the number of matches, rows are spooled to a temporary file in a
+    // first pass and read back in a second pass once the header has been written.
+    let mut spool = BufWriter::new(tempfile::tempfile()?);
+    let mut max_match_parts_by_text = HashMap::new();
+    let mut match_count = 0;
 
+    for (i, m) in matches_iter.enumerate() {
         error::cancel_if(&cancel_requested)?;
-        on_matches_exported(matches.len());
+        on_matches_exported(i);
 
-        (matches, max_match_parts_by_text)
-    };
+        let Match { values, texts } = m?;
+
+        for (text, parts) in &texts {
+            let match_parts = parts.iter().filter(|p| p.is_match()).count();
+            let max_match_parts = max_match_parts_by_text.entry(text.clone()).or_insert(0);
+            *max_match_parts = (*max_match_parts).max(match_parts);
+        }
+
+        let row: Vec<_> = columns
+            .iter()
+            .filter_map(TableExportColumn::data)
+            .map(|data| match data {
+                ExportData::Value(value) => SpooledCell::Value(
+                    values
+                        .get(value)
+                        .map(|s| s.trim().to_string())
+                        .unwrap_or_default(),
+                ),
+                ExportData::Text(text) => SpooledCell::Text(texts.get(text).unwrap().clone()),
+            })
+            .collect();
+
+        serde_json::to_writer(&mut spool, &row)?;
+        spool.write_all(b"\n")?;
+
+        match_count = i + 1;
+    }
+
+    error::cancel_if(&cancel_requested)?;
+    on_matches_exported(match_count);
+
+    let mut spool = spool.into_inner().map_err(IntoInnerError::into_error)?;
+    spool.rewind()?;
 
     error::cancel_if(&cancel_requested)?;
 
     out.write_record(columns.iter().flat_map(|c| match c {
         TableExportColumn::Number => vec!["Number".into()],
-        TableExportColumn::Data(ExportData::Value(ExportDataValue::CorpusAnno { anno_key })) => {
-            vec![format!("Corpus {}", node_anno_key_format.display(anno_key))]
+        TableExportColumn::Data(ExportData::Value(value), _) => {
+            vec![value_header(value, query_nodes, anno_key_formats)]
+        }
+        TableExportColumn::Data(ExportData::Text(text), _) => {
+            let max_match_parts = *max_match_parts_by_text.get(text).unwrap_or(&0);
+            let column_types = ColumnTypes::new(max_match_parts, query_nodes.len(), text);
+
+            let text_headers = column_types.into_iter().map(|c| match c {
+                (Match, _) if max_match_parts <= 1 => "Match".into(),
+                (Match, i) => format!("Match {}", i + 1),
+                (Context, 0) if max_match_parts == 0 => "Context".into(),
+                (Context, 0) if max_match_parts == 1 && column_types.has_left_context() => {
+                    "Left context".into()
+                }
+                (Context, _) if max_match_parts == 1 => "Right context".into(),
+                (Context, i) => format!("Context {}", i + 1),
+            });
+
+            text_headers
+                .chain(kwic.then(|| "KWIC".into()))
+                .map(|header| {
+                    format!(
+                        "{header}{} ({})",
+                        match &text.anno_key {
+                            AnnoKeyOrDefault::AnnoKey(anno_key) =>
+                                format!(" {}", node_anno_key_format.display(anno_key)),
+                            AnnoKeyOrDefault::Default => "".into(),
+                        },
+                        text.segmentation.as_deref().unwrap_or("tokens")
+                    )
+                })
+                .collect()
+        }
+    }))?;
+
+    for (i, line) in BufReader::new(spool).lines().enumerate() {
+        error::cancel_if(&cancel_requested)?;
+
+        let mut cells = serde_json::from_str::<Vec<SpooledCell>>(&line?)?.into_iter();
+
+        out.write_record(columns.iter().flat_map(|c| {
+            match c {
+                TableExportColumn::Number => {
+                    vec![TableCell::Value((i + 1).to_string(), ValueType::Integer)]
+                }
+                TableExportColumn::Data(data, value_type) => {
+                    match (
+                        data,
+                        cells
+                            .next()
+                            .expect("spooled row should have a cell per data column"),
+                    ) {
+                        (ExportData::Value(_), SpooledCell::Value(value)) => {
+                            vec![TableCell::Value(value, *value_type)]
+                        }
+                        (ExportData::Text(text), SpooledCell::Text(parts)) => {
+                            let max_match_parts = *max_match_parts_by_text.get(text).unwrap_or(&0);
+                            let column_types =
+                                ColumnTypes::new(max_match_parts, query_nodes.len(), text);
+                            let text_columns =
+                                TextColumns::new(parts.clone(), &text.formatting).collect();
+
+                            TextColumnsAligned::new(text_columns, column_types)
+                                .map(TableCell::Text)
+                                .chain(kwic.then(|| {
+                                    TableCell::Kwic(text_fragments(&parts, &text.formatting))
+                                }))
+                                .collect()
+                        }
+                        _ => unreachable!("spooled cell should match data column"),
+                    }
+                }
+            }
+        }))?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+pub(super) fn value_header(
+    value: &ExportDataValue,
+    query_nodes: &[Vec<QueryNode>],
+    anno_key_formats: AnnoKeyFormats<'_>,
+) -> String {
+    let AnnoKeyFormats {
+        node: node_anno_key_format,
+        edge: edge_anno_key_format,
+    } = anno_key_formats;
+
+    match value {
+        ExportDataValue::CorpusAnno { anno_key } => {
+            format!("Corpus {}", node_anno_key_format.display(anno_key))
         }
-        TableExportColumn::Data(ExportData::Value(ExportDataValue::DocumentAnno { anno_key })) => {
+        ExportDataValue::DocumentAnno { anno_key } => {
             if anno::is_doc_anno_key(anno_key) {
-                vec!["Document".into()]
+                "Document".into()
             } else {
-                vec![format!(
-                    "Document {}",
-                    node_anno_key_format.display(anno_key)
-                )]
+                format!("Document {}", node_anno_key_format.display(anno_key))
             }
         }
-        TableExportColumn::Data(ExportData::Value(ExportDataValue::MatchNodeAnno {
+        ExportDataValue::MatchNodeAnno {
             anno_key,
             index,
-        })) => {
-            vec![format!(
-                "{} {}",
+            aggregation,
+        } => {
+            format!(
+                "{} {}{}",
                 format_query_nodes(
                     query_nodes
                         .get(*index)
                         .expect("query node index should be valid"),
                 ),
                 node_anno_key_format.display(anno_key),
-            )]
+                match aggregation {
+                    AnnoAggregation::First => "",
+                    AnnoAggregation::Distinct { .. } => " (distinct)",
+                    AnnoAggregation::All { .. } => " (all)",
+                },
+            )
         }
-        TableExportColumn::Data(ExportData::Value(ExportDataValue::EdgeAnno {
+        ExportDataValue::EdgeAnno {
             edge_type,
             anno_key,
             source_node_index,
             target_node_index,
-        })) => {
-            vec![format!(
+        } => {
+            format!(
                 "{} {} {} {}",
                 format_query_nodes(
                     query_nodes
@@ -145,13 +585,13 @@ where
                 edge_anno_key_format(edge_type)
                     .expect("edge type should be valid")
                     .display(anno_key),
-            )]
+            )
         }
-        TableExportColumn::Data(ExportData::Value(ExportDataValue::QueryNodeProperty {
+        ExportDataValue::QueryNodeProperty {
             query_node_property_key,
             match_node_index,
-        })) => {
-            vec![format!(
+        } => {
+            format!(
                 "{} {}",
                 format_query_nodes(
                     query_nodes
@@ -162,66 +602,90 @@ where
                     QueryNodePropertyKey::Fragment => "query fragment",
                     QueryNodePropertyKey::Variable => "query variable",
                 },
-            )]
+            )
         }
-        TableExportColumn::Data(ExportData::Text(text)) => {
-            let max_match_parts = *max_match_parts_by_text.get(text).unwrap_or(&0);
-            let column_types = ColumnTypes::new(max_match_parts, query_nodes.len(), text);
-
-            column_types
-                .into_iter()
-                .map(|c| {
-                    format!(
-                        "{}{} ({})",
-                        match c {
-                            (Match, _) if max_match_parts <= 1 => "Match".into(),
-                            (Match, i) => format!("Match {}", i + 1),
-                            (Context, 0) if max_match_parts == 0 => "Context".into(),
-                            (Context, 0)
-                                if max_match_parts == 1 && column_types.has_left_context() =>
-                            {
-                                "Left context".into()
-                            }
-                            (Context, _) if max_match_parts == 1 => "Right context".into(),
-                            (Context, i) => format!("Context {}", i + 1),
-                        },
-                        match &text.anno_key {
-                            AnnoKeyOrDefault::AnnoKey(anno_key) =>
-                                format!(" {}", node_anno_key_format.display(anno_key)),
-                            AnnoKeyOrDefault::Default => "".into(),
-                        },
-                        text.segmentation.as_deref().unwrap_or("tokens")
-                    )
-                })
-                .collect()
+        ExportDataValue::CoveredText {
+            index,
+            segmentation,
+            anno_key,
+            ..
+        } => {
+            format!(
+                "{} text{} ({})",
+                format_query_nodes(
+                    query_nodes
+                        .get(*index)
+                        .expect("query node index should be valid"),
+                ),
+                match anno_key {
+                    AnnoKeyOrDefault::AnnoKey(anno_key) =>
+                        format!(" {}", node_anno_key_format.display(anno_key)),
+                    AnnoKeyOrDefault::Default => "".into(),
+                },
+                segmentation.as_deref().unwrap_or("tokens"),
+            )
         }
-    }))?;
+        ExportDataValue::MatchNodePosition { index, position } => {
+            format!(
+                "{} {}",
+                format_query_nodes(
+                    query_nodes
+                        .get(*index)
+                        .expect("query node index should be valid"),
+                ),
+                match position {
+                    NodePosition::TokenIndex => "token index",
+                    NodePosition::FirstTokenIndex => "first token index",
+                    NodePosition::LastTokenIndex => "last token index",
+                    NodePosition::NodeName => "node name",
+                },
+            )
+        }
+        ExportDataValue::TraversalAnno {
+            edge_type,
+            traversal,
+            item,
+            anno_key,
+        } => {
+            let format_node = |index: &usize| {
+                format_query_nodes(
+                    query_nodes
+                        .get(*index)
+                        .expect("query node index should be valid"),
+                )
+                .to_string()
+            };
 
-    for (i, Match { values, texts }) in matches.into_iter().enumerate() {
-        error::cancel_if(&cancel_requested)?;
+            let (node, traversal) = match traversal {
+                Traversal::Parent { index } => (format_node(index), "parent".into()),
+                Traversal::Ancestor { index, distance } => {
+                    (format_node(index), format!("ancestor {distance}"))
+                }
+                Traversal::Children { index } => (format_node(index), "children".into()),
+                Traversal::Path {
+                    source_node_index,
+                    target_node_index,
+                } => (
+                    format_node(source_node_index),
+                    format!("path to {}", format_node(target_node_index)),
+                ),
+            };
 
-        out.write_record(columns.iter().flat_map(|c| match c {
-            TableExportColumn::Number => vec![(i + 1).to_string()],
-            TableExportColumn::Data(ExportData::Value(value)) => {
-                vec![
-                    values
-                        .get(value)
-                        .map(|s| s.trim().to_string())
-                        .unwrap_or_default(),
-                ]
-            }
-            TableExportColumn::Data(ExportData::Text(text)) => {
-                let max_match_parts = *max_match_parts_by_text.get(text).unwrap_or(&0);
-                let parts = texts.get(text).unwrap().clone();
-                let column_types = ColumnTypes::new(max_match_parts, query_nodes.len(), text);
-                TextColumnsAligned::new(parts, column_types).collect()
-            }
-        }))?;
+            format!(
+                "{node} {traversal} ({}) {}",
+                edge_type.operator(),
+                match item {
+                    TraversalItem::Nodes => node_anno_key_format.display(anno_key).to_string(),
+                    TraversalItem::Edges => format!(
+                        "edge {}",
+                        edge_anno_key_format(edge_type)
+                            .expect("edge type should be valid")
+                            .display(anno_key),
+                    ),
+                },
+            )
+        }
     }
-
-    out.flush()?;
-
-    Ok(())
 }
 
 fn format_query_nodes(query_nodes: &[QueryNode]) -> impl Display {
@@ -233,16 +697,26 @@ fn format_query_nodes(query_nodes: &[QueryNode]) -> impl Display {
         .format_with("|", |elt, f| f(&format_args!("#{elt}")))
 }
 
+/// Cell of a row as spooled to disk between the two passes of the export.
+///
+/// Texts are stored as parts before being split into aligned columns, since the alignment depends
+/// on all matches.
+#[derive(Debug, Deserialize, Serialize)]
+enum SpooledCell {
+    Value(String),
+    Text(Vec<TextPart>),
+}
+
 #[derive(Debug)]
 struct TextColumnsAligned {
-    text_columns: PutBack<TextColumns>,
+    text_columns: PutBack<vec::IntoIter<(ColumnType, String)>>,
     column_types: ColumnTypesIter,
 }
 
 impl TextColumnsAligned {
-    fn new(parts: Vec<TextPart>, column_types: ColumnTypes) -> Self {
+    fn new(text_columns: Vec<(ColumnType, String)>, column_types: ColumnTypes) -> Self {
         Self {
-            text_columns: itertools::put_back(TextColumns::new(parts)),
+            text_columns: itertools::put_back(text_columns),
             column_types: column_types.into_iter(),
         }
     }
@@ -269,21 +743,23 @@ impl Iterator for TextColumnsAligned {
 }
 
 #[derive(Debug)]
-struct TextColumns {
+struct TextColumns<'a> {
     parts: vec::IntoIter<TextPart>,
+    formatting: &'a TextFormatting,
     pending_column: Option<(ColumnType, String)>,
 }
 
-impl TextColumns {
-    fn new(parts: Vec<TextPart>) -> Self {
+impl<'a> TextColumns<'a> {
+    fn new(parts: Vec<TextPart>, formatting: &'a TextFormatting) -> Self {
         Self {
             parts: parts.into_iter(),
+            formatting,
             pending_column: None,
         }
     }
 }
 
-impl Iterator for TextColumns {
+impl Iterator for TextColumns<'_> {
     type Item = (ColumnType, String);
 
     fn next(&mut self) -> Option<(ColumnType, String)> {
@@ -291,26 +767,20 @@ impl Iterator for TextColumns {
             return Some(column);
         }
 
-        let mut context_column: Option<String> = None;
+        let mut context_parts = Vec::new();
 
         let match_column = loop {
-            let serialized_part = match self.parts.next() {
-                Some(TextPart::Match { segments, .. }) => break Some(segments.join(" ")),
-                Some(TextPart::Context { segments }) => segments.join(" "),
-                Some(TextPart::Gap) => "(...)".into(),
-                None => break None,
-            };
-
-            context_column = Some(match context_column.take() {
-                None => serialized_part,
-                Some(mut c) => {
-                    c.push(' ');
-                    c.push_str(&serialized_part);
-                    c
+            match self.parts.next() {
+                Some(part @ TextPart::Match { .. }) => {
+                    break Some(format_text(&[part], self.formatting));
                 }
-            });
+                Some(part) => context_parts.push(part),
+                None => break None,
+            }
         };
 
+        let context_column =
+            (!context_parts.is_empty()).then(|| format_text(&context_parts, self.formatting));
         let context_column = context_column.map(|c| (Context, c));
         let match_column = match_column.map(|c| (Match, c));
 
@@ -340,11 +810,12 @@ impl ColumnTypes {
         };
 
         // Reserve a column for left/right context if the left/right context size is non-zero *or*
-        // there are secondary nodes. In the latter case there may be context even if the context
-        // size is zero because context refers to all nodes but only primary nodes count as
-        // a match.
-        let has_left_context = data.left_context > 0 || has_secondary_nodes;
-        let has_right_context = data.right_context > 0 || has_secondary_nodes;
+        // there is a context unit *or* there are secondary nodes. In the latter cases there may be
+        // context even if the context size is zero because the context unit may extend beyond the
+        // match, and because context refers to all nodes but only primary nodes count as a match.
+        let has_context_unit = data.context_unit.is_some();
+        let has_left_context = data.left_context > 0 || has_context_unit || has_secondary_nodes;
+        let has_right_context = data.right_context > 0 || has_context_unit || has_secondary_nodes;
 
         let column_count = match (match_count, has_left_context, has_right_context) {
             (0, false, false) => 0,
@@ -417,17 +888,19 @@ mod tests {
 
     use graphannis_core::graph::ANNIS_NS;
     use graphannis_core::types::AnnoKey;
+    use itertools::Itertools;
 
     // Cannot use `super::*` due to a bug in rust-analyzer
     use super::{
         AnnimateError, AnnoKeyFormats, AnnoKeyOrDefault, ExportData, ExportDataText,
-        ExportDataValue, Match, TableExportColumn, TableWriter, TextPart, export,
+        ExportDataValue, Match, TableCell, TableExportColumn, TableWriter, TextFormatting,
+        TextFragmentKind, TextPart, TypedValue, ValueType, export_matches,
     };
     use crate::anno::AnnoKeyFormat;
 
     macro_rules! export_test {
         ($(
-            $name:ident: context = ($left_context:expr, $right_context:expr), matches = [
+            $name:ident: context = ($left_context:expr, $right_context:expr), $(kwic = $kwic:expr,)? $(formatting = $formatting:expr,)? matches = [
                 $({doc_name = $doc_name:expr, parts = [$($part:tt)*]})*
             ] => $expected:expr
         )*) => { $(
@@ -440,7 +913,9 @@ mod tests {
                     anno_key: AnnoKeyOrDefault::Default,
                     left_context: $left_context,
                     right_context: $right_context,
+                    context_unit: None,
                     primary_node_indices: None,
+                    formatting: export_test!(@formatting $($formatting)?),
                 };
 
                 let export_data_value_doc_anno = ExportDataValue::DocumentAnno {
@@ -459,12 +934,16 @@ mod tests {
 
                 let anno_key_format = AnnoKeyFormat::new(&HashSet::new());
 
-                export(
+                export_matches(
                     &[
                         TableExportColumn::Number,
-                        TableExportColumn::Data(ExportData::Value(export_data_value_doc_anno.clone())),
-                        TableExportColumn::Data(ExportData::Text(text.clone())),
+                        TableExportColumn::Data(
+                            ExportData::Value(export_data_value_doc_anno.clone()),
+                            ValueType::Auto,
+                        ),
+                        TableExportColumn::Data(ExportData::Text(text.clone()), ValueType::Auto),
                     ],
+                    false $(|| $kwic)?,
                     matches.into_iter().map(Ok),
                     &[vec![]],
                     AnnoKeyFormats {
@@ -480,9 +959,15 @@ mod tests {
             }
         )* };
 
-        (@expand_part (C $($t:expr)*)) => { TextPart::Context { segments: vec![$($t.into()),*] } };
-        (@expand_part (M $($t:expr)*)) => { TextPart::Match { index: 0, segments: vec![$($t.into()),*] } };
+        (@expand_part (C $($t:expr)*)) => { TextPart::Context { segments: vec![$($t.into()),*], whitespace: None } };
+        (@expand_part (M $($t:expr)*)) => { TextPart::Match { index: 0, segments: vec![$($t.into()),*], whitespace: None } };
+        (@expand_part (M $index:literal: $($t:expr)*)) => { TextPart::Match { index: $index, segments: vec![$($t.into()),*], whitespace: None } };
+        (@expand_part (CW $($t:expr, $w:expr);*)) => { TextPart::Context { segments: vec![$($t.into()),*], whitespace: Some(vec![$($w.into()),*]) } };
+        (@expand_part (MW $($t:expr, $w:expr);*)) => { TextPart::Match { index: 0, segments: vec![$($t.into()),*], whitespace: Some(vec![$($w.into()),*]) } };
         (@expand_part (G)) => { TextPart::Gap };
+
+        (@formatting) => { TextFormatting::default() };
+        (@formatting $formatting:expr) => { $formatting };
     }
 
     export_test! {
@@ -588,6 +1073,13 @@ mod tests {
             ["2"     , "doc2"    , "111 (...) 222"        , "def"           , "333 (...) 444"         ],
         ]
 
+        values_with_special_characters: context=(0, 0), matches = [
+            {doc_name = "doc \"1\"\nline 2", parts = [(M "a\tb")]}
+        ] => [
+            ["Number", "Document"          , "Match (tokens)"],
+            ["1"     , "doc \"1\"\nline 2", "a\tb"          ],
+        ]
+
         multiple_matches_different_number_of_match_nodes: context=(1, 1), matches = [
             {doc_name = "doc1", parts = [(M "abc")]}
             {doc_name = "doc1", parts = [(C "111") (G) (C "222") (M "def") (C "333") (G) (C "444")]}
@@ -600,6 +1092,46 @@ mod tests {
             ["3"     ,"doc2"     , ""                  , "ghi"             , ""                  , "jkl"             , ""                  ],
             ["4"     ,"doc2"     ,"555 (...) 666"      , "mno"             , "777 (...) 888"     , "pqr"             , "999 (...) 000"     ],
         ]
+
+        kwic_no_match: context=(1, 1), kwic = true, matches = [] => [
+            ["Number", "Document", "Context (tokens)", "KWIC (tokens)"],
+        ]
+
+        kwic_one_match_node: context=(1, 1), kwic = true, matches = [
+            {doc_name = "doc1", parts = [(C "111") (G) (C "222") (M "abc") (C "333") (G) (C "444")]}
+        ] => [
+            ["Number", "Document", "Left context (tokens)", "Match (tokens)", "Right context (tokens)", "KWIC (tokens)"                         ],
+            ["1"     , "doc1"    , "111 (...) 222"        , "abc"           , "333 (...) 444"         , "111 (...) 222 [0:abc] 333 (...) 444"],
+        ]
+
+        kwic_multiple_match_nodes: context=(0, 0), kwic = true, matches = [
+            {doc_name = "doc1", parts = [(M 0: "abc" "def") (C "111") (M 1: "ghi")]}
+        ] => [
+            ["Number", "Document", "Match 1 (tokens)", "Match 2 (tokens)", "KWIC (tokens)"              ],
+            ["1"     , "doc1"    , "abc def"         , "ghi"             , "[0:abc def] 111 [1:ghi]"],
+        ]
+
+        custom_formatting: context=(1, 1), kwic = true, formatting = TextFormatting {
+            segment_separator: "".into(),
+            gap_marker: "…".into(),
+            match_brackets: Some(("[[".into(), "]]".into())),
+            restore_whitespace: false,
+        }, matches = [
+            {doc_name = "doc1", parts = [(C "我" "们") (G) (C "在") (M "学" "习") (C "中" "文")]}
+        ] => [
+            ["Number", "Document", "Left context (tokens)", "Match (tokens)", "Right context (tokens)", "KWIC (tokens)"          ],
+            ["1"     , "doc1"    , "我们…在"              , "[[学习]]"      , "中文"                  , "我们…在[0:[[学习]]]中文"],
+        ]
+
+        restored_whitespace: context=(1, 1), kwic = true, formatting = TextFormatting {
+            restore_whitespace: true,
+            ..TextFormatting::default()
+        }, matches = [
+            {doc_name = "doc1", parts = [(CW "a", " ") (G) (CW "Haus", "") (MW "-", "" ; "Tür", "") (CW ".", "  "; "Es", "\n")]}
+        ] => [
+            ["Number", "Document", "Left context (tokens)", "Match (tokens)", "Right context (tokens)", "KWIC (tokens)"               ],
+            ["1"     , "doc1"    , "a (...) Haus"         , "-Tür"          , ".  Es"                 , "a (...) Haus[0:-Tür].  Es"],
+        ]
     }
 
     #[derive(Default)]
@@ -614,10 +1146,25 @@ mod tests {
     impl TableWriter for TestTableWriter {
         fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
         where
-            I: IntoIterator<Item: AsRef<str>>,
+            I: IntoIterator<Item: Into<TableCell>>,
         {
-            self.0
-                .push(record.into_iter().map(|s| s.as_ref().into()).collect());
+            self.0.push(
+                record
+                    .into_iter()
+                    .map(|cell| match cell.into() {
+                        TableCell::Text(text) | TableCell::Value(text, _) => text,
+                        TableCell::Kwic(fragments) => fragments
+                            .into_iter()
+                            .map(|fragment| match fragment.kind {
+                                TextFragmentKind::Match(index) => {
+                                    format!("[{index}:{}]", fragment.text)
+                                }
+                                _ => fragment.text,
+                            })
+                            .join(""),
+                    })
+                    .collect(),
+            );
 
             Ok(())
         }
@@ -626,4 +1173,63 @@ mod tests {
             Ok(())
         }
     }
+
+    #[test]
+    fn value_type_auto_detects_numbers_and_dates() {
+        let number = |n| Some(TypedValue::Number(n));
+
+        assert_eq!(ValueType::Auto.parse("42"), number(42.0));
+        assert_eq!(ValueType::Auto.parse("-0.5"), number(-0.5));
+        assert_eq!(ValueType::Auto.parse("0"), number(0.0));
+        assert_eq!(ValueType::Auto.parse("0.05"), number(0.05));
+        assert_eq!(ValueType::Auto.parse("11299"), number(11299.0));
+        assert_eq!(
+            ValueType::Auto.parse("2024-02-29"),
+            Some(TypedValue::Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            })
+        );
+
+        for text in [
+            "",
+            "-",
+            "007",
+            "1.",
+            ".5",
+            "1.10",
+            "0.50",
+            "2.0",
+            "-0",
+            "-0.0",
+            "+1",
+            "1e3",
+            "1,5",
+            "NaN",
+            "inf",
+            "2024-13-01",
+            "2024-1-1",
+            "1234567890123456",
+            "abc",
+        ] {
+            assert_eq!(ValueType::Auto.parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn value_type_explicit() {
+        assert_eq!(ValueType::Text.parse("42"), None);
+        assert_eq!(
+            ValueType::Integer.parse("007"),
+            Some(TypedValue::Number(7.0))
+        );
+        assert_eq!(ValueType::Integer.parse("1.5"), None);
+        assert_eq!(
+            ValueType::Float.parse("1e3"),
+            Some(TypedValue::Number(1000.0))
+        );
+        assert_eq!(ValueType::Float.parse("inf"), None);
+        assert_eq!(ValueType::Date.parse("42"), None);
+    }
 }
@@ -1,11 +1,16 @@
 use std::io::Write;
 
-use graphannis::corpusstorage::QueryLanguage;
 use itertools::Itertools;
-use rust_xlsxwriter::{DocProperties, Table, Workbook, Worksheet};
+use rust_xlsxwriter::{
+    DocProperties, ExcelDateTime, Format, Table, TableColumn, Workbook, Worksheet, XlsxError,
+};
+use serde::Deserialize;
 
-use super::table::{self, TableExportColumn, TableWriter};
-use super::{AnnoKeyFormats, Exporter, QueryInfo};
+use super::table::{
+    self, TableCell, TableExportColumn, TableExportMode, TableWriter, TextFragment,
+    TextFragmentKind, TypedValue, ValueType,
+};
+use super::{AnnoKeyFormats, Exporter, GAP_COLOR, MATCH_COLORS, QueryInfo};
 use crate::error::AnnimateError;
 use crate::query::{ExportData, Match};
 use crate::version::VERSION_INFO;
@@ -18,18 +23,33 @@ pub(super) struct XlsxExporter;
 pub struct XlsxExportConfig {
     /// Columns to export.
     pub columns: Vec<TableExportColumn>,
+
+    /// What to export in the rows of the table.
+    pub mode: TableExportMode,
+
+    /// Options of the XLSX format.
+    pub options: XlsxOptions,
+}
+
+/// Options of the XLSX (Excel) format.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct XlsxOptions {
+    /// Whether to add a "Counts" worksheet with the number of matches in each corpus.
+    ///
+    /// Counting requires searching the corpora once more, so this makes the export take longer.
+    pub counts_sheet: bool,
+
+    /// Whether to add a "KWIC" (keyword in context) column for each "match in context" column,
+    /// containing the whole text of a match in a single cell with the match highlighted.
+    pub kwic_cells: bool,
 }
 
 impl Exporter for XlsxExporter {
     type Config = XlsxExportConfig;
 
     fn get_export_data(config: &XlsxExportConfig) -> Vec<ExportData> {
-        config
-            .columns
-            .iter()
-            .filter_map(TableExportColumn::data)
-            .cloned()
-            .collect()
+        table::get_export_data(&config.columns, config.mode)
     }
 
     fn export<F, G, I, S, W>(
@@ -47,6 +67,102 @@ impl Exporter for XlsxExporter {
         I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
         S: AsRef<str>,
         W: Write + Send,
+    {
+        let sheets = XlsxExporter::export_sheets(
+            config,
+            "Data",
+            matches_iter,
+            &query_info,
+            anno_key_formats,
+            on_matches_exported,
+            cancel_requested,
+        )?;
+
+        XlsxExporter::save_workbook(vec![sheets], out)
+    }
+}
+
+/// Worksheets containing the export of a single query, which can be saved into a workbook
+/// together with those of other queries.
+pub(crate) struct XlsxSheets {
+    name: String,
+    data_worksheets: Vec<Worksheet>,
+    info_rows: Vec<(&'static str, String)>,
+    counts: Option<Vec<(String, u64, u64)>>,
+}
+
+impl XlsxExporter {
+    /// Exports matches into worksheets named after `name`, without saving them.
+    pub(super) fn export_sheets<F, G, I, S>(
+        config: &XlsxExportConfig,
+        name: &str,
+        matches_iter: I,
+        query_info: &QueryInfo<'_, S>,
+        anno_key_formats: AnnoKeyFormats<'_>,
+        on_matches_exported: F,
+        cancel_requested: G,
+    ) -> Result<XlsxSheets, AnnimateError>
+    where
+        F: Fn(usize),
+        G: Fn() -> bool,
+        I: ExactSizeIterator<Item = Result<Match, AnnimateError>>,
+        S: AsRef<str>,
+    {
+        let (data_worksheets, truncated_cells) = {
+            let mut xlsx_table_writer = XlsxTableWriter::new(name)?;
+
+            table::export(
+                &config.columns,
+                config.mode,
+                config.options.kwic_cells,
+                matches_iter,
+                query_info.nodes,
+                anno_key_formats,
+                &mut xlsx_table_writer,
+                on_matches_exported,
+                cancel_requested,
+            )?;
+
+            xlsx_table_writer.into_worksheets()?
+        };
+
+        let mut info_rows = query_info.info_rows();
+
+        if truncated_cells > 0 {
+            info_rows.push((
+                "Truncated cells",
+                format!(
+                    "{truncated_cells} cells longer than {MAX_CELL_CHARS} characters were cut off and marked with \"{TRUNCATION_MARKER}\"",
+                ),
+            ));
+        }
+
+        Ok(XlsxSheets {
+            name: name.into(),
+            data_worksheets,
+            info_rows,
+            counts: query_info.counts.map(|counts| {
+                counts
+                    .iter()
+                    .map(|count| {
+                        (
+                            count.corpus_name.clone(),
+                            count.match_count,
+                            count.document_count,
+                        )
+                    })
+                    .collect()
+            }),
+        })
+    }
+
+    /// Saves the worksheets of one or more queries into a single workbook.
+    ///
+    /// With more than one query, the "Information" and "Counts" worksheets contain the information
+    /// about all queries, referring to them by the names of their data worksheets.
+    pub(super) fn save_workbook<W>(sheets: Vec<XlsxSheets>, out: W) -> Result<(), AnnimateError>
+    where
+        W: Write + Send,
     {
         let mut workbook = Workbook::new();
 
@@ -56,61 +172,152 @@ impl Exporter for XlsxExporter {
         )));
 
         let info_worksheet = {
+            let rows = match sheets.as_slice() {
+                [sheets] => sheets
+                    .info_rows
+                    .iter()
+                    .map(|(name, value)| vec![*name, value.as_str()])
+                    .collect(),
+                _ => batch_info_rows(&sheets),
+            };
+
             let mut worksheet = Worksheet::new();
-            worksheet
-                .set_name("Information")?
-                .write_string(0, 0, "Query")?
-                .write_string(0, 1, query_info.aql_query)?
-                .write_string(1, 0, "Query Language")?
-                .write_string(
-                    1,
-                    1,
-                    match query_info.query_language {
-                        QueryLanguage::AQL => "AQL (latest)",
-                        QueryLanguage::AQLQuirksV3 => "AQL (compatibility mode)",
-                    },
-                )?
-                .write_string(2, 0, "Corpora")?
-                .write_string(
-                    2,
-                    1,
-                    query_info.corpus_names.iter().map(|s| s.as_ref()).join(","),
-                )?
-                .write_string(3, 0, "Annimate version")?
-                .write_string(3, 1, VERSION_INFO.annimate_version)?
-                .autofit()
-                .add_table(0, 0, 3, 1, &Table::new().set_header_row(false))?;
+            worksheet.set_name("Information")?;
+
+            for (row, cells) in (0..).zip(&rows) {
+                for (col, cell) in (0..).zip(cells) {
+                    worksheet.write_string(row, col, *cell)?;
+                }
+            }
+
+            worksheet.autofit().add_table(
+                0,
+                0,
+                (rows.len() - 1) as u32,
+                (rows[0].len() - 1) as u16,
+                &Table::new().set_header_row(false),
+            )?;
             worksheet
         };
 
-        let data_worksheet = {
-            let mut xlsx_table_writer = XlsxTableWriter::new("Data")?;
+        let counts_worksheet = if sheets.iter().any(|s| s.counts.is_some()) {
+            // The counts of multiple queries are told apart by the names of their data worksheets
+            let with_names = sheets.len() > 1;
+            let first_col = u16::from(with_names);
 
-            table::export(
-                &config.columns,
-                matches_iter,
-                query_info.nodes,
-                anno_key_formats,
-                &mut xlsx_table_writer,
-                on_matches_exported,
-                cancel_requested,
+            let mut worksheet = Worksheet::new();
+            worksheet.set_name("Counts")?;
+
+            let mut row = 0;
+            for s in &sheets {
+                for (corpus_name, match_count, document_count) in s.counts.iter().flatten() {
+                    row += 1;
+
+                    if with_names {
+                        worksheet.write_string(row, 0, &s.name)?;
+                    }
+
+                    worksheet
+                        .write_string(row, first_col, corpus_name)?
+                        .write_number(row, first_col + 1, *match_count as f64)?
+                        .write_number(row, first_col + 2, *document_count as f64)?;
+                }
+            }
+
+            let mut columns = vec![
+                TableColumn::new().set_header("Corpus"),
+                TableColumn::new().set_header("Matches"),
+                TableColumn::new().set_header("Documents with matches"),
+            ];
+            if with_names {
+                columns.insert(0, TableColumn::new().set_header("Worksheet"));
+            }
+
+            worksheet.autofit().add_table(
+                0,
+                0,
+                row,
+                first_col + 2,
+                &Table::new().set_columns(&columns),
             )?;
 
-            xlsx_table_writer.into_worksheet()?
+            Some(worksheet)
+        } else {
+            None
         };
 
-        workbook.push_worksheet(data_worksheet);
+        for data_worksheet in sheets.into_iter().flat_map(|s| s.data_worksheets) {
+            workbook.push_worksheet(data_worksheet);
+        }
         workbook.push_worksheet(info_worksheet);
+        if let Some(counts_worksheet) = counts_worksheet {
+            workbook.push_worksheet(counts_worksheet);
+        }
         workbook.save_to_writer(out)?;
 
         Ok(())
     }
 }
 
+/// Returns the rows of the "Information" worksheet for multiple queries, with a column for each
+/// query.
+///
+/// The first row contains the names of the data worksheets of the queries. Information that is
+/// missing for a query, such as the number of truncated cells, is left empty.
+fn batch_info_rows(sheets: &[XlsxSheets]) -> Vec<Vec<&str>> {
+    let names = sheets
+        .iter()
+        .flat_map(|s| s.info_rows.iter().map(|(name, _)| *name))
+        .unique();
+
+    let mut rows = vec![
+        std::iter::once("Worksheet")
+            .chain(sheets.iter().map(|s| s.name.as_str()))
+            .collect(),
+    ];
+
+    for name in names {
+        rows.push(
+            std::iter::once(name)
+                .chain(sheets.iter().map(|s| {
+                    s.info_rows
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .map_or("", |(_, value)| value.as_str())
+                }))
+                .collect(),
+        );
+    }
+
+    rows
+}
+
+/// Maximum number of rows of an Excel worksheet.
+const MAX_ROWS: u32 = 1_048_576;
+
+/// Maximum number of characters of an Excel cell.
+const MAX_CELL_CHARS: usize = 32_767;
+
+/// Text appended to cells that were truncated to [`MAX_CELL_CHARS`] characters.
+const TRUNCATION_MARKER: &str = "[truncated]";
+
+/// Writes a table into one or more worksheets.
+///
+/// The first record is treated as a header row. Whenever a worksheet is full, writing continues
+/// in a new worksheet starting with the same header row.
 struct XlsxTableWriter {
+    name: String,
+    full_worksheets: Vec<Worksheet>,
     worksheet: Worksheet,
+    header: Option<Vec<String>>,
     rows: u32,
     cols: u16,
+    max_rows: u32,
+    truncated_cells: usize,
+    context_format: Format,
+    match_formats: Vec<Format>,
+    gap_format: Format,
+    date_format: Format,
 }
 
 impl XlsxTableWriter {
@@ -118,44 +325,43 @@ impl XlsxTableWriter {
     where
         S: Into<String>,
     {
+        let name = name.into();
         let mut worksheet = Worksheet::new();
-        worksheet.set_name(name)?;
+        worksheet.set_name(&name)?;
         Ok(XlsxTableWriter {
+            name,
+            full_worksheets: Vec::new(),
             worksheet,
+            header: None,
             rows: 0,
             cols: 0,
+            max_rows: MAX_ROWS,
+            truncated_cells: 0,
+            context_format: Format::new(),
+            match_formats: MATCH_COLORS
+                .into_iter()
+                .map(|color| Format::new().set_bold().set_font_color(color))
+                .collect(),
+            gap_format: Format::new().set_italic().set_font_color(GAP_COLOR),
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
         })
     }
 
-    fn into_worksheet(mut self) -> Result<Worksheet, AnnimateError> {
-        self.worksheet.autofit();
-
-        if self.rows > 0 && self.cols > 0 {
-            self.worksheet.add_table(
-                0,
-                0,
-                self.rows - 1,
-                self.cols - 1,
-                // Treat the first row as a header row iff there are more rows than just the header
-                // because Excel disallows tables consisting only of a header row
-                &Table::new().set_header_row(self.rows > 1),
-            )?;
-        }
-
-        Ok(self.worksheet)
-    }
-}
-
-impl TableWriter for XlsxTableWriter {
-    fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
-    where
-        I: IntoIterator<Item: AsRef<str>>,
-    {
+    fn write_row(&mut self, cells: Vec<TableCell>) -> Result<(), AnnimateError> {
         let row = self.rows;
         let mut col = 0;
 
-        for data in record {
-            self.worksheet.write_string(row, col, data.as_ref())?;
+        for cell in cells {
+            match cell {
+                TableCell::Text(text) => {
+                    let text = self.truncate(&text);
+                    self.worksheet.write_string(row, col, text)?;
+                }
+                TableCell::Value(text, value_type) => {
+                    self.write_value(row, col, &text, value_type)?;
+                }
+                TableCell::Kwic(fragments) => self.write_kwic(row, col, &fragments)?,
+            }
             col += 1;
 
             if col > self.cols {
@@ -168,9 +374,256 @@ impl TableWriter for XlsxTableWriter {
         Ok(())
     }
 
+    fn write_value(
+        &mut self,
+        row: u32,
+        col: u16,
+        text: &str,
+        value_type: ValueType,
+    ) -> Result<(), AnnimateError> {
+        match value_type.parse(text) {
+            Some(TypedValue::Number(number)) => {
+                self.worksheet.write_number(row, col, number)?;
+                return Ok(());
+            }
+            Some(TypedValue::Date { year, month, day }) => {
+                // Excel doesn't support all dates, e.g. those before 1900
+                if let Ok(date) = ExcelDateTime::from_ymd(year, month, day) {
+                    self.worksheet
+                        .write_datetime_with_format(row, col, date, &self.date_format)?;
+                    return Ok(());
+                }
+            }
+            None => {}
+        }
+
+        let text = self.truncate(text);
+        self.worksheet.write_string(row, col, text)?;
+
+        Ok(())
+    }
+
+    fn write_kwic(
+        &mut self,
+        row: u32,
+        col: u16,
+        fragments: &[TextFragment],
+    ) -> Result<(), AnnimateError> {
+        let len: usize = fragments.iter().map(|f| f.text.chars().count()).sum();
+        let truncated = len > MAX_CELL_CHARS;
+        let mut remaining = if truncated {
+            MAX_CELL_CHARS - TRUNCATION_MARKER.chars().count()
+        } else {
+            len
+        };
+
+        let mut fragments: Vec<_> = fragments
+            .iter()
+            // Excel doesn't allow empty fragments in rich strings
+            .filter(|fragment| !fragment.text.is_empty())
+            .map_while(|fragment| {
+                let text = prefix(&fragment.text, remaining);
+                if text.is_empty() {
+                    return None;
+                }
+                remaining -= text.chars().count();
+
+                let format = match fragment.kind {
+                    TextFragmentKind::Match(index) => {
+                        &self.match_formats[index % self.match_formats.len()]
+                    }
+                    TextFragmentKind::Context | TextFragmentKind::Separator => &self.context_format,
+                    TextFragmentKind::Gap => &self.gap_format,
+                };
+
+                Some((format, text))
+            })
+            .collect();
+
+        if truncated {
+            fragments.push((&self.context_format, TRUNCATION_MARKER));
+            self.truncated_cells += 1;
+        }
+
+        if fragments.is_empty() {
+            self.worksheet.write_string(row, col, "")?;
+        } else {
+            match self.worksheet.write_rich_string(row, col, &fragments) {
+                Ok(_) => {}
+                // The length check of rich strings includes their formatting markup, so texts
+                // close to the limit can only be written without highlighting
+                Err(XlsxError::MaxStringLengthExceeded) => {
+                    let text: String = fragments.into_iter().map(|(_, text)| text).collect();
+                    self.worksheet.write_string(row, col, text)?;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Truncates the given text to fit into a cell, marking it with [`TRUNCATION_MARKER`].
+    fn truncate(&mut self, text: &str) -> String {
+        if text.chars().count() <= MAX_CELL_CHARS {
+            return text.into();
+        }
+
+        self.truncated_cells += 1;
+
+        format!(
+            "{}{TRUNCATION_MARKER}",
+            prefix(text, MAX_CELL_CHARS - TRUNCATION_MARKER.chars().count())
+        )
+    }
+
+    /// Finishes the current worksheet and continues in a new one, starting with the header row.
+    fn start_next_worksheet(&mut self) -> Result<(), AnnimateError> {
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(format!(
+            "{} ({})",
+            self.name,
+            self.full_worksheets.len() + 2
+        ))?;
+
+        let full_worksheet = std::mem::replace(&mut self.worksheet, worksheet);
+        self.full_worksheets
+            .push(finish_worksheet(full_worksheet, self.rows, self.cols)?);
+
+        self.rows = 0;
+        self.cols = 0;
+
+        let header = self.header.clone().unwrap_or_default();
+        self.write_row(header.into_iter().map(TableCell::Text).collect())
+    }
+
+    /// Returns all worksheets written so far together with the number of truncated cells.
+    fn into_worksheets(self) -> Result<(Vec<Worksheet>, usize), AnnimateError> {
+        let mut worksheets = self.full_worksheets;
+        worksheets.push(finish_worksheet(self.worksheet, self.rows, self.cols)?);
+        Ok((worksheets, self.truncated_cells))
+    }
+}
+
+fn finish_worksheet(
+    mut worksheet: Worksheet,
+    rows: u32,
+    cols: u16,
+) -> Result<Worksheet, AnnimateError> {
+    worksheet.autofit();
+
+    if rows > 0 && cols > 0 {
+        worksheet.add_table(
+            0,
+            0,
+            rows - 1,
+            cols - 1,
+            // Treat the first row as a header row iff there are more rows than just the header
+            // because Excel disallows tables consisting only of a header row
+            &Table::new().set_header_row(rows > 1),
+        )?;
+    }
+
+    Ok(worksheet)
+}
+
+/// Returns the longest prefix of `text` with at most `max_chars` characters.
+fn prefix(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+impl TableWriter for XlsxTableWriter {
+    fn write_record<I>(&mut self, record: I) -> Result<(), AnnimateError>
+    where
+        I: IntoIterator<Item: Into<TableCell>>,
+    {
+        let cells: Vec<TableCell> = record.into_iter().map(Into::into).collect();
+
+        if self.header.is_none() {
+            self.header = Some(
+                cells
+                    .iter()
+                    .map(|cell| match cell {
+                        TableCell::Text(text) | TableCell::Value(text, _) => text.clone(),
+                        TableCell::Kwic(fragments) => {
+                            fragments.iter().map(|f| f.text.as_str()).collect()
+                        }
+                    })
+                    .collect(),
+            );
+        } else if self.rows >= self.max_rows {
+            self.start_next_worksheet()?;
+        }
+
+        self.write_row(cells)
+    }
+
     // Worksheet writes are buffered into in-memory state; the actual I/O happens later in
     // `Workbook::save_to_writer`, which propagates errors directly.
     fn flush(&mut self) -> Result<(), AnnimateError> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn continues_in_new_worksheet_with_header_when_full() {
+        let mut writer = XlsxTableWriter::new("Data").unwrap();
+        writer.max_rows = 3;
+
+        writer
+            .write_record(["Number", "Text"].map(String::from))
+            .unwrap();
+        for i in 1..=5 {
+            writer.write_record([i.to_string(), "text".into()]).unwrap();
+        }
+
+        let (worksheets, truncated_cells) = writer.into_worksheets().unwrap();
+
+        assert_eq!(
+            worksheets.iter().map(Worksheet::name).collect_vec(),
+            ["Data", "Data (2)", "Data (3)"]
+        );
+        assert_eq!(truncated_cells, 0);
+    }
+
+    #[test]
+    fn truncates_long_cells() {
+        let mut writer = XlsxTableWriter::new("Data").unwrap();
+
+        let short = "ä".repeat(MAX_CELL_CHARS);
+        let long = "ä".repeat(MAX_CELL_CHARS + 1);
+
+        assert_eq!(writer.truncate(&short), short);
+
+        let truncated = writer.truncate(&long);
+        assert_eq!(truncated.chars().count(), MAX_CELL_CHARS);
+        assert!(truncated.ends_with(TRUNCATION_MARKER));
+
+        writer
+            .write_record([
+                TableCell::Text(long.clone()),
+                TableCell::Kwic(vec![
+                    TextFragment {
+                        kind: TextFragmentKind::Context,
+                        text: short,
+                    },
+                    TextFragment {
+                        kind: TextFragmentKind::Match(0),
+                        text: long,
+                    },
+                ]),
+            ])
+            .unwrap();
+
+        assert_eq!(writer.truncated_cells, 3);
+    }
+}
@@ -4,6 +4,8 @@
 
 use std::collections::btree_map::Entry;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
 use anno::NodeAnnoKeys;
 use format::QueryInfo;
@@ -18,13 +20,16 @@ use serde::Serialize;
 mod anno;
 mod aql;
 mod cache;
+mod check;
 mod error;
 mod format;
 mod import;
 mod metadata;
 mod name;
+mod parallel;
 mod project;
 mod query;
+mod sampling;
 mod util;
 mod version;
 
@@ -36,14 +41,23 @@ pub use aql::{
     LineColumnIndex, LineColumnRange, QueryAnalysisResult, QueryNode, QueryNodePropertyKey,
     QueryNodes, QueryValidationError,
 };
+pub use check::{ProjectDiagnostic, ProjectDiagnosticKind, ProjectDiagnostics};
 pub use error::AnnimateError;
-pub use format::{CsvExportConfig, ExportFormat, TableExportColumn, XlsxExportConfig};
+pub use format::{
+    CsvDelimiter, CsvDialect, CsvExportConfig, CsvLineTerminator, CsvQuoteStyle, ExportFormat,
+    FrequencySplit, HtmlExportConfig, JsonlExportConfig, OdsExportConfig, TableExportColumn,
+    TableExportMode, ValueType, XlsxExportConfig, XlsxOptions,
+};
 pub use graphannis::corpusstorage::CorpusInfo;
 pub use graphannis::graph::AnnoKey;
 pub use project::{
-    Project, ProjectContext, ProjectExportColumn, ProjectExportFormat, load_project, save_project,
+    Project, ProjectContext, ProjectExportColumn, ProjectExportFormat, ProjectFormatOptions,
+    ProjectQuery, ProjectTraversalKind, load_project, save_project,
+};
+pub use query::{
+    AnnoAggregation, ExportData, ExportDataText, ExportDataValue, NodePosition, QueryLanguage,
+    TextFormatting, Traversal, TraversalItem,
 };
-pub use query::{ExportData, ExportDataText, ExportDataValue, QueryLanguage};
 pub use version::{VERSION_INFO, VersionInfo};
 
 use crate::anno::EdgeTypes;
@@ -56,6 +70,7 @@ pub struct Storage {
     validation_storage: ValidationStorage,
     metadata_storage: MetadataStorage,
     cache_storage: CacheStorage,
+    parallelism: Parallelism,
 }
 
 impl Storage {
@@ -83,9 +98,20 @@ impl Storage {
             validation_storage,
             metadata_storage,
             cache_storage,
+            parallelism: Parallelism::default(),
         })
     }
 
+    /// Sets how matches are searched and collected during exports.
+    ///
+    /// Defaults to [`Parallelism::default`], which depends on the platform.
+    pub fn with_parallelism(self, parallelism: Parallelism) -> Self {
+        Self {
+            parallelism,
+            ..self
+        }
+    }
+
     /// Returns information about stored corpora.
     pub fn corpora(&self) -> Result<Corpora, AnnimateError> {
         let sets = self.metadata_storage.corpus_sets();
@@ -421,6 +447,64 @@ impl Storage {
         Ok(segmentations)
     }
 
+    /// Checks a project against the stored corpora.
+    ///
+    /// This reports corpora that don't exist, invalid queries, incomplete columns and columns
+    /// referring to query nodes, annotations, edge types or segmentations that don't exist in the
+    /// selected corpora, so that these problems surface before an export is started.
+    pub fn check_project(&self, project: &Project) -> Result<ProjectDiagnostics, AnnimateError> {
+        check::check_project(self, project)
+    }
+
+    /// Counts the matches of a query in each of the given corpora.
+    ///
+    /// If `by_document` is `true`, the matches are additionally counted for each document. This
+    /// requires finding all matches, so it takes longer than counting only per corpus.
+    ///
+    /// If searching any of the corpora takes longer than `timeout`, this fails with
+    /// [`AnnimateError::QueryTimedOut`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn count_matches<F, G, S>(
+        &self,
+        corpus_names: &[S],
+        aql_query: &str,
+        query_language: QueryLanguage,
+        by_document: bool,
+        timeout: Option<Duration>,
+        on_status: F,
+        cancel_requested: G,
+    ) -> Result<MatchCounts, AnnimateError>
+    where
+        F: Fn(CountStatusEvent),
+        G: Fn() -> bool,
+        S: AsRef<str>,
+    {
+        on_status(CountStatusEvent::Started);
+        error::cancel_if(&cancel_requested)?;
+
+        let query = Query::new(
+            &self.corpus_storage,
+            &self.cache_storage,
+            corpus_names,
+            aql_query,
+            query_language,
+        )?;
+
+        let corpora = query.count(
+            by_document,
+            timeout,
+            |count| {
+                on_status(CountStatusEvent::CorporaSearched {
+                    count,
+                    total_count: corpus_names.len(),
+                });
+            },
+            &cancel_requested,
+        )?;
+
+        Ok(MatchCounts { corpora })
+    }
+
     /// Exports matches for a query.
     pub fn export_matches<F, G, P>(
         &self,
@@ -433,6 +517,145 @@ impl Storage {
         F: Fn(ExportStatusEvent),
         G: Fn() -> bool,
         P: AsRef<Path>,
+    {
+        self.find_matches_for_export(
+            config,
+            on_status,
+            &cancel_requested,
+            |format, matches, query_info, anno_key_formats, on_matches_exported| {
+                util::write_atomically(output_file, |out| {
+                    format::export(
+                        format,
+                        matches,
+                        query_info,
+                        anno_key_formats,
+                        out,
+                        on_matches_exported,
+                        &cancel_requested,
+                    )
+                })
+            },
+        )
+    }
+
+    /// Exports matches for several queries in one run.
+    ///
+    /// Returns the paths of the files that were written.
+    pub fn export_batch<F, G>(
+        &self,
+        configs: Vec<NamedExportConfig>,
+        target: BatchExportTarget,
+        on_status: F,
+        cancel_requested: G,
+    ) -> Result<Vec<PathBuf>, AnnimateError>
+    where
+        F: Fn(BatchExportStatusEvent),
+        G: Fn() -> bool,
+    {
+        let query_count = configs.len();
+        let on_query_status = |query_index| {
+            let on_status = &on_status;
+            move |event| {
+                on_status(BatchExportStatusEvent {
+                    query_index,
+                    query_count,
+                    event,
+                });
+            }
+        };
+
+        match target {
+            BatchExportTarget::Directory(dir) => {
+                let file_stems =
+                    name::get_query_file_stems(&configs.iter().map(|c| &c.name).collect_vec());
+
+                configs
+                    .into_iter()
+                    .zip(file_stems)
+                    .enumerate()
+                    .map(
+                        |(query_index, (NamedExportConfig { config, .. }, file_stem))| {
+                            let output_file =
+                                dir.join(format!("{file_stem}.{}", config.format.file_extension()));
+
+                            self.export_matches(
+                                config,
+                                &output_file,
+                                on_query_status(query_index),
+                                &cancel_requested,
+                            )?;
+
+                            Ok(output_file)
+                        },
+                    )
+                    .collect()
+            }
+            BatchExportTarget::Workbook(output_file) => {
+                let sheet_names =
+                    name::get_query_sheet_names(&configs.iter().map(|c| &c.name).collect_vec());
+
+                let sheets: Vec<_> = configs
+                    .into_iter()
+                    .zip(sheet_names)
+                    .enumerate()
+                    .map(
+                        |(query_index, (NamedExportConfig { config, .. }, sheet_name))| {
+                            self.find_matches_for_export(
+                                config,
+                                on_query_status(query_index),
+                                &cancel_requested,
+                                |format,
+                                 matches,
+                                 query_info,
+                                 anno_key_formats,
+                                 on_matches_exported| {
+                                    let ExportFormat::Xlsx(xlsx_config) = format else {
+                                        return Err(AnnimateError::BatchWorkbookRequiresXlsx);
+                                    };
+
+                                    format::export_xlsx_sheets(
+                                        &xlsx_config,
+                                        &sheet_name,
+                                        matches,
+                                        &query_info,
+                                        anno_key_formats,
+                                        on_matches_exported,
+                                        &cancel_requested,
+                                    )
+                                },
+                            )
+                        },
+                    )
+                    .try_collect()?;
+
+                util::write_atomically(&output_file, |out| {
+                    format::save_xlsx_workbook(sheets, out)
+                })?;
+
+                Ok(vec![output_file])
+            }
+        }
+    }
+
+    /// Searches the matches to export for a query and passes them to `export` together with
+    /// everything needed to write them in the given format.
+    fn find_matches_for_export<E, F, G, T>(
+        &self,
+        config: ExportConfig,
+        on_status: F,
+        cancel_requested: G,
+        export: E,
+    ) -> Result<T, AnnimateError>
+    where
+        E: FnOnce(
+            ExportFormat,
+            Box<dyn ExactSizeIterator<Item = Result<query::Match, AnnimateError>> + '_>,
+            QueryInfo<'_, String>,
+            format::AnnoKeyFormats<'_>,
+            &dyn Fn(usize),
+        ) -> Result<T, AnnimateError>,
+        F: Fn(ExportStatusEvent),
+        G: Fn() -> bool,
     {
         on_status(ExportStatusEvent::Started);
         error::cancel_if(&cancel_requested)?;
@@ -456,8 +679,18 @@ impl Storage {
             config.query_language,
         )?;
 
+        let counts = match &config.format {
+            ExportFormat::Xlsx(xlsx_config) if xlsx_config.options.counts_sheet => {
+                Some(query.count(false, config.timeout, |_| (), &cancel_requested)?)
+            }
+            _ => None,
+        };
+
         let matches = query.find(
             config.format.get_export_data(),
+            config.sampling,
+            config.timeout,
+            self.parallelism,
             |count| {
                 on_status(ExportStatusEvent::CorporaSearched {
                     count,
@@ -474,28 +707,24 @@ impl Storage {
             aql_query: &config.aql_query,
             query_language: config.query_language,
             nodes: query.nodes(),
+            random_seed: config.sampling.random_seed,
+            counts: counts.as_deref(),
         };
 
         let total_count = matches.len();
 
-        util::write_atomically(output_file, |out| {
-            format::export(
-                config.format,
-                matches,
-                query_info,
-                format::AnnoKeyFormats {
-                    node: node_anno_keys.format(),
-                    edge: &|edge_type| edge_types.format(edge_type),
-                },
-                out,
-                |count| {
-                    on_status(ExportStatusEvent::MatchesExported { count, total_count });
-                },
-                &cancel_requested,
-            )
-        })?;
-
-        Ok(())
+        export(
+            config.format,
+            Box::new(matches),
+            query_info,
+            format::AnnoKeyFormats {
+                node: node_anno_keys.format(),
+                edge: &|edge_type| edge_types.format(edge_type),
+            },
+            &|count| {
+                on_status(ExportStatusEvent::MatchesExported { count, total_count });
+            },
+        )
     }
 
     fn delete_corpus_with_cache(&self, corpus_name: &str) -> Result<(), AnnimateError> {
@@ -545,8 +774,168 @@ pub struct ExportConfig {
     /// Language of the query to run.
     pub query_language: QueryLanguage,
 
+    /// Maximum duration of the search in each corpus.
+    ///
+    /// If the search in a corpus takes longer, the export fails with
+    /// [`AnnimateError::QueryTimedOut`].
+    pub timeout: Option<Duration>,
+
     /// Format in which to export matches.
     pub format: ExportFormat,
+
+    /// Selection of the matches to export.
+    pub sampling: ExportSampling,
+}
+
+/// Configuration of the export of one query in a batch export.
+pub struct NamedExportConfig {
+    /// Name of the query.
+    ///
+    /// This is used to name the exported file or worksheet. If it is empty, a name is derived from
+    /// the position of the query within the batch.
+    pub name: String,
+
+    /// Configuration of the export of the query.
+    pub config: ExportConfig,
+}
+
+/// Where to write the results of a batch export.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BatchExportTarget {
+    /// Write one file per query into the given directory.
+    ///
+    /// Each file is named after its query, with the extension of its export format.
+    Directory(PathBuf),
+
+    /// Write a single XLSX workbook with one data worksheet per query to the given file.
+    ///
+    /// All queries must be exported in the XLSX format, otherwise the export fails with
+    /// [`AnnimateError::BatchWorkbookRequiresXlsx`].
+    Workbook(PathBuf),
+}
+
+/// Selection of a subset of the matches of a query for export.
+///
+/// The selection happens in the following order:
+/// 1. From the matches of each corpus, at most [`limit_per_corpus`](Self::limit_per_corpus) are
+///    selected.
+/// 2. From the selected matches of all corpora, the first [`offset`](Self::offset) are skipped.
+/// 3. From the remaining matches, at most [`limit`](Self::limit) are selected.
+///
+/// If a [`random_seed`](Self::random_seed) is given, the selection in steps 1 and 3 is a uniform
+/// random sample, otherwise the first matches are selected. In both cases, the selected matches
+/// are exported in their original order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExportSampling {
+    /// Number of matches to skip.
+    pub offset: usize,
+
+    /// Maximum number of matches to export.
+    pub limit: Option<usize>,
+
+    /// Maximum number of matches to export per corpus.
+    pub limit_per_corpus: Option<usize>,
+
+    /// Seed for selecting a random sample of the matches instead of the first ones.
+    ///
+    /// Exports with the same seed and otherwise identical configuration select the same matches.
+    pub random_seed: Option<u64>,
+}
+
+/// How corpora are searched and the data of matches is collected during an export.
+///
+/// In both cases, matches are exported in the same order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parallelism {
+    /// Search one corpus and collect the data of one match at a time.
+    ///
+    /// This is the default except on Linux, since collecting data in parallel leads to a massive
+    /// slowdown on Windows due to high contention on the `RwLock` of the corpus cache used within
+    /// graphANNIS.
+    Sequential,
+
+    /// Search corpora and collect the data of matches on multiple threads.
+    ///
+    /// This is the default on Linux with multiple CPU cores, where it leads to a significant
+    /// speedup depending on the number of cores. Use the `find` benchmark to compare both
+    /// strategies on a given machine.
+    Parallel,
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        let multiple_cores = thread::available_parallelism().is_ok_and(|n| n.get() > 1);
+
+        if cfg!(target_os = "linux") && multiple_cores {
+            Parallelism::Parallel
+        } else {
+            Parallelism::Sequential
+        }
+    }
+}
+
+/// Number of matches of a query in each of the searched corpora.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchCounts {
+    /// Counts for each corpus, ordered by corpus name.
+    pub corpora: Vec<CorpusMatchCount>,
+}
+
+impl MatchCounts {
+    /// Returns the total number of matches in all corpora.
+    pub fn total_count(&self) -> u64 {
+        self.corpora.iter().map(|c| c.match_count).sum()
+    }
+}
+
+/// Number of matches of a query in a corpus.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusMatchCount {
+    /// Name of the corpus.
+    pub corpus_name: String,
+
+    /// Number of matches in the corpus.
+    pub match_count: u64,
+
+    /// Number of documents in the corpus with at least one match.
+    pub document_count: u64,
+
+    /// Counts for each document with at least one match, ordered by document node name.
+    ///
+    /// This is only present if counts by document were requested.
+    pub documents: Option<Vec<DocumentMatchCount>>,
+}
+
+/// Number of matches of a query in a document.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMatchCount {
+    /// Node name of the document.
+    pub document_node_name: String,
+
+    /// Number of matches in the document.
+    pub match_count: u64,
+}
+
+/// Event describing the status of an ongoing count.
+#[derive(Clone, Debug, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum CountStatusEvent {
+    /// Count was started.
+    Started,
+    /// Corpora were searched.
+    CorporaSearched {
+        /// Number of corpora searched.
+        count: usize,
+        /// Total number of corpora to search.
+        total_count: usize,
+    },
 }
 
 /// Event describing the status of an ongoing export.
@@ -574,6 +963,20 @@ pub enum ExportStatusEvent {
         total_count: usize,
     },
 }
+
+/// Event describing the status of an ongoing batch export.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchExportStatusEvent {
+    /// Index of the query the event refers to.
+    pub query_index: usize,
+    /// Total number of queries to export.
+    pub query_count: usize,
+    /// Status of the export of the query.
+    #[serde(flatten)]
+    pub event: ExportStatusEvent,
+}
+
 /// Event describing the status of an ongoing import.
 #[derive(Clone, Debug, Serialize)]
 #[serde(
@@ -620,6 +1023,13 @@ pub struct ImportCorpus {
     trace: Vec<FilesystemEntity<String>>,
 }
 
+impl ImportCorpus {
+    /// Returns the name of the file the corpus is being imported from.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+}
+
 impl From<&ImportableCorpus> for ImportCorpus {
     fn from(importable_corpus: &ImportableCorpus) -> ImportCorpus {
         ImportCorpus {
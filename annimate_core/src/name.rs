@@ -71,6 +71,140 @@ pub(crate) fn node_name_to_node_id(
         .and_then(|node_id| node_id.ok_or_else(|| GraphAnnisError::NoSuchNodeID(node_name.into())))
 }
 
+/// Returns a file stem for each query in a batch export, based on the query names.
+///
+/// Characters that are not allowed in file names on some platforms are replaced with `_`, and names
+/// reserved for devices on Windows such as `CON` are prefixed with `_`. Unnamed queries are named
+/// after their position, and duplicates are made unique by appending a number, ignoring case as
+/// some file systems do.
+pub(crate) fn get_query_file_stems<S>(query_names: &[S]) -> Vec<String>
+where
+    S: AsRef<str>,
+{
+    unique_names(
+        query_names
+            .iter()
+            .map(|name| {
+                name.as_ref()
+                    .chars()
+                    .map(|c| match c {
+                        '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+                        c if c.is_control() => '_',
+                        c => c,
+                    })
+                    .collect::<String>()
+                    // Windows doesn't allow file names ending with a dot or a space
+                    .trim_end_matches(['.', ' '])
+                    .trim_start()
+                    .to_string()
+            })
+            .map(|stem| {
+                if is_windows_device_name(&stem) {
+                    format!("_{stem}")
+                } else {
+                    stem
+                }
+            }),
+        &[],
+        |a, b| a == b,
+    )
+}
+
+/// Returns whether Windows treats a file with the given stem as a device, even with an extension.
+fn is_windows_device_name(stem: &str) -> bool {
+    let base = stem.split('.').next().unwrap_or_default().trim_end();
+
+    match base.to_ascii_uppercase().as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        name => {
+            (name.starts_with("COM") || name.starts_with("LPT"))
+                && matches!(name.as_bytes()[3..], [b'1'..=b'9'])
+        }
+    }
+}
+
+/// Maximum number of characters of a worksheet name taken from a query name.
+///
+/// Excel allows at most 31 characters, which leaves room for the suffixes making names unique and
+/// numbering the worksheets of a query with more matches than fit into one worksheet.
+const MAX_SHEET_NAME_CHARS: usize = 20;
+
+/// Returns a worksheet name for each query in a batch export into a single workbook, based on the
+/// query names.
+///
+/// Characters that are not allowed in worksheet names are replaced with `_` and long names are
+/// shortened. Unnamed queries are named after their position, and duplicates as well as names of
+/// other worksheets such as "Information" are made unique by appending a number, ignoring case as
+/// Excel does. This includes the names `"{name} (2)"`, `"{name} (3)"` etc. of the additional
+/// worksheets of a query with more matches than fit into one worksheet.
+pub(crate) fn get_query_sheet_names<S>(query_names: &[S]) -> Vec<String>
+where
+    S: AsRef<str>,
+{
+    unique_names(
+        query_names.iter().map(|name| {
+            name.as_ref()
+                .chars()
+                .map(|c| match c {
+                    '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+                    c if c.is_control() => '_',
+                    c => c,
+                })
+                .take(MAX_SHEET_NAME_CHARS)
+                .collect::<String>()
+                // Excel doesn't allow worksheet names starting or ending with an apostrophe
+                .trim_matches(['\'', ' '])
+                .to_string()
+        }),
+        &["Information", "Counts"],
+        |a, b| a == b || is_overflow_sheet_name(a, b) || is_overflow_sheet_name(b, a),
+    )
+}
+
+/// Returns whether `name` is the name of an additional worksheet of the worksheet `base`.
+fn is_overflow_sheet_name(name: &str, base: &str) -> bool {
+    name.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix(" ("))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Makes the given names unique, where `conflict` tells whether two lowercase names conflict.
+fn unique_names<I, F>(names: I, reserved: &[&str], conflict: F) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+    F: Fn(&str, &str) -> bool,
+{
+    let mut used: Vec<String> = reserved.iter().map(|name| name.to_lowercase()).collect();
+
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let name = if name.is_empty() {
+                format!("Query {}", index + 1)
+            } else {
+                name
+            };
+
+            (1..)
+                .map(|i| match i {
+                    1 => name.clone(),
+                    i => format!("{name}_{i}"),
+                })
+                .find(|candidate| {
+                    let candidate = candidate.to_lowercase();
+                    let unused = !used.iter().any(|name| conflict(name, &candidate));
+                    if unused {
+                        used.push(candidate);
+                    }
+                    unused
+                })
+                .expect("some candidate should be unused")
+        })
+        .collect()
+}
+
 // From https://github.com/korpling/graphANNIS/blob/2c656d5c79e15b7c29809132c467681fa473464f/graphannis/src/annis/db/corpusstorage.rs#L301
 // Exception: Duplicate b'"' removed
 const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
@@ -169,4 +303,48 @@ mod tests {
             "c%C3%B6rp%C3%BCs/d%C3%B6c"
         );
     }
+
+    #[test]
+    fn get_query_file_stems_replaces_invalid_characters_and_deduplicates() {
+        assert_eq!(
+            get_query_file_stems(&["nouns", "a/b: c?", "", "Nouns", "verbs. ", "nouns"]),
+            ["nouns", "a_b_ c_", "Query 3", "Nouns_2", "verbs", "nouns_3"]
+        );
+    }
+
+    #[test]
+    fn get_query_file_stems_avoids_windows_device_names() {
+        assert_eq!(
+            get_query_file_stems(&["CON", "nul.txt", "com1", "COM10", "lpt", "console"]),
+            ["_CON", "_nul.txt", "_com1", "COM10", "lpt", "console"]
+        );
+    }
+
+    #[test]
+    fn get_query_sheet_names_avoids_overflow_sheet_names() {
+        assert_eq!(
+            get_query_sheet_names(&["a", "A (2)", "b (2)", "b", "c (x)", "c"]),
+            ["a", "A (2)_2", "b (2)", "b_2", "c (x)", "c"]
+        );
+    }
+
+    #[test]
+    fn get_query_sheet_names_shortens_and_avoids_reserved_names() {
+        assert_eq!(
+            get_query_sheet_names(&[
+                "[nouns]",
+                "'quoted'",
+                "information",
+                "a very long query name exceeding the limit",
+                "",
+            ]),
+            [
+                "_nouns_",
+                "quoted",
+                "information_2",
+                "a very long query na",
+                "Query 5"
+            ]
+        );
+    }
 }
@@ -0,0 +1,251 @@
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use std::vec;
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::error::AnnimateError;
+
+/// Number of items processed together by [`ParMapChunks`].
+const CHUNK_SIZE: usize = 256;
+
+/// Interval in which cancellation is checked while waiting for items to be processed.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stack size of the threads processing items, same as in the desktop app and the CLI.
+const THREAD_STACK_SIZE_MB: usize = 64;
+
+/// Thread pool processing items in parallel.
+///
+/// Its threads have a larger stack size than those of the global pool (default is 2 MB) to avoid
+/// stack overflows when searching corpora with complex queries.
+static THREAD_POOL: LazyLock<ThreadPool> = LazyLock::new(|| {
+    ThreadPoolBuilder::new()
+        .thread_name(|index| format!("annimate-worker-{index}"))
+        .stack_size(THREAD_STACK_SIZE_MB * 1024 * 1024)
+        .build()
+        .expect("thread pool should build")
+});
+
+/// Number of threads processing items in parallel.
+pub(crate) fn thread_count() -> usize {
+    THREAD_POOL.current_num_threads()
+}
+
+/// Applies a fallible function to all items in parallel, preserving their order.
+///
+/// Progress is reported and cancellation is checked on the calling thread, while it waits for the
+/// items to be processed. `on_items_processed` is called with the number of processed items,
+/// starting from zero and excluding the final count, just like it would be when processing the
+/// items one after another. Items that haven't been started when cancellation is requested are
+/// skipped.
+pub(crate) fn try_map_with_progress<F, G, P, T, U>(
+    items: &[T],
+    f: F,
+    mut on_items_processed: P,
+    cancel_requested: G,
+) -> Result<Vec<U>, AnnimateError>
+where
+    F: Fn(&T) -> Result<U, AnnimateError> + Sync,
+    G: Fn() -> bool,
+    P: FnMut(usize),
+    T: Sync,
+    U: Send,
+{
+    let cancelled = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        let handle = scope.spawn(|| {
+            THREAD_POOL.install(|| {
+                items
+                    .par_iter()
+                    .map_with(sender, |sender, item| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return Err(AnnimateError::Cancelled);
+                        }
+
+                        let result = f(item);
+                        // The receiver is only dropped after all items have been processed
+                        let _ = sender.send(());
+                        result
+                    })
+                    .collect()
+            })
+        });
+
+        let mut processed_count = 0;
+        if !items.is_empty() {
+            on_items_processed(processed_count);
+        }
+
+        loop {
+            if cancel_requested() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+
+            match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+                Ok(()) => {
+                    processed_count += 1;
+                    if processed_count < items.len() {
+                        on_items_processed(processed_count);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let result = handle.join().expect("worker thread should not panic");
+
+        if cancelled.load(Ordering::Relaxed) {
+            Err(AnnimateError::Cancelled)
+        } else {
+            result
+        }
+    })
+}
+
+/// Iterator applying a function to the items of a [`Vec`] in parallel, preserving their order.
+///
+/// Items are processed in chunks of [`CHUNK_SIZE`] when the iterator is advanced, so that only one
+/// chunk of results needs to be kept in memory at a time.
+#[derive(Debug)]
+pub(crate) struct ParMapChunks<F, T, U> {
+    items: vec::IntoIter<T>,
+    results: vec::IntoIter<U>,
+    f: F,
+}
+
+impl<F, T, U> ParMapChunks<F, T, U> {
+    pub(crate) fn new(items: Vec<T>, f: F) -> Self {
+        Self {
+            items: items.into_iter(),
+            results: Vec::new().into_iter(),
+            f,
+        }
+    }
+}
+
+impl<F, T, U> Iterator for ParMapChunks<F, T, U>
+where
+    F: Fn(T) -> U + Sync + Send,
+    T: Send,
+    U: Send,
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        if let Some(result) = self.results.next() {
+            return Some(result);
+        }
+
+        let chunk: Vec<_> = self.items.by_ref().take(CHUNK_SIZE).collect();
+        self.results = THREAD_POOL
+            .install(|| chunk.into_par_iter().map(&self.f).collect::<Vec<_>>())
+            .into_iter();
+        self.results.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.results.len() + self.items.len();
+        (len, Some(len))
+    }
+}
+
+impl<F, T, U> ExactSizeIterator for ParMapChunks<F, T, U>
+where
+    F: Fn(T) -> U + Sync + Send,
+    T: Send,
+    U: Send,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn try_map_with_progress_preserves_order() {
+        let items: Vec<_> = (0..100).collect();
+        let progress = RefCell::new(Vec::new());
+
+        let result = try_map_with_progress(
+            &items,
+            |i| Ok(i * 2),
+            |count| progress.borrow_mut().push(count),
+            || false,
+        )
+        .unwrap();
+
+        assert_eq!(result, (0..100).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(progress.into_inner(), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn try_map_with_progress_fails_on_error() {
+        let items: Vec<_> = (0..100).collect();
+
+        let result = try_map_with_progress(
+            &items,
+            |&i| {
+                if i == 42 {
+                    Err(AnnimateError::MatchWithoutNodes)
+                } else {
+                    Ok(i)
+                }
+            },
+            |_| (),
+            || false,
+        );
+
+        assert!(matches!(result, Err(AnnimateError::MatchWithoutNodes)));
+    }
+
+    #[test]
+    fn try_map_with_progress_cancelled() {
+        let items: Vec<_> = (0..100).collect();
+
+        let result = try_map_with_progress(&items, |&i| Ok(i), |_| (), || true);
+
+        assert!(matches!(result, Err(AnnimateError::Cancelled)));
+    }
+
+    #[test]
+    fn try_map_with_progress_uses_thread_pool() {
+        let items: Vec<_> = (0..10).collect();
+
+        let thread_names = try_map_with_progress(
+            &items,
+            |_| Ok(thread::current().name().map(str::to_string)),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+        assert!(thread_names.iter().all(|name| {
+            name.as_deref()
+                .is_some_and(|name| name.starts_with("annimate-worker-"))
+        }));
+    }
+
+    #[test]
+    fn par_map_chunks_preserves_order() {
+        let len = 3 * CHUNK_SIZE + 1;
+        let mut iter = ParMapChunks::new((0..len).collect(), |i| i * 2);
+
+        assert_eq!(iter.len(), len);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.len(), len - 1);
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            (1..len).map(|i| i * 2).collect::<Vec<_>>()
+        );
+    }
+}
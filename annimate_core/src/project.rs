@@ -11,27 +11,64 @@
 //! - `"match-in-context"` column: Added `annotation` key
 //!
 //!   Migration from v1: Add `annotation = "default"`
+//!
+//! ## Version 3 (Annimate 1.10.0)
+//!
+//! - Moved `query`, `query-language` and `columns` into a new `queries` array of tables, each of
+//!   which has an additional optional `name` key
+//! - Added optional `format-options.csv` table with keys `delimiter`, `quote-style`,
+//!   `line-terminator`, `bom` and `escape-formulas`
+//! - Added optional `format-options.xlsx` table with keys `counts-sheet` and `kwic-cells`
+//! - Added optional `query-timeout` key
+//! - Added optional `output-path` key, relative to the location of the project file
+//! - Added `"jsonl"`, `"html"` and `"ods"` values for `export-format`
+//! - `"match-in-context"` column: Added optional `context-unit`, `segment-separator`,
+//!   `gap-marker`, `match-brackets` and `restore-whitespace` keys
+//! - Added `"covered-text"` column with keys `node-index`, `segmentation`, `annotation` and
+//!   optional `segment-separator` and `restore-whitespace`
+//! - Added `"match-position"` column with keys `node-index` and `position`
+//! - `"match-annotation"` column: Added optional `aggregation` key
+//! - Added `"traversal-annotation"` column with keys `edge-type`, `traversal`, `distance`,
+//!   `node-index`, `target-node-index`, `annotation-of`, `annotation` and `value-type`
+//! - `"corpus-metadata"`, `"document-metadata"`, `"match-annotation"` and `"edge-annotation"`
+//!   columns: Added optional `value-type` key
+//!
+//!   Migration from v2: Move `query`, `query-language` and `columns` into a single query without
+//!   a name, missing keys take their default values
 
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use graphannis::corpusstorage::QueryLanguage;
 use graphannis::graph::AnnoKey;
+use itertools::Itertools;
 use serde::Deserialize;
 
 use crate::anno::{AnnoKeyOrDefault, EdgeType, ExportableEdgeComponentType};
 use crate::aql::QueryNodePropertyKey;
 use crate::error::{AnnimateError, AnnimateReadFileError};
-use crate::util;
+use crate::format::{
+    CsvDelimiter, CsvDialect, CsvExportConfig, CsvLineTerminator, CsvQuoteStyle, ExportFormat,
+    HtmlExportConfig, JsonlExportConfig, OdsExportConfig, TableExportColumn, TableExportMode,
+    ValueType, XlsxExportConfig, XlsxOptions,
+};
+use crate::query::{
+    AnnoAggregation, ExportData, ExportDataText, ExportDataValue, NodePosition, TextFormatting,
+    Traversal, TraversalItem,
+};
+use crate::{ExportConfig, ExportSampling, NamedExportConfig, util};
 
 const FILE_HEADER: &str =
     "# Annimate project file\n# https://github.com/matthias-stemmler/annimate\n\n";
 
 /// Annimate project, to be saved to and loaded from a file.
+// Unknown fields are denied so that files using the v2 layout with a top-level `query` under
+// version 3 are rejected instead of silently losing their query
 #[derive(Debug, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Project {
     /// Currently selected corpus set.
     pub corpus_set: Option<String>,
@@ -40,27 +77,70 @@ pub struct Project {
     #[serde(default, rename = "corpora")]
     pub corpus_names: Vec<String>,
 
-    /// Currently entered AQL query.
+    /// Currently configured queries, all of which are run on the selected corpora.
+    #[serde(default)]
+    pub queries: Vec<ProjectQuery>,
+
+    /// Currently configured timeout for searching each corpus, in seconds.
+    pub query_timeout: Option<u32>,
+
+    /// Currently selected export format.
+    pub export_format: ProjectExportFormat,
+
+    /// Path of the file or directory the matches were last exported to.
+    ///
+    /// In the project file, this is stored relative to the location of the project file if
+    /// possible, so that the project and its exports can be moved together.
+    pub output_path: Option<PathBuf>,
+
+    /// Currently configured options of the export formats.
+    #[serde(default)]
+    pub format_options: ProjectFormatOptions,
+}
+
+/// Query as configured in a project, together with the columns to export for it.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectQuery {
+    /// Name of the query.
+    ///
+    /// In batch exports, this is used to name the file or worksheet containing the matches of the
+    /// query.
+    #[serde(default)]
+    pub name: String,
+
+    /// AQL query.
     #[serde(default, rename = "query")]
     pub aql_query: String,
 
-    /// Currently selected query language.
+    /// Query language.
     #[serde(with = "query_language")]
     pub query_language: QueryLanguage,
 
-    /// Currently configured columns to export.
+    /// Columns to export.
     #[serde(default, rename = "columns")]
     pub export_columns: Vec<ProjectExportColumn>,
+}
 
-    /// Currently selected export format.
-    pub export_format: ProjectExportFormat,
+/// Options of the export formats as configured in a project.
+///
+/// Options are kept for all formats, not just the selected one, so they are not lost when switching
+/// between formats.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ProjectFormatOptions {
+    /// Dialect to use for the CSV format.
+    pub csv: CsvDialect,
+
+    /// Options of the XLSX format.
+    pub xlsx: XlsxOptions,
 }
 
 /// Column to export as configured in a project.
 ///
 /// See [`crate::format::TableExportColumn`].
 #[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all_fields = "kebab-case", tag = "type")]
 pub enum ProjectExportColumn {
     #[serde(rename = "number")]
@@ -69,17 +149,21 @@ pub enum ProjectExportColumn {
     AnnoCorpus {
         #[serde(rename = "annotation")]
         anno_key: Option<AnnoKey>,
+        value_type: Option<ValueType>,
     },
     #[serde(rename = "document-metadata")]
     AnnoDocument {
         #[serde(rename = "annotation")]
         anno_key: Option<AnnoKey>,
+        value_type: Option<ValueType>,
     },
     #[serde(rename = "match-annotation")]
     AnnoMatch {
         #[serde(rename = "annotation")]
         anno_key: Option<AnnoKey>,
         node_index: Option<u32>,
+        aggregation: Option<AnnoAggregation>,
+        value_type: Option<ValueType>,
     },
     #[serde(rename = "edge-annotation")]
     AnnoEdge {
@@ -88,6 +172,7 @@ pub enum ProjectExportColumn {
         anno_key: Option<AnnoKey>,
         source_node_index: Option<u32>,
         target_node_index: Option<u32>,
+        value_type: Option<ValueType>,
     },
     #[serde(rename = "match-in-context")]
     MatchInContext {
@@ -95,8 +180,14 @@ pub enum ProjectExportColumn {
         #[serde(rename = "annotation")]
         anno_key: Option<AnnoKeyOrDefault>,
         context: ProjectContext,
+        context_unit: Option<AnnoKey>,
         #[serde(default)]
         primary_node_indices: Vec<u32>,
+        segment_separator: Option<String>,
+        gap_marker: Option<String>,
+        match_brackets: Option<(String, String)>,
+        #[serde(default)]
+        restore_whitespace: bool,
     },
     #[serde(rename = "query-node-property")]
     QueryNodeProperty {
@@ -105,10 +196,57 @@ pub enum ProjectExportColumn {
         #[serde(rename = "node-index")]
         match_node_index: Option<u32>,
     },
+    #[serde(rename = "covered-text")]
+    CoveredText {
+        node_index: Option<u32>,
+        segmentation: Option<String>,
+        #[serde(rename = "annotation")]
+        anno_key: Option<AnnoKeyOrDefault>,
+        segment_separator: Option<String>,
+        #[serde(default)]
+        restore_whitespace: bool,
+    },
+    #[serde(rename = "match-position")]
+    MatchPosition {
+        node_index: Option<u32>,
+        position: NodePosition,
+    },
+    #[serde(rename = "traversal-annotation")]
+    TraversalAnno {
+        edge_type: Option<EdgeType>,
+        traversal: ProjectTraversalKind,
+        distance: Option<u32>,
+        node_index: Option<u32>,
+        target_node_index: Option<u32>,
+        #[serde(rename = "annotation-of")]
+        item: TraversalItem,
+        #[serde(rename = "annotation")]
+        anno_key: Option<AnnoKey>,
+        value_type: Option<ValueType>,
+    },
+}
+
+/// Kind of traversal of a "traversal annotation" column as configured in a project.
+///
+/// See [`crate::Traversal`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProjectTraversalKind {
+    /// Parent of the node given by `node-index`.
+    Parent,
+
+    /// Ancestor of the node given by `node-index` at the given `distance`.
+    Ancestor,
+
+    /// Children of the node given by `node-index`.
+    Children,
+
+    /// Path from the node given by `node-index` to the node given by `target-node-index`.
+    Path,
 }
 
 /// Context configuration for a "match in context" column as configured in a project.
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all_fields = "kebab-case", untagged)]
 pub enum ProjectContext {
     /// Same context size on both sides.
@@ -128,11 +266,302 @@ pub enum ProjectContext {
 ///
 /// See [`crate::format::ExportFormat`].
 #[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum ProjectExportFormat {
     Csv,
     Xlsx,
+    Jsonl,
+    Html,
+    Ods,
+}
+
+impl Project {
+    /// Converts a project with a single query into an [`ExportConfig`].
+    ///
+    /// This fails if the project doesn't contain exactly one query or if any of the export columns
+    /// is incomplete, i.e. misses a setting that is required for the export, such as the
+    /// annotation of a "match annotation" column.
+    pub fn into_export_config(self) -> Result<ExportConfig, AnnimateError> {
+        let count = self.queries.len();
+
+        match <[_; 1]>::try_from(self.into_export_configs()?) {
+            Ok([named_export_config]) => Ok(named_export_config.config),
+            Err(_) => Err(AnnimateError::ExpectedSingleQuery { count }),
+        }
+    }
+
+    /// Converts the project into one [`NamedExportConfig`] per query, e.g. for
+    /// [`Storage::export_batch`](crate::Storage::export_batch).
+    ///
+    /// This fails if any of the export columns is incomplete, i.e. misses a setting that is
+    /// required for the export, such as the annotation of a "match annotation" column.
+    pub fn into_export_configs(self) -> Result<Vec<NamedExportConfig>, AnnimateError> {
+        let timeout = self
+            .query_timeout
+            .map(|secs| Duration::from_secs(secs.into()));
+
+        self.queries
+            .into_iter()
+            .enumerate()
+            .map(|(query_index, query)| {
+                let columns = query
+                    .export_columns
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, column)| {
+                        column
+                            .into_table_export_column()
+                            .ok_or(AnnimateError::IncompleteExportColumn { query_index, index })
+                    })
+                    .try_collect()?;
+
+                Ok(NamedExportConfig {
+                    name: query.name,
+                    config: ExportConfig {
+                        corpus_names: self.corpus_names.clone(),
+                        aql_query: query.aql_query,
+                        query_language: query.query_language,
+                        timeout,
+                        format: match self.export_format {
+                            ProjectExportFormat::Csv => ExportFormat::Csv(CsvExportConfig {
+                                columns,
+                                mode: TableExportMode::Matches,
+                                dialect: self.format_options.csv,
+                            }),
+                            ProjectExportFormat::Xlsx => ExportFormat::Xlsx(XlsxExportConfig {
+                                columns,
+                                mode: TableExportMode::Matches,
+                                options: self.format_options.xlsx,
+                            }),
+                            ProjectExportFormat::Jsonl => {
+                                ExportFormat::Jsonl(JsonlExportConfig { columns })
+                            }
+                            ProjectExportFormat::Html => {
+                                ExportFormat::Html(HtmlExportConfig { columns })
+                            }
+                            ProjectExportFormat::Ods => ExportFormat::Ods(OdsExportConfig {
+                                columns,
+                                mode: TableExportMode::Matches,
+                            }),
+                        },
+                        sampling: ExportSampling::default(),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+impl ProjectQuery {
+    /// Writes the query and its columns as a TOML table, as it appears in a project file.
+    ///
+    /// Together with [`ProjectQuery::from_toml`], this allows applications to keep queries that
+    /// they cannot represent otherwise.
+    pub fn to_toml(&self) -> String {
+        toml_edit::DocumentMut::from(query_to_table(self.clone())).to_string()
+    }
+
+    /// Reads a query from a TOML table as written by [`ProjectQuery::to_toml`].
+    pub fn from_toml(s: &str) -> Result<Self, AnnimateError> {
+        toml::from_str(s).map_err(|err| AnnimateError::FailedToReadProject(err.into()))
+    }
+}
+
+impl ProjectExportColumn {
+    /// Writes the column as a TOML table, as it appears in a project file.
+    ///
+    /// Together with [`ProjectExportColumn::from_toml`], this allows applications to keep columns
+    /// that they cannot represent otherwise.
+    pub fn to_toml(&self) -> String {
+        toml_edit::DocumentMut::from(column_to_table(self.clone())).to_string()
+    }
+
+    /// Reads a column from a TOML table as written by [`ProjectExportColumn::to_toml`].
+    pub fn from_toml(s: &str) -> Result<Self, AnnimateError> {
+        toml::from_str(s).map_err(|err| AnnimateError::FailedToReadProject(err.into()))
+    }
+
+    pub(crate) fn into_table_export_column(self) -> Option<TableExportColumn> {
+        Some(match self {
+            ProjectExportColumn::Number => TableExportColumn::Number,
+            ProjectExportColumn::AnnoCorpus {
+                anno_key,
+                value_type,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::CorpusAnno {
+                    anno_key: anno_key?,
+                }),
+                value_type.unwrap_or_default(),
+            ),
+            ProjectExportColumn::AnnoDocument {
+                anno_key,
+                value_type,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::DocumentAnno {
+                    anno_key: anno_key?,
+                }),
+                value_type.unwrap_or_default(),
+            ),
+            ProjectExportColumn::AnnoMatch {
+                anno_key,
+                node_index,
+                aggregation,
+                value_type,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::MatchNodeAnno {
+                    anno_key: anno_key?,
+                    index: node_index?.try_into().ok()?,
+                    aggregation: aggregation.unwrap_or_default(),
+                }),
+                value_type.unwrap_or_default(),
+            ),
+            ProjectExportColumn::AnnoEdge {
+                edge_type,
+                anno_key,
+                source_node_index,
+                target_node_index,
+                value_type,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::EdgeAnno {
+                    edge_type: edge_type?,
+                    anno_key: anno_key?,
+                    source_node_index: source_node_index?.try_into().ok()?,
+                    target_node_index: target_node_index?.try_into().ok()?,
+                }),
+                value_type.unwrap_or_default(),
+            ),
+            ProjectExportColumn::MatchInContext {
+                segmentation,
+                anno_key,
+                context,
+                context_unit,
+                primary_node_indices,
+                segment_separator,
+                gap_marker,
+                match_brackets,
+                restore_whitespace,
+            } => {
+                let (left_context, right_context) = match context {
+                    ProjectContext::Symmetric(size) => (size, size),
+                    ProjectContext::Asymmetric { left, right } => (left, right),
+                };
+
+                TableExportColumn::Data(
+                    ExportData::Text(ExportDataText {
+                        segmentation: {
+                            // An empty segmentation refers to tokens
+                            let segmentation = segmentation?;
+                            (!segmentation.is_empty()).then_some(segmentation)
+                        },
+                        left_context: left_context.try_into().ok()?,
+                        right_context: right_context.try_into().ok()?,
+                        context_unit,
+                        anno_key: anno_key?,
+                        primary_node_indices: Some(
+                            primary_node_indices
+                                .into_iter()
+                                .map(|i| i.try_into().ok())
+                                .collect::<Option<_>>()?,
+                        ),
+                        formatting: {
+                            let default = TextFormatting::default();
+
+                            TextFormatting {
+                                segment_separator: segment_separator
+                                    .unwrap_or(default.segment_separator),
+                                gap_marker: gap_marker.unwrap_or(default.gap_marker),
+                                match_brackets,
+                                restore_whitespace,
+                            }
+                        },
+                    }),
+                    ValueType::Text,
+                )
+            }
+            ProjectExportColumn::QueryNodeProperty {
+                query_node_property_key,
+                match_node_index,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::QueryNodeProperty {
+                    query_node_property_key,
+                    match_node_index: match_node_index?.try_into().ok()?,
+                }),
+                ValueType::Text,
+            ),
+            ProjectExportColumn::CoveredText {
+                node_index,
+                segmentation,
+                anno_key,
+                segment_separator,
+                restore_whitespace,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::CoveredText {
+                    index: node_index?.try_into().ok()?,
+                    segmentation: {
+                        // An empty segmentation refers to tokens
+                        let segmentation = segmentation?;
+                        (!segmentation.is_empty()).then_some(segmentation)
+                    },
+                    anno_key: anno_key?,
+                    formatting: {
+                        let default = TextFormatting::default();
+
+                        TextFormatting {
+                            segment_separator: segment_separator
+                                .unwrap_or(default.segment_separator),
+                            restore_whitespace,
+                            ..default
+                        }
+                    },
+                }),
+                ValueType::Text,
+            ),
+            ProjectExportColumn::MatchPosition {
+                node_index,
+                position,
+            } => TableExportColumn::Data(
+                ExportData::Value(ExportDataValue::MatchNodePosition {
+                    index: node_index?.try_into().ok()?,
+                    position,
+                }),
+                ValueType::Auto,
+            ),
+            ProjectExportColumn::TraversalAnno {
+                edge_type,
+                traversal,
+                distance,
+                node_index,
+                target_node_index,
+                item,
+                anno_key,
+                value_type,
+            } => {
+                let index = node_index?.try_into().ok()?;
+
+                TableExportColumn::Data(
+                    ExportData::Value(ExportDataValue::TraversalAnno {
+                        edge_type: edge_type?,
+                        traversal: match traversal {
+                            ProjectTraversalKind::Parent => Traversal::Parent { index },
+                            ProjectTraversalKind::Ancestor => Traversal::Ancestor {
+                                index,
+                                distance: distance?.try_into().ok()?,
+                            },
+                            ProjectTraversalKind::Children => Traversal::Children { index },
+                            ProjectTraversalKind::Path => Traversal::Path {
+                                source_node_index: index,
+                                target_node_index: target_node_index?.try_into().ok()?,
+                            },
+                        },
+                        item,
+                        anno_key: anno_key?,
+                    }),
+                    value_type.unwrap_or_default(),
+                )
+            }
+        })
+    }
 }
 
 #[derive(Deserialize)]
@@ -144,15 +573,17 @@ struct FormatVersion {
 enum ValidVersion {
     V1,
     V2,
+    V3,
 }
 
 impl FormatVersion {
-    const CURRENT: Self = Self { value: 2 };
+    const CURRENT: Self = Self { value: 3 };
 
     fn validate(self) -> Result<ValidVersion, AnnimateReadFileError> {
         match self.value {
             1 => Ok(ValidVersion::V1),
             2 => Ok(ValidVersion::V2),
+            3 => Ok(ValidVersion::V3),
             _ => Err(AnnimateReadFileError::UnsupportedVersion {
                 version: self.value,
             }),
@@ -173,8 +604,11 @@ impl FromStr for ProjectFile {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match toml::from_str::<FormatVersion>(s)?.validate()? {
-            ValidVersion::V1 => Ok(toml::from_str::<v1::ProjectFile>(s)?.into()),
-            ValidVersion::V2 => Ok(toml::from_str(s)?),
+            ValidVersion::V1 => {
+                Ok(v2::ProjectFile::from(toml::from_str::<v1::ProjectFile>(s)?).into())
+            }
+            ValidVersion::V2 => Ok(toml::from_str::<v2::ProjectFile>(s)?.into()),
+            ValidVersion::V3 => Ok(toml::from_str(s)?),
         }
     }
 }
@@ -188,7 +622,14 @@ where
     let project_file: ProjectFile = fs::read_to_string(path)?
         .parse()
         .map_err(AnnimateError::FailedToReadProject)?;
-    Ok(project_file.project)
+
+    Ok(Project {
+        output_path: project_file
+            .project
+            .output_path
+            .map(|output_path| project_dir(path).join(output_path)),
+        ..project_file.project
+    })
 }
 
 /// Saves a project to a file.
@@ -196,9 +637,15 @@ pub fn save_project<P>(project: Project, path: P) -> Result<(), AnnimateError>
 where
     P: AsRef<Path>,
 {
+    let path = path.as_ref();
     let project_file = ProjectFile {
         format_version: FormatVersion::CURRENT,
-        project,
+        project: Project {
+            output_path: project
+                .output_path
+                .map(|output_path| util::relative_path(&output_path, project_dir(path))),
+            ..project
+        },
     };
 
     util::write_atomically(path, |out| {
@@ -208,6 +655,13 @@ where
     Ok(())
 }
 
+fn project_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
 // We manually build a `toml_edit::DocumentMut` instead of using `toml::to_string_pretty`
 // in order to make the formatting more stable, which produces smaller diffs when project files are
 // checked into a VCS. We still don't have full control, e.g. we can't control the style of strings (https://toml.io/en/v1.0.0#string).
@@ -244,161 +698,396 @@ fn to_string_pretty(project_file: ProjectFile) -> String {
             table["corpora"] = corpora_array.into();
         }
 
-        if !project_file.project.aql_query.is_empty() {
-            table["query"] = project_file.project.aql_query.into();
+        if let Some(query_timeout) = project_file.project.query_timeout {
+            table["query-timeout"] = i64::from(query_timeout).into();
         }
 
-        table["query-language"] = match project_file.project.query_language {
-            QueryLanguage::AQL => query_language::TAG_AQL,
-            QueryLanguage::AQLQuirksV3 => query_language::TAG_AQL_QUIRKS_V3,
+        table["queries"] = project_file
+            .project
+            .queries
+            .into_iter()
+            .map(query_to_table)
+            .collect::<toml_edit::ArrayOfTables>()
+            .into();
+
+        table["export-format"] = match project_file.project.export_format {
+            ProjectExportFormat::Csv => "csv",
+            ProjectExportFormat::Xlsx => "xlsx",
+            ProjectExportFormat::Jsonl => "jsonl",
+            ProjectExportFormat::Html => "html",
+            ProjectExportFormat::Ods => "ods",
         }
         .into();
 
-        table["columns"] = project_file
-            .project
-            .export_columns
-            .into_iter()
-            .map(|column| {
-                let mut table = toml_edit::Table::new();
+        if let Some(output_path) = project_file.project.output_path {
+            table["output-path"] = path_to_item(&output_path);
+        }
 
-                match column {
-                    ProjectExportColumn::Number => {
-                        table["type"] = "number".into();
-                    }
-                    ProjectExportColumn::AnnoCorpus { anno_key } => {
-                        table["type"] = "corpus-metadata".into();
+        let ProjectFormatOptions { csv, xlsx } = project_file.project.format_options;
 
-                        if let Some(anno_key) = anno_key {
-                            table["annotation"] = anno_key_to_item(anno_key);
-                        }
+        if csv != CsvDialect::default() || xlsx != XlsxOptions::default() {
+            let mut format_options_table = toml_edit::Table::new();
+            format_options_table.set_implicit(true);
+
+            if csv != CsvDialect::default() {
+                format_options_table["csv"] = {
+                    let mut table = toml_edit::Table::new();
+
+                    table["delimiter"] = match csv.delimiter {
+                        CsvDelimiter::Comma => "comma",
+                        CsvDelimiter::Semicolon => "semicolon",
+                        CsvDelimiter::Tab => "tab",
                     }
-                    ProjectExportColumn::AnnoDocument { anno_key } => {
-                        table["type"] = "document-metadata".into();
+                    .into();
 
-                        if let Some(anno_key) = anno_key {
-                            table["annotation"] = anno_key_to_item(anno_key);
-                        }
+                    table["quote-style"] = match csv.quote_style {
+                        CsvQuoteStyle::Necessary => "necessary",
+                        CsvQuoteStyle::Always => "always",
                     }
-                    ProjectExportColumn::AnnoMatch {
-                        anno_key,
-                        node_index,
-                    } => {
-                        table["type"] = "match-annotation".into();
-
-                        if let Some(anno_key) = anno_key {
-                            table["annotation"] = anno_key_to_item(anno_key);
-                        }
+                    .into();
 
-                        if let Some(node_index) = node_index {
-                            table["node-index"] = i64::from(node_index).into();
-                        }
+                    table["line-terminator"] = match csv.line_terminator {
+                        CsvLineTerminator::Crlf => "crlf",
+                        CsvLineTerminator::Lf => "lf",
                     }
-                    ProjectExportColumn::AnnoEdge {
-                        edge_type,
-                        anno_key,
-                        source_node_index,
-                        target_node_index,
-                    } => {
-                        table["type"] = "edge-annotation".into();
-
-                        if let Some(EdgeType { ctype, name }) = edge_type {
-                            table["edge-type"] = {
-                                let mut table = toml_edit::InlineTable::new();
-                                table.insert(
-                                    "ctype",
-                                    match ctype {
-                                        ExportableEdgeComponentType::Dominance => {
-                                            "Dominance".into()
-                                        }
-                                        ExportableEdgeComponentType::Pointing => "Pointing".into(),
-                                    },
-                                );
-                                table.insert("name", name.into());
-                                table.into()
-                            };
-                        }
+                    .into();
 
-                        if let Some(anno_key) = anno_key {
-                            table["annotation"] = anno_key_to_item(anno_key);
-                        }
+                    table["bom"] = csv.bom.into();
+                    table["escape-formulas"] = csv.escape_formulas.into();
 
-                        if let Some(source_node_index) = source_node_index {
-                            table["source-node-index"] = i64::from(source_node_index).into();
-                        }
+                    table.into()
+                };
+            }
 
-                        if let Some(target_node_index) = target_node_index {
-                            table["target-node-index"] = i64::from(target_node_index).into();
-                        }
-                    }
-                    ProjectExportColumn::MatchInContext {
-                        segmentation,
-                        anno_key,
-                        context,
-                        primary_node_indices,
-                    } => {
-                        table["type"] = "match-in-context".into();
-
-                        if let Some(segmentation) = segmentation {
-                            table["segmentation"] = segmentation.into();
-                        }
+            if xlsx != XlsxOptions::default() {
+                format_options_table["xlsx"] = {
+                    let mut table = toml_edit::Table::new();
+                    table["counts-sheet"] = xlsx.counts_sheet.into();
+                    table["kwic-cells"] = xlsx.kwic_cells.into();
+                    table.into()
+                };
+            }
 
-                        if let Some(anno_key) = anno_key {
-                            table["annotation"] = match anno_key {
-                                AnnoKeyOrDefault::AnnoKey(anno_key) => anno_key_to_item(anno_key),
-                                AnnoKeyOrDefault::Default => AnnoKeyOrDefault::TAG_DEFAULT.into(),
-                            };
-                        }
+            table["format-options"] = format_options_table.into();
+        }
 
-                        table["context"] = match context {
-                            ProjectContext::Symmetric(size) => i64::from(size).into(),
-                            ProjectContext::Asymmetric { left, right } => {
-                                let mut table = toml_edit::InlineTable::new();
-                                table.insert("left", i64::from(left).into());
-                                table.insert("right", i64::from(right).into());
-                                table.into()
-                            }
-                        };
+        table.into()
+    };
 
-                        if !primary_node_indices.is_empty() {
-                            table["primary-node-indices"] = primary_node_indices
-                                .into_iter()
-                                .map(i64::from)
-                                .collect::<toml_edit::Value>()
-                                .into();
-                        }
-                    }
-                    ProjectExportColumn::QueryNodeProperty {
-                        query_node_property_key,
-                        match_node_index,
-                    } => {
-                        table["type"] = "query-node-property".into();
-
-                        table["property"] = match query_node_property_key {
-                            QueryNodePropertyKey::Fragment => "fragment".into(),
-                            QueryNodePropertyKey::Variable => "variable".into(),
-                        };
-
-                        if let Some(match_node_index) = match_node_index {
-                            table["node-index"] = i64::from(match_node_index).into();
-                        }
-                    }
+    document.to_string()
+}
+
+fn query_to_table(query: ProjectQuery) -> toml_edit::Table {
+    let mut table = toml_edit::Table::new();
+
+    if !query.name.is_empty() {
+        table["name"] = query.name.into();
+    }
+
+    if !query.aql_query.is_empty() {
+        table["query"] = query.aql_query.into();
+    }
+
+    table["query-language"] = match query.query_language {
+        QueryLanguage::AQL => query_language::TAG_AQL,
+        QueryLanguage::AQLQuirksV3 => query_language::TAG_AQL_QUIRKS_V3,
+    }
+    .into();
+
+    table["columns"] = query
+        .export_columns
+        .into_iter()
+        .map(column_to_table)
+        .collect::<toml_edit::ArrayOfTables>()
+        .into();
+
+    table
+}
+
+fn column_to_table(column: ProjectExportColumn) -> toml_edit::Table {
+    let mut table = toml_edit::Table::new();
+
+    match column {
+        ProjectExportColumn::Number => {
+            table["type"] = "number".into();
+        }
+        ProjectExportColumn::AnnoCorpus {
+            anno_key,
+            value_type,
+        } => {
+            table["type"] = "corpus-metadata".into();
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = anno_key_to_item(anno_key);
+            }
+
+            if let Some(value_type) = value_type {
+                table["value-type"] = value_type_to_item(value_type);
+            }
+        }
+        ProjectExportColumn::AnnoDocument {
+            anno_key,
+            value_type,
+        } => {
+            table["type"] = "document-metadata".into();
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = anno_key_to_item(anno_key);
+            }
+
+            if let Some(value_type) = value_type {
+                table["value-type"] = value_type_to_item(value_type);
+            }
+        }
+        ProjectExportColumn::AnnoMatch {
+            anno_key,
+            node_index,
+            aggregation,
+            value_type,
+        } => {
+            table["type"] = "match-annotation".into();
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = anno_key_to_item(anno_key);
+            }
+
+            if let Some(node_index) = node_index {
+                table["node-index"] = i64::from(node_index).into();
+            }
+
+            if let Some(aggregation) = aggregation {
+                table["aggregation"] = aggregation_to_item(aggregation);
+            }
+
+            if let Some(value_type) = value_type {
+                table["value-type"] = value_type_to_item(value_type);
+            }
+        }
+        ProjectExportColumn::AnnoEdge {
+            edge_type,
+            anno_key,
+            source_node_index,
+            target_node_index,
+            value_type,
+        } => {
+            table["type"] = "edge-annotation".into();
+
+            if let Some(edge_type) = edge_type {
+                table["edge-type"] = edge_type_to_item(edge_type);
+            }
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = anno_key_to_item(anno_key);
+            }
+
+            if let Some(source_node_index) = source_node_index {
+                table["source-node-index"] = i64::from(source_node_index).into();
+            }
+
+            if let Some(target_node_index) = target_node_index {
+                table["target-node-index"] = i64::from(target_node_index).into();
+            }
+
+            if let Some(value_type) = value_type {
+                table["value-type"] = value_type_to_item(value_type);
+            }
+        }
+        ProjectExportColumn::MatchInContext {
+            segmentation,
+            anno_key,
+            context,
+            context_unit,
+            primary_node_indices,
+            segment_separator,
+            gap_marker,
+            match_brackets,
+            restore_whitespace,
+        } => {
+            table["type"] = "match-in-context".into();
+
+            if let Some(segmentation) = segmentation {
+                table["segmentation"] = segmentation.into();
+            }
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = match anno_key {
+                    AnnoKeyOrDefault::AnnoKey(anno_key) => anno_key_to_item(anno_key),
+                    AnnoKeyOrDefault::Default => AnnoKeyOrDefault::TAG_DEFAULT.into(),
                 };
+            }
 
-                table
-            })
-            .collect::<toml_edit::ArrayOfTables>()
-            .into();
+            table["context"] = match context {
+                ProjectContext::Symmetric(size) => i64::from(size).into(),
+                ProjectContext::Asymmetric { left, right } => {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("left", i64::from(left).into());
+                    table.insert("right", i64::from(right).into());
+                    table.into()
+                }
+            };
 
-        table["export-format"] = match project_file.project.export_format {
-            ProjectExportFormat::Csv => "csv",
-            ProjectExportFormat::Xlsx => "xlsx",
+            if let Some(context_unit) = context_unit {
+                table["context-unit"] = anno_key_to_item(context_unit);
+            }
+
+            if !primary_node_indices.is_empty() {
+                table["primary-node-indices"] = primary_node_indices
+                    .into_iter()
+                    .map(i64::from)
+                    .collect::<toml_edit::Value>()
+                    .into();
+            }
+
+            if let Some(segment_separator) = segment_separator {
+                table["segment-separator"] = segment_separator.into();
+            }
+
+            if let Some(gap_marker) = gap_marker {
+                table["gap-marker"] = gap_marker.into();
+            }
+
+            if let Some((open, close)) = match_brackets {
+                table["match-brackets"] = [open, close]
+                    .into_iter()
+                    .collect::<toml_edit::Value>()
+                    .into();
+            }
+
+            if restore_whitespace {
+                table["restore-whitespace"] = true.into();
+            }
         }
-        .into();
+        ProjectExportColumn::QueryNodeProperty {
+            query_node_property_key,
+            match_node_index,
+        } => {
+            table["type"] = "query-node-property".into();
+
+            table["property"] = match query_node_property_key {
+                QueryNodePropertyKey::Fragment => "fragment".into(),
+                QueryNodePropertyKey::Variable => "variable".into(),
+            };
+
+            if let Some(match_node_index) = match_node_index {
+                table["node-index"] = i64::from(match_node_index).into();
+            }
+        }
+        ProjectExportColumn::CoveredText {
+            node_index,
+            segmentation,
+            anno_key,
+            segment_separator,
+            restore_whitespace,
+        } => {
+            table["type"] = "covered-text".into();
+
+            if let Some(node_index) = node_index {
+                table["node-index"] = i64::from(node_index).into();
+            }
 
-        table.into()
+            if let Some(segmentation) = segmentation {
+                table["segmentation"] = segmentation.into();
+            }
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = match anno_key {
+                    AnnoKeyOrDefault::AnnoKey(anno_key) => anno_key_to_item(anno_key),
+                    AnnoKeyOrDefault::Default => AnnoKeyOrDefault::TAG_DEFAULT.into(),
+                };
+            }
+
+            if let Some(segment_separator) = segment_separator {
+                table["segment-separator"] = segment_separator.into();
+            }
+
+            if restore_whitespace {
+                table["restore-whitespace"] = true.into();
+            }
+        }
+        ProjectExportColumn::MatchPosition {
+            node_index,
+            position,
+        } => {
+            table["type"] = "match-position".into();
+
+            if let Some(node_index) = node_index {
+                table["node-index"] = i64::from(node_index).into();
+            }
+
+            table["position"] = match position {
+                NodePosition::TokenIndex => "token-index",
+                NodePosition::FirstTokenIndex => "first-token-index",
+                NodePosition::LastTokenIndex => "last-token-index",
+                NodePosition::NodeName => "node-name",
+            }
+            .into();
+        }
+        ProjectExportColumn::TraversalAnno {
+            edge_type,
+            traversal,
+            distance,
+            node_index,
+            target_node_index,
+            item,
+            anno_key,
+            value_type,
+        } => {
+            table["type"] = "traversal-annotation".into();
+
+            if let Some(edge_type) = edge_type {
+                table["edge-type"] = edge_type_to_item(edge_type);
+            }
+
+            table["traversal"] = match traversal {
+                ProjectTraversalKind::Parent => "parent",
+                ProjectTraversalKind::Ancestor => "ancestor",
+                ProjectTraversalKind::Children => "children",
+                ProjectTraversalKind::Path => "path",
+            }
+            .into();
+
+            if let Some(distance) = distance {
+                table["distance"] = i64::from(distance).into();
+            }
+
+            if let Some(node_index) = node_index {
+                table["node-index"] = i64::from(node_index).into();
+            }
+
+            if let Some(target_node_index) = target_node_index {
+                table["target-node-index"] = i64::from(target_node_index).into();
+            }
+
+            table["annotation-of"] = match item {
+                TraversalItem::Nodes => "nodes",
+                TraversalItem::Edges => "edges",
+            }
+            .into();
+
+            if let Some(anno_key) = anno_key {
+                table["annotation"] = anno_key_to_item(anno_key);
+            }
+
+            if let Some(value_type) = value_type {
+                table["value-type"] = value_type_to_item(value_type);
+            }
+        }
     };
 
-    document.to_string()
+    table
+}
+
+fn path_to_item(path: &Path) -> toml_edit::Item {
+    if path.is_relative() {
+        // Always use `/` as the separator for relative paths, so that the project file is the same
+        // on all platforms
+        path.components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .join("/")
+            .into()
+    } else {
+        path.to_string_lossy().as_ref().into()
+    }
 }
 
 fn anno_key_to_item(anno_key: AnnoKey) -> toml_edit::Item {
@@ -410,6 +1099,52 @@ fn anno_key_to_item(anno_key: AnnoKey) -> toml_edit::Item {
     table.into()
 }
 
+fn edge_type_to_item(EdgeType { ctype, name }: EdgeType) -> toml_edit::Item {
+    let mut table = toml_edit::InlineTable::new();
+
+    table.insert(
+        "ctype",
+        match ctype {
+            ExportableEdgeComponentType::Dominance => "Dominance".into(),
+            ExportableEdgeComponentType::Pointing => "Pointing".into(),
+        },
+    );
+    table.insert("name", name.into());
+
+    table.into()
+}
+
+fn aggregation_to_item(aggregation: AnnoAggregation) -> toml_edit::Item {
+    let mut table = toml_edit::InlineTable::new();
+
+    match aggregation {
+        AnnoAggregation::First => {
+            table.insert("mode", "first".into());
+        }
+        AnnoAggregation::Distinct { separator } => {
+            table.insert("mode", "distinct".into());
+            table.insert("separator", separator.into());
+        }
+        AnnoAggregation::All { separator } => {
+            table.insert("mode", "all".into());
+            table.insert("separator", separator.into());
+        }
+    }
+
+    table.into()
+}
+
+fn value_type_to_item(value_type: ValueType) -> toml_edit::Item {
+    match value_type {
+        ValueType::Auto => "auto",
+        ValueType::Text => "text",
+        ValueType::Integer => "integer",
+        ValueType::Float => "float",
+        ValueType::Date => "date",
+    }
+    .into()
+}
+
 mod query_language {
     use std::fmt;
 
@@ -454,8 +1189,9 @@ mod v1 {
     use graphannis::graph::AnnoKey;
     use serde::Deserialize;
 
-    use super::{FormatVersion, ProjectContext, ProjectExportFormat};
+    use super::{FormatVersion, ProjectContext, v2};
     use crate::anno::AnnoKeyOrDefault;
+    use v2::ProjectExportFormat;
 
     #[derive(Deserialize)]
     #[serde(rename_all = "kebab-case")]
@@ -511,11 +1247,11 @@ mod v1 {
         },
     }
 
-    impl From<ProjectFile> for super::ProjectFile {
+    impl From<ProjectFile> for v2::ProjectFile {
         fn from(v1: ProjectFile) -> Self {
-            super::ProjectFile {
+            v2::ProjectFile {
                 format_version: v1.format_version,
-                project: super::Project {
+                project: v2::Project {
                     corpus_set: v1.project.corpus_set,
                     corpus_names: v1.project.corpus_names,
                     aql_query: v1.project.aql_query,
@@ -532,7 +1268,7 @@ mod v1 {
         }
     }
 
-    impl From<ProjectExportColumn> for super::ProjectExportColumn {
+    impl From<ProjectExportColumn> for v2::ProjectExportColumn {
         fn from(c: ProjectExportColumn) -> Self {
             match c {
                 ProjectExportColumn::Number => Self::Number,
@@ -559,3 +1295,187 @@ mod v1 {
         }
     }
 }
+
+mod v2 {
+    use graphannis::corpusstorage::QueryLanguage;
+    use graphannis::graph::AnnoKey;
+    use serde::Deserialize;
+
+    use super::{FormatVersion, ProjectContext};
+    use crate::anno::{AnnoKeyOrDefault, EdgeType};
+    use crate::aql::QueryNodePropertyKey;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub(super) struct ProjectFile {
+        #[serde(flatten)]
+        pub(super) format_version: FormatVersion,
+        pub(super) project: Project,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub(super) struct Project {
+        pub(super) corpus_set: Option<String>,
+        #[serde(default, rename = "corpora")]
+        pub(super) corpus_names: Vec<String>,
+        #[serde(default, rename = "query")]
+        pub(super) aql_query: String,
+        #[serde(with = "super::query_language")]
+        pub(super) query_language: QueryLanguage,
+        #[serde(default, rename = "columns")]
+        pub(super) export_columns: Vec<ProjectExportColumn>,
+        pub(super) export_format: ProjectExportFormat,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all_fields = "kebab-case", tag = "type")]
+    pub enum ProjectExportColumn {
+        #[serde(rename = "number")]
+        Number,
+        #[serde(rename = "corpus-metadata")]
+        AnnoCorpus {
+            #[serde(rename = "annotation")]
+            anno_key: Option<AnnoKey>,
+        },
+        #[serde(rename = "document-metadata")]
+        AnnoDocument {
+            #[serde(rename = "annotation")]
+            anno_key: Option<AnnoKey>,
+        },
+        #[serde(rename = "match-annotation")]
+        AnnoMatch {
+            #[serde(rename = "annotation")]
+            anno_key: Option<AnnoKey>,
+            node_index: Option<u32>,
+        },
+        #[serde(rename = "edge-annotation")]
+        AnnoEdge {
+            edge_type: Option<EdgeType>,
+            #[serde(rename = "annotation")]
+            anno_key: Option<AnnoKey>,
+            source_node_index: Option<u32>,
+            target_node_index: Option<u32>,
+        },
+        #[serde(rename = "match-in-context")]
+        MatchInContext {
+            segmentation: Option<String>,
+            #[serde(rename = "annotation")]
+            anno_key: Option<AnnoKeyOrDefault>,
+            context: ProjectContext,
+            #[serde(default)]
+            primary_node_indices: Vec<u32>,
+        },
+        #[serde(rename = "query-node-property")]
+        QueryNodeProperty {
+            #[serde(rename = "property")]
+            query_node_property_key: QueryNodePropertyKey,
+            #[serde(rename = "node-index")]
+            match_node_index: Option<u32>,
+        },
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum ProjectExportFormat {
+        Csv,
+        Xlsx,
+    }
+
+    impl From<ProjectFile> for super::ProjectFile {
+        fn from(v2: ProjectFile) -> Self {
+            super::ProjectFile {
+                format_version: v2.format_version,
+                project: super::Project {
+                    corpus_set: v2.project.corpus_set,
+                    corpus_names: v2.project.corpus_names,
+                    // v2 -> v3 migration
+                    queries: vec![super::ProjectQuery {
+                        name: String::new(),
+                        aql_query: v2.project.aql_query,
+                        query_language: v2.project.query_language,
+                        export_columns: v2
+                            .project
+                            .export_columns
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                    }],
+                    query_timeout: None,
+                    export_format: v2.project.export_format.into(),
+                    output_path: None,
+                    format_options: super::ProjectFormatOptions::default(),
+                },
+            }
+        }
+    }
+
+    impl From<ProjectExportColumn> for super::ProjectExportColumn {
+        fn from(c: ProjectExportColumn) -> Self {
+            match c {
+                ProjectExportColumn::Number => Self::Number,
+                ProjectExportColumn::AnnoCorpus { anno_key } => Self::AnnoCorpus {
+                    anno_key,
+                    value_type: None,
+                },
+                ProjectExportColumn::AnnoDocument { anno_key } => Self::AnnoDocument {
+                    anno_key,
+                    value_type: None,
+                },
+                ProjectExportColumn::AnnoMatch {
+                    anno_key,
+                    node_index,
+                } => Self::AnnoMatch {
+                    anno_key,
+                    node_index,
+                    aggregation: None,
+                    value_type: None,
+                },
+                ProjectExportColumn::AnnoEdge {
+                    edge_type,
+                    anno_key,
+                    source_node_index,
+                    target_node_index,
+                } => Self::AnnoEdge {
+                    edge_type,
+                    anno_key,
+                    source_node_index,
+                    target_node_index,
+                    value_type: None,
+                },
+                ProjectExportColumn::MatchInContext {
+                    segmentation,
+                    anno_key,
+                    context,
+                    primary_node_indices,
+                } => Self::MatchInContext {
+                    segmentation,
+                    anno_key,
+                    context,
+                    context_unit: None,
+                    primary_node_indices,
+                    segment_separator: None,
+                    gap_marker: None,
+                    match_brackets: None,
+                    restore_whitespace: false,
+                },
+                ProjectExportColumn::QueryNodeProperty {
+                    query_node_property_key,
+                    match_node_index,
+                } => Self::QueryNodeProperty {
+                    query_node_property_key,
+                    match_node_index,
+                },
+            }
+        }
+    }
+
+    impl From<ProjectExportFormat> for super::ProjectExportFormat {
+        fn from(f: ProjectExportFormat) -> Self {
+            match f {
+                ProjectExportFormat::Csv => Self::Csv,
+                ProjectExportFormat::Xlsx => Self::Xlsx,
+            }
+        }
+    }
+}
@@ -1,27 +1,33 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::ops::Bound;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{iter, vec};
 
 pub use graphannis::corpusstorage::QueryLanguage;
-use graphannis::corpusstorage::{ResultOrder, SearchQuery};
+use graphannis::corpusstorage::{MatchExtra, ResultOrder, SearchQuery};
 use graphannis::errors::GraphAnnisError;
 use graphannis::graph::{Edge, GraphStorage};
 use graphannis::model::AnnotationComponentType;
 use graphannis::{CorpusStorage, Graph, util};
+use graphannis_core::annostorage::ValueSearch;
 use graphannis_core::errors::GraphAnnisCoreError;
-use graphannis_core::graph::DEFAULT_NS;
+use graphannis_core::graph::{DEFAULT_NS, NODE_NAME_KEY};
 use graphannis_core::types::{AnnoKey, NodeID};
-use itertools::Itertools;
+use itertools::{Either, Itertools};
+use serde::{Deserialize, Serialize};
 
 use crate::anno::{
     self, AnnoKeyOrDefault, DEFAULT_ORDERING_COMPONENT, EdgeType, GAP_ORDERING_COMPONENT,
-    TOKEN_ANNO_KEY, get_anno_key_for_segmentation,
+    TOK_WHITESPACE_AFTER_ANNO_KEY, TOKEN_ANNO_KEY, get_anno_key_for_segmentation,
 };
 use crate::aql::{self, QueryNode, QueryNodePropertyKey};
 use crate::cache::CacheStorage;
 use crate::error::{self, AnnimateError};
-use crate::name;
+use crate::sampling::Sampler;
 use crate::util::group_by;
+use crate::{CorpusMatchCount, DocumentMatchCount, ExportSampling, Parallelism};
+use crate::{name, parallel};
 
 /// Configuration of data of a match to be exported.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -49,6 +55,11 @@ impl ExportData {
             ExportData::Value(ExportDataValue::QueryNodeProperty {
                 match_node_index, ..
             }) => vec![*match_node_index],
+            ExportData::Value(ExportDataValue::CoveredText { index, .. }) => vec![*index],
+            ExportData::Value(ExportDataValue::MatchNodePosition { index, .. }) => vec![*index],
+            ExportData::Value(ExportDataValue::TraversalAnno { traversal, .. }) => {
+                traversal.node_indices()
+            }
             ExportData::Text(text) => text.primary_node_indices.clone().unwrap_or_default(),
         }
     }
@@ -77,6 +88,10 @@ pub enum ExportDataValue {
         /// If the query matches multiple nodes, this specifies the index of the node for which to
         /// export the annotation.
         index: usize,
+
+        /// How to combine the values if the annotation is found on multiple nodes overlapping with
+        /// the match node.
+        aggregation: AnnoAggregation,
     },
     /// Annotation of an edge between two of the match nodes.
     EdgeAnno {
@@ -103,6 +118,169 @@ pub enum ExportDataValue {
         /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
         match_node_index: usize,
     },
+    /// Text covered by one of the match nodes.
+    CoveredText {
+        /// Index of the matched node within the match.
+        ///
+        /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
+        index: usize,
+
+        /// Segmentation to use, or [None] to use tokens.
+        segmentation: Option<String>,
+
+        /// Key of the annotation of the segmentation nodes to be exported, or
+        /// [`AnnoKeyOrDefault::Default`] to use segmentation text.
+        anno_key: AnnoKeyOrDefault,
+
+        /// How to format the text.
+        ///
+        /// Only `segment_separator` and `restore_whitespace` apply, since the text is exported
+        /// without gap markers and match brackets.
+        formatting: TextFormatting,
+    },
+    /// Position of one of the match nodes.
+    MatchNodePosition {
+        /// Index of the matched node within the match.
+        ///
+        /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
+        index: usize,
+
+        /// The position to export.
+        position: NodePosition,
+    },
+    /// Annotation of the nodes or edges reached by following edges of a given type from one of
+    /// the match nodes.
+    ///
+    /// If multiple nodes or edges are reached, their values are separated by spaces.
+    TraversalAnno {
+        /// Type (component type, component name) of the edges to follow.
+        edge_type: EdgeType,
+
+        /// Which nodes to reach from which match nodes.
+        traversal: Traversal,
+
+        /// Whether to export annotations of the nodes reached or of the edges followed.
+        item: TraversalItem,
+
+        /// Key of the annotation.
+        anno_key: AnnoKey,
+    },
+}
+
+/// Way of following edges from match nodes to reach other nodes.
+///
+/// Edges are followed in their direction, so for dominance edges, the parent of a node is the node
+/// dominating it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Traversal {
+    /// The source node of an edge ending at the match node.
+    Parent {
+        /// Index of the matched node within the match.
+        ///
+        /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
+        index: usize,
+    },
+    /// The node reached by following edges backwards from the match node `distance` times.
+    ///
+    /// Edges followed are exported from the ancestor towards the match node.
+    Ancestor {
+        /// Index of the matched node within the match.
+        ///
+        /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
+        index: usize,
+
+        /// Number of edges between the ancestor and the match node, where `1` means the parent.
+        distance: usize,
+    },
+    /// The target nodes of all edges starting at the match node, in token order.
+    Children {
+        /// Index of the matched node within the match.
+        ///
+        /// This is numbered in the same way as [`ExportDataValue::MatchNodeAnno::index`].
+        index: usize,
+    },
+    /// The nodes strictly between two match nodes on a shortest path of edges from the source to
+    /// the target node.
+    ///
+    /// Edges followed are exported from the source towards the target node.
+    Path {
+        /// Index of the source node within the match.
+        source_node_index: usize,
+
+        /// Index of the target node within the match.
+        target_node_index: usize,
+    },
+}
+
+impl Traversal {
+    pub(crate) fn node_indices(&self) -> Vec<usize> {
+        match self {
+            Traversal::Parent { index }
+            | Traversal::Ancestor { index, .. }
+            | Traversal::Children { index } => vec![*index],
+            Traversal::Path {
+                source_node_index,
+                target_node_index,
+            } => vec![*source_node_index, *target_node_index],
+        }
+    }
+}
+
+/// Whether to export annotations of the nodes reached or the edges followed in a [`Traversal`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraversalItem {
+    /// Annotations of the nodes reached.
+    Nodes,
+
+    /// Annotations of the edges followed.
+    Edges,
+}
+
+/// How the values of an annotation found on multiple nodes are combined.
+///
+/// An annotation of a match node is looked up on the node itself first. Only if the node doesn't
+/// have the annotation, it is looked up on the nodes overlapping with the tokens covered by the
+/// node, which may yield multiple values.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum AnnoAggregation {
+    /// Only the first value that is found.
+    #[default]
+    First,
+
+    /// All distinct values in the order of their first occurrence.
+    Distinct {
+        /// String to join the values with.
+        separator: String,
+    },
+
+    /// All values in token order, one per node having the annotation.
+    All {
+        /// String to join the values with.
+        separator: String,
+    },
+}
+
+/// Position of a node within its document.
+///
+/// Tokens are numbered from zero in the order given by the default ordering component. If
+/// a document contains multiple texts, the tokens of each text are numbered separately.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NodePosition {
+    /// Index of the node if it is a token, otherwise empty.
+    TokenIndex,
+
+    /// Index of the first token covered by the node.
+    FirstTokenIndex,
+
+    /// Index of the last token covered by the node.
+    LastTokenIndex,
+
+    /// Name of the node (`annis:node_name`).
+    NodeName,
 }
 
 /// Configuration of the text of a match to be exported.
@@ -117,6 +295,15 @@ pub struct ExportDataText {
     /// Size of the right context, in segmentation nodes.
     pub right_context: usize,
 
+    /// Key of an annotation marking the context unit, or [None] to only use `left_context` and
+    /// `right_context`.
+    ///
+    /// If this is set, the context consists of the tokens covered by the nodes with this
+    /// annotation that enclose the match nodes, e.g. the sentence containing the match. For match
+    /// nodes without such an enclosing node, the context is determined by `left_context` and
+    /// `right_context` instead.
+    pub context_unit: Option<AnnoKey>,
+
     /// Key of the annotation of the segmentation nodes to be exported, or
     /// [`AnnoKeyOrDefault::Default`] to use segmentation text.
     pub anno_key: AnnoKeyOrDefault,
@@ -133,6 +320,41 @@ pub struct ExportDataText {
     /// multiple primary query nodes, it is treated as belonging to the first one of them in the
     /// list.
     pub primary_node_indices: Option<Vec<usize>>,
+
+    /// How to format the text in the columns of a table.
+    pub formatting: TextFormatting,
+}
+
+/// Formatting of the text of a match in the columns of a table.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TextFormatting {
+    /// Separator between two adjacent segments.
+    pub segment_separator: String,
+
+    /// Marker for a gap between two parts of the text that are not adjacent.
+    pub gap_marker: String,
+
+    /// Opening and closing brackets around the parts of the text belonging to a match node, or
+    /// [None] to not use brackets.
+    pub match_brackets: Option<(String, String)>,
+
+    /// Whether to restore the whitespace between segments from the whitespace annotations of the
+    /// tokens (`annis::tok-whitespace-after`).
+    ///
+    /// If this is set, `segment_separator` is only used after segments whose last token has no
+    /// whitespace annotation and around gap markers.
+    pub restore_whitespace: bool,
+}
+
+impl Default for TextFormatting {
+    fn default() -> Self {
+        Self {
+            segment_separator: " ".into(),
+            gap_marker: "(...)".into(),
+            match_brackets: None,
+            restore_whitespace: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -141,10 +363,24 @@ pub(crate) struct Match {
     pub(crate) texts: HashMap<ExportDataText, Vec<TextPart>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
 pub(crate) enum TextPart {
-    Match { index: usize, segments: Vec<String> },
-    Context { segments: Vec<String> },
+    Match {
+        index: usize,
+        segments: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        whitespace: Option<Vec<String>>,
+    },
+    Context {
+        segments: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        whitespace: Option<Vec<String>>,
+    },
     Gap,
 }
 
@@ -152,6 +388,49 @@ impl TextPart {
     pub(crate) fn is_match(&self) -> bool {
         matches!(self, TextPart::Match { .. })
     }
+
+    /// Returns the segments of the part together with the whitespace following each of them, if
+    /// it has been restored.
+    pub(crate) fn segments(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        let (segments, whitespace) = match self {
+            TextPart::Match {
+                segments,
+                whitespace,
+                ..
+            }
+            | TextPart::Context {
+                segments,
+                whitespace,
+            } => (segments.as_slice(), whitespace.as_deref()),
+            TextPart::Gap => (&[][..], None),
+        };
+
+        segments.iter().enumerate().map(move |(i, segment)| {
+            (
+                segment.as_str(),
+                whitespace.and_then(|w| w.get(i)).map(String::as_str),
+            )
+        })
+    }
+
+    fn push_segment(&mut self, segment: String, segment_whitespace: Option<String>) {
+        if let TextPart::Match {
+            segments,
+            whitespace,
+            ..
+        }
+        | TextPart::Context {
+            segments,
+            whitespace,
+        } = self
+        {
+            segments.push(segment);
+
+            if let (Some(whitespace), Some(segment_whitespace)) = (whitespace, segment_whitespace) {
+                whitespace.push(segment_whitespace);
+            }
+        }
+    }
 }
 
 pub(crate) struct Query<'a, S> {
@@ -185,9 +464,115 @@ impl<'a, S> Query<'a, S> {
         &self.nodes
     }
 
+    pub(crate) fn count<F, G>(
+        &self,
+        by_document: bool,
+        timeout: Option<Duration>,
+        mut on_corpora_searched: F,
+        cancel_requested: G,
+    ) -> Result<Vec<CorpusMatchCount>, AnnimateError>
+    where
+        F: FnMut(usize),
+        G: Fn() -> bool,
+        S: AsRef<str>,
+    {
+        let corpus_names = self.sorted_corpus_names();
+        let corpus_count = corpus_names.len();
+        let mut corpus_match_counts = Vec::with_capacity(corpus_count);
+
+        for (corpus_index, corpus_name) in corpus_names.into_iter().enumerate() {
+            error::cancel_if(&cancel_requested)?;
+            on_corpora_searched(corpus_index);
+
+            let search_query = SearchQuery {
+                corpus_names: &[corpus_name],
+                query: self.aql_query,
+                query_language: self.query_language,
+                timeout,
+            };
+
+            let corpus_match_count = if by_document {
+                // graphANNIS can only count matches per corpus, so we need to find all matches
+                // in order to know their documents
+                let mut document_match_counts = BTreeMap::new();
+
+                for match_id in self
+                    .corpus_storage
+                    .find(search_query, 0, None, ResultOrder::NotSorted)
+                    .map_err(|err| map_timeout(err, corpus_name))?
+                {
+                    let match_node_names = util::node_names_from_match(&match_id);
+                    let first_match_node_name = match_node_names
+                        .first()
+                        .ok_or(AnnimateError::MatchWithoutNodes)?;
+                    let doc_node_name = name::get_doc_node_name(first_match_node_name);
+
+                    *document_match_counts
+                        .entry(doc_node_name.to_string())
+                        .or_default() += 1;
+                }
+
+                CorpusMatchCount {
+                    corpus_name: corpus_name.into(),
+                    match_count: document_match_counts.values().sum(),
+                    document_count: document_match_counts.len() as u64,
+                    documents: Some(
+                        document_match_counts
+                            .into_iter()
+                            .map(|(document_node_name, match_count)| DocumentMatchCount {
+                                document_node_name,
+                                match_count,
+                            })
+                            .collect(),
+                    ),
+                }
+            } else {
+                let count_extra = self
+                    .corpus_storage
+                    .count_extra(search_query)
+                    .map_err(|err| map_timeout(err, corpus_name))?;
+
+                CorpusMatchCount {
+                    corpus_name: corpus_name.into(),
+                    match_count: count_extra.match_count,
+                    document_count: count_extra.document_count,
+                    documents: None,
+                }
+            };
+
+            corpus_match_counts.push(corpus_match_count);
+        }
+
+        error::cancel_if(&cancel_requested)?;
+        on_corpora_searched(corpus_count);
+
+        Ok(corpus_match_counts)
+    }
+
+    /// Returns the names of the corpora to search, in the order in which they are searched.
+    ///
+    /// We iterate over the corpus names ourselves instead of calling `CorpusStorage::find` with
+    /// the list of all corpus names, so we know which corpus each match belongs to. Otherwise
+    /// (and this is what ANNIS itself does) we would have to rely on the corpus name being equal
+    /// (up to URL encoding) to the first part of each match node name. While this is usually the
+    /// case, not relying on this assumption enables us to import a corpus under a different name
+    /// than its intrinsic name, which can happen, for instance, when a .graphml file is renamed.
+    fn sorted_corpus_names(&self) -> Vec<&str>
+    where
+        S: AsRef<str>,
+    {
+        let mut corpus_names: Vec<_> = self.corpus_names.iter().map(|s| s.as_ref()).collect();
+        // Sort in the same order as graphANNIS does when `ResultOrder::Normal` is used
+        corpus_names.sort();
+        corpus_names
+    }
+
     pub(crate) fn find<F, G, I>(
         &self,
         export_data: I,
+        sampling: ExportSampling,
+        timeout: Option<Duration>,
+        parallelism: Parallelism,
         mut on_corpora_searched: F,
         cancel_requested: G,
     ) -> Result<impl ExactSizeIterator<Item = Result<Match, AnnimateError>>, AnnimateError>
@@ -195,7 +580,7 @@ impl<'a, S> Query<'a, S> {
         F: FnMut(usize),
         G: Fn() -> bool,
         I: IntoIterator<Item = ExportData>,
-        S: AsRef<str>,
+        S: AsRef<str> + Sync,
     {
         let export_data: HashSet<_> = export_data.into_iter().collect();
 
@@ -214,178 +599,292 @@ impl<'a, S> Query<'a, S> {
         let mut segment_anno_keys = HashMap::new();
 
         for data in &export_data {
-            if let ExportData::Text(text) = data
-                && !segment_anno_keys.contains_key(&text.segmentation)
-            {
+            let segmentation = match data {
+                ExportData::Value(ExportDataValue::CoveredText { segmentation, .. }) => {
+                    segmentation
+                }
+                ExportData::Text(text) => &text.segmentation,
+                ExportData::Value(_) => continue,
+            };
+
+            if !segment_anno_keys.contains_key(segmentation) {
                 segment_anno_keys.insert(
-                    text.segmentation.clone(),
+                    segmentation.clone(),
                     get_anno_key_for_segmentation(
                         self.corpus_storage,
                         self.cache_storage,
                         self.corpus_names,
-                        text.segmentation.as_deref(),
+                        segmentation.as_deref(),
                     )?,
                 );
             }
         }
 
-        // We iterate over the corpus names ourselves instead of calling `CorpusStorage::find` with
-        // the list of all corpus names, so we know which corpus each match belongs to. Otherwise
-        // (and this is what ANNIS itself does) we would have to rely on the corpus name being equal
-        // (up to URL encoding) to the first part of each match node name. While this is usually the
-        // case, not relying on this assumption enables us to import a corpus under a different name
-        // than its intrinsic name, which can happen, for instance, when a .graphml file is renamed.
-        let corpus_names = {
-            let mut corpus_names: Vec<_> = self.corpus_names.iter().map(|s| s.as_ref()).collect();
-            // Sort in the same order as graphANNIS does when `ResultOrder::Normal` is used
-            corpus_names.sort();
-            corpus_names
-        };
+        let corpus_names = self.sorted_corpus_names();
 
         let corpus_count = corpus_names.len();
-        let mut match_extras = Vec::new();
 
-        for (corpus_index, corpus_name) in corpus_names.into_iter().enumerate() {
-            error::cancel_if(&cancel_requested)?;
-            on_corpora_searched(corpus_index);
+        // Without a random sample, only as many matches are searched as can end up being exported,
+        // so that huge corpora are not searched completely for a few matches
+        let mut sampler = Sampler::new(sampling);
+        let total_search_limit = sampler.total_search_limit();
+
+        let find_extra = |corpus_name: &&str, limit| {
+            let corpus_match_extras = self.corpus_storage.find_extra(
+                SearchQuery {
+                    corpus_names: &[corpus_name],
+                    query: self.aql_query,
+                    query_language: self.query_language,
+                    timeout,
+                },
+                0,
+                limit,
+                ResultOrder::Normal,
+            );
+
+            corpus_match_extras.map_err(|err| map_timeout(err, corpus_name))
+        };
+
+        let match_extras_by_corpus = match parallelism {
+            Parallelism::Sequential => {
+                // Matches found in previous corpora reduce the number still needed in total
+                let mut remaining = total_search_limit;
+
+                corpus_names
+                    .iter()
+                    .enumerate()
+                    .map(|(corpus_index, corpus_name)| {
+                        error::cancel_if(&cancel_requested)?;
+                        on_corpora_searched(corpus_index);
+
+                        sampler
+                            .search_corpus(&mut remaining, |limit| find_extra(corpus_name, limit))
+                    })
+                    .try_collect()?
+            }
+            Parallelism::Parallel => {
+                let limit = sampler.search_limit(total_search_limit);
+
+                parallel::try_map_with_progress(
+                    &corpus_names,
+                    |corpus_name| find_extra(corpus_name, limit),
+                    &mut on_corpora_searched,
+                    &cancel_requested,
+                )?
+            }
+        };
 
+        // Sampling happens in corpus order regardless of parallelism, so that random samples are
+        // reproducible
+        let mut match_extras = Vec::new();
+
+        for (corpus_name, corpus_match_extras) in
+            corpus_names.into_iter().zip(match_extras_by_corpus)
+        {
             match_extras.extend(
-                self.corpus_storage
-                    .find_extra(
-                        SearchQuery {
-                            corpus_names: &[corpus_name],
-                            query: self.aql_query,
-                            query_language: self.query_language,
-                            timeout: None,
-                        },
-                        0,
-                        None,
-                        ResultOrder::Normal,
-                    )?
+                sampler
+                    .select_per_corpus(corpus_match_extras)
                     .into_iter()
                     .map(|match_extra| (match_extra, corpus_name)),
             );
         }
 
+        // Sampling happens before any data is collected for the matches below, since that is the
+        // expensive part of an export
+        let match_extras = sampler.select_total(match_extras);
+
         error::cancel_if(&cancel_requested)?;
         on_corpora_searched(corpus_count);
 
-        // This loop could be parallelized, but that leads to a massive slowdown on Windows
-        // due to high contention on the `RwLock` of the corpus cache used within graphANNIS.
-        // On Linux there's a significant speedup, though.
-        Ok(match_extras
-            .into_iter()
-            .map(move |(match_extra, corpus_name)| {
-                let match_node_names = util::node_names_from_match(&match_extra.match_id);
-                let first_match_node_name = match_node_names
-                    .first()
-                    .ok_or(AnnimateError::MatchWithoutNodes)?;
-
-                let corpus_node_name = name::get_corpus_node_name(first_match_node_name)?;
-                let doc_node_name = name::get_doc_node_name(first_match_node_name);
-
-                let mut values = HashMap::new();
-                let mut texts = HashMap::new();
-
-                for d in &export_data {
-                    match d {
-                        ExportData::Value(value) => match value {
-                            ExportDataValue::CorpusAnno { anno_key } => {
-                                if let Some(anno) = get_corpus_or_doc_anno(
+        let documents = DocumentCache::new(match parallelism {
+            Parallelism::Sequential => 1,
+            Parallelism::Parallel => parallel::thread_count(),
+        });
+
+        let get_match = move |(match_extra, corpus_name): (MatchExtra, &str)| {
+            let match_node_names = util::node_names_from_match(&match_extra.match_id);
+            let first_match_node_name = match_node_names
+                .first()
+                .ok_or(AnnimateError::MatchWithoutNodes)?;
+
+            let corpus_node_name = name::get_corpus_node_name(first_match_node_name)?;
+            let doc_node_name = name::get_doc_node_name(first_match_node_name);
+
+            let mut values = HashMap::new();
+            let mut texts = HashMap::new();
+
+            for d in &export_data {
+                match d {
+                    ExportData::Value(value) => match value {
+                        ExportDataValue::CorpusAnno { anno_key } => {
+                            if let Some(anno) = get_corpus_or_doc_anno(
+                                self.corpus_storage,
+                                corpus_name,
+                                &corpus_node_name,
+                                anno_key,
+                            )? {
+                                values.insert(value.clone(), anno);
+                            }
+                        }
+                        ExportDataValue::DocumentAnno { anno_key } => {
+                            if let Some(anno) = get_corpus_or_doc_anno(
+                                self.corpus_storage,
+                                corpus_name,
+                                doc_node_name,
+                                anno_key,
+                            )? {
+                                values.insert(value.clone(), anno);
+                            }
+                        }
+                        ExportDataValue::MatchNodeAnno {
+                            anno_key,
+                            index,
+                            aggregation,
+                        } => {
+                            if let Some(node_name) = match_node_names.get(*index)
+                                && let Some(anno) = get_anno_with_overlapping_coverage(
                                     self.corpus_storage,
                                     corpus_name,
-                                    &corpus_node_name,
+                                    node_name,
                                     anno_key,
-                                )? {
-                                    values.insert(value.clone(), anno);
-                                }
+                                    aggregation,
+                                )?
+                            {
+                                values.insert(value.clone(), anno);
                             }
-                            ExportDataValue::DocumentAnno { anno_key } => {
-                                if let Some(anno) = get_corpus_or_doc_anno(
+                        }
+                        ExportDataValue::EdgeAnno {
+                            edge_type,
+                            anno_key,
+                            source_node_index,
+                            target_node_index,
+                        } => {
+                            if let Some(source_node_name) = match_node_names.get(*source_node_index)
+                                && let Some(target_node_name) =
+                                    match_node_names.get(*target_node_index)
+                                && let Some(anno) = get_edge_anno(
                                     self.corpus_storage,
                                     corpus_name,
-                                    doc_node_name,
+                                    source_node_name,
+                                    target_node_name,
+                                    edge_type,
                                     anno_key,
-                                )? {
-                                    values.insert(value.clone(), anno);
-                                }
+                                )?
+                            {
+                                values.insert(value.clone(), anno);
                             }
-                            ExportDataValue::MatchNodeAnno { anno_key, index } => {
-                                if let Some(node_name) = match_node_names.get(*index)
-                                    && let Some(anno) = get_anno_with_overlapping_coverage(
-                                        self.corpus_storage,
-                                        corpus_name,
-                                        node_name,
-                                        anno_key,
-                                    )?
-                                {
-                                    values.insert(value.clone(), anno);
-                                }
-                            }
-                            ExportDataValue::EdgeAnno {
-                                edge_type,
-                                anno_key,
-                                source_node_index,
-                                target_node_index,
-                            } => {
-                                if let Some(source_node_name) =
-                                    match_node_names.get(*source_node_index)
-                                    && let Some(target_node_name) =
-                                        match_node_names.get(*target_node_index)
-                                    && let Some(anno) = get_edge_anno(
-                                        self.corpus_storage,
-                                        corpus_name,
-                                        source_node_name,
-                                        target_node_name,
-                                        edge_type,
-                                        anno_key,
-                                    )?
-                                {
-                                    values.insert(value.clone(), anno);
-                                }
+                        }
+                        ExportDataValue::QueryNodeProperty {
+                            query_node_property_key,
+                            match_node_index,
+                        } => {
+                            if let Some(query_node) = self
+                                .nodes
+                                .get(*match_node_index)
+                                .expect("query node index should be valid")
+                                .iter()
+                                .find(|node| node.alternative == match_extra.alternative)
+                            {
+                                values.insert(
+                                    value.clone(),
+                                    match query_node_property_key {
+                                        QueryNodePropertyKey::Fragment => {
+                                            query_node.query_fragment.clone()
+                                        }
+                                        QueryNodePropertyKey::Variable => {
+                                            query_node.variable.clone()
+                                        }
+                                    },
+                                );
                             }
-                            ExportDataValue::QueryNodeProperty {
-                                query_node_property_key,
-                                match_node_index,
-                            } => {
-                                if let Some(query_node) = self
-                                    .nodes
-                                    .get(*match_node_index)
-                                    .expect("query node index should be valid")
-                                    .iter()
-                                    .find(|node| node.alternative == match_extra.alternative)
-                                {
-                                    values.insert(
-                                        value.clone(),
-                                        match query_node_property_key {
-                                            QueryNodePropertyKey::Fragment => {
-                                                query_node.query_fragment.clone()
-                                            }
-                                            QueryNodePropertyKey::Variable => {
-                                                query_node.variable.clone()
-                                            }
-                                        },
-                                    );
-                                }
+                        }
+                        ExportDataValue::CoveredText {
+                            index,
+                            segmentation,
+                            anno_key,
+                            formatting,
+                        } => {
+                            if let Some(node_name) = match_node_names.get(*index)
+                                && let Some(text) = get_covered_text(
+                                    self.corpus_storage,
+                                    corpus_name,
+                                    node_name,
+                                    segment_anno_keys.get(segmentation).unwrap(),
+                                    anno_key,
+                                    formatting,
+                                )?
+                            {
+                                values.insert(value.clone(), text);
                             }
-                        },
-                        ExportData::Text(text) => {
-                            texts.insert(
-                                text.clone(),
-                                get_parts(
+                        }
+                        ExportDataValue::MatchNodePosition { index, position } => {
+                            if let Some(node_name) = match_node_names.get(*index)
+                                && let Some(position) = get_node_position(
                                     self.corpus_storage,
                                     corpus_name,
-                                    match_node_names.clone(),
-                                    text,
-                                    segment_anno_keys.get(&text.segmentation).unwrap(),
-                                )?,
-                            );
+                                    node_name,
+                                    *position,
+                                    &documents,
+                                )?
+                            {
+                                values.insert(value.clone(), position);
+                            }
                         }
+                        ExportDataValue::TraversalAnno {
+                            edge_type,
+                            traversal,
+                            item,
+                            anno_key,
+                        } => {
+                            if let Some(anno) = get_traversal_anno(
+                                self.corpus_storage,
+                                corpus_name,
+                                &match_node_names,
+                                edge_type,
+                                traversal,
+                                *item,
+                                anno_key,
+                                &documents,
+                            )? {
+                                values.insert(value.clone(), anno);
+                            }
+                        }
+                    },
+                    ExportData::Text(text) => {
+                        texts.insert(
+                            text.clone(),
+                            get_parts(
+                                self.corpus_storage,
+                                corpus_name,
+                                match_node_names.clone(),
+                                text,
+                                segment_anno_keys.get(&text.segmentation).unwrap(),
+                            )?,
+                        );
                     }
                 }
+            }
+
+            Ok(Match { values, texts })
+        };
 
-                Ok(Match { values, texts })
-            }))
+        // Parallelizing this leads to a massive slowdown on Windows due to high contention on the
+        // `RwLock` of the corpus cache used within graphANNIS, see `Parallelism::Sequential`
+        Ok(match parallelism {
+            Parallelism::Sequential => Either::Left(match_extras.into_iter().map(get_match)),
+            Parallelism::Parallel => {
+                Either::Right(parallel::ParMapChunks::new(match_extras, get_match))
+            }
+        })
+    }
+}
+
+fn map_timeout(err: GraphAnnisError, corpus_name: &str) -> AnnimateError {
+    match err {
+        GraphAnnisError::Timeout => AnnimateError::QueryTimedOut {
+            corpus_name: corpus_name.into(),
+        },
+        err => err.into(),
     }
 }
 
@@ -407,11 +906,14 @@ fn get_corpus_or_doc_anno(
     anno::get_anno(&graph, node_id, anno_key)
 }
 
+/// Returns the annotation of a node, or else the annotations of the nodes overlapping with the
+/// tokens covered by the node, combined according to `aggregation`.
 fn get_anno_with_overlapping_coverage(
     corpus_storage: &CorpusStorage,
     corpus_name: &str,
     node_name: &str,
     anno_key: &AnnoKey,
+    aggregation: &AnnoAggregation,
 ) -> Result<Option<String>, GraphAnnisError> {
     let graph = corpus_storage.subgraph(corpus_name, vec![node_name.into()], 0, 0, None)?;
     let node_id = name::node_name_to_node_id(&graph, node_name)?;
@@ -426,15 +928,271 @@ fn get_anno_with_overlapping_coverage(
         return Ok(None);
     };
 
+    let mut seen_node_ids = HashSet::new();
+    let mut values = Vec::new();
+
     for token_id in graph_helper.get_connected_node_ids_in_order(covered_token_id) {
-        for node_id in graph_helper.get_covering_node_ids(token_id?) {
-            if let Some(anno) = anno::get_anno(&graph, node_id?, anno_key)? {
-                return Ok(Some(anno));
+        let token_id = token_id?;
+
+        if !graph_helper.is_covering_node_id(node_id, token_id)? {
+            continue;
+        }
+
+        for covering_node_id in graph_helper.get_covering_node_ids(token_id) {
+            let covering_node_id = covering_node_id?;
+
+            // A node covering multiple tokens only contributes its value once
+            if !seen_node_ids.insert(covering_node_id) {
+                continue;
+            }
+
+            if let Some(anno) = anno::get_anno(&graph, covering_node_id, anno_key)? {
+                match aggregation {
+                    AnnoAggregation::First => return Ok(Some(anno)),
+                    AnnoAggregation::Distinct { .. } if values.contains(&anno) => {}
+                    AnnoAggregation::Distinct { .. } | AnnoAggregation::All { .. } => {
+                        values.push(anno);
+                    }
+                }
             }
         }
     }
 
-    Ok(None)
+    Ok(match aggregation {
+        AnnoAggregation::First => None,
+        AnnoAggregation::Distinct { separator } | AnnoAggregation::All { separator } => {
+            (!values.is_empty()).then(|| values.join(separator))
+        }
+    })
+}
+
+/// Returns the segments covered by a node, separated by spaces.
+///
+/// Each segment is given by the value of `anno_key` of a segmentation node covering some of the
+/// tokens covered by the node.
+fn get_covered_text(
+    corpus_storage: &CorpusStorage,
+    corpus_name: &str,
+    node_name: &str,
+    segment_anno_key: &AnnoKey,
+    anno_key: &AnnoKeyOrDefault,
+    formatting: &TextFormatting,
+) -> Result<Option<String>, GraphAnnisError> {
+    let graph = corpus_storage.subgraph(corpus_name, vec![node_name.into()], 0, 0, None)?;
+    let node_id = name::node_name_to_node_id(&graph, node_name)?;
+    let graph_helper = GraphHelper::new(&graph);
+    let node_annos = graph.get_node_annos();
+
+    let Some(covered_token_id) = graph_helper.get_covered_token_id(node_id)? else {
+        return Ok(None);
+    };
+
+    let mut token_ids = Vec::new();
+
+    for token_id in graph_helper.get_connected_node_ids_in_order(covered_token_id) {
+        let token_id = token_id?;
+
+        if graph_helper.is_covering_node_id(node_id, token_id)? {
+            token_ids.push(token_id);
+        }
+    }
+
+    let mut text = String::new();
+    let mut pending_separator = None;
+
+    let get_segment_node_id =
+        |token_id: &NodeID| graph_helper.get_segment_node_id(*token_id, segment_anno_key);
+
+    for group in group_by(&token_ids, get_segment_node_id) {
+        let (segment_node_id, token_ids) = group?;
+
+        if let Some(segment) = node_annos.get_value_for_item(
+            &segment_node_id,
+            match anno_key {
+                AnnoKeyOrDefault::AnnoKey(anno_key) => anno_key,
+                AnnoKeyOrDefault::Default => segment_anno_key,
+            },
+        )? {
+            text.extend(pending_separator.take());
+            text.push_str(&segment);
+
+            let whitespace = if formatting.restore_whitespace {
+                let last_token_id = token_ids.last().expect("group should not be empty");
+                node_annos.get_value_for_item(last_token_id, &TOK_WHITESPACE_AFTER_ANNO_KEY)?
+            } else {
+                None
+            };
+
+            pending_separator =
+                Some(whitespace.unwrap_or_else(|| formatting.segment_separator.as_str().into()));
+        }
+    }
+
+    Ok(Some(text))
+}
+
+fn get_node_position(
+    corpus_storage: &CorpusStorage,
+    corpus_name: &str,
+    node_name: &str,
+    position: NodePosition,
+    documents: &DocumentCache,
+) -> Result<Option<String>, GraphAnnisError> {
+    if position == NodePosition::NodeName {
+        return Ok(Some(node_name.into()));
+    }
+
+    let graph = corpus_storage.subgraph(corpus_name, vec![node_name.into()], 0, 0, None)?;
+    let node_id = name::node_name_to_node_id(&graph, node_name)?;
+    let graph_helper = GraphHelper::new(&graph);
+
+    let token_id = match position {
+        NodePosition::TokenIndex => graph_helper.is_token(node_id)?.then_some(node_id),
+        NodePosition::FirstTokenIndex | NodePosition::LastTokenIndex => {
+            let Some(covered_token_id) = graph_helper.get_covered_token_id(node_id)? else {
+                return Ok(None);
+            };
+
+            let mut covered_token_ids = graph_helper
+                .get_connected_node_ids_in_order(covered_token_id)
+                .filter_map(|token_id| {
+                    token_id
+                        .and_then(|token_id| {
+                            graph_helper
+                                .is_covering_node_id(node_id, token_id)
+                                .map(|is_covering| is_covering.then_some(token_id))
+                        })
+                        .transpose()
+                });
+
+            if position == NodePosition::FirstTokenIndex {
+                covered_token_ids.next().transpose()?
+            } else {
+                covered_token_ids.last().transpose()?
+            }
+        }
+        NodePosition::NodeName => unreachable!("node name should have been handled above"),
+    };
+
+    let Some(token_id) = token_id else {
+        return Ok(None);
+    };
+
+    let Some(token_name) = graph
+        .get_node_annos()
+        .get_value_for_item(&token_id, &NODE_NAME_KEY)?
+    else {
+        return Ok(None);
+    };
+
+    let doc_node_name = name::get_doc_node_name(node_name);
+    let token_index = documents
+        .get(corpus_storage, corpus_name, doc_node_name)?
+        .token_indices
+        .get(&*token_name)
+        .map(|index| index.to_string());
+
+    Ok(token_index)
+}
+
+/// Cache of the most recently used documents.
+///
+/// Some values, such as token indices or annotations of nodes reached by following edges,
+/// require loading a whole document, so it is cached. Since matches are sorted by document,
+/// keeping one document per thread processing matches suffices.
+struct DocumentCache {
+    capacity: usize,
+    /// Cached documents by document node name, the most recently used one last.
+    documents: Mutex<VecDeque<(String, Arc<Document>)>>,
+}
+
+/// A whole document, together with the indices of its tokens.
+struct Document {
+    graph: Graph<AnnotationComponentType>,
+    token_indices: TokenIndices,
+}
+
+/// Indices of the tokens of a document, by token node name.
+type TokenIndices = HashMap<String, usize>;
+
+impl DocumentCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            documents: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn get(
+        &self,
+        corpus_storage: &CorpusStorage,
+        corpus_name: &str,
+        doc_node_name: &str,
+    ) -> Result<Arc<Document>, GraphAnnisError> {
+        {
+            let mut documents = self.documents.lock().unwrap();
+
+            if let Some(position) = documents
+                .iter()
+                .position(|(cached_doc_node_name, _)| cached_doc_node_name == doc_node_name)
+            {
+                let entry = documents.remove(position).unwrap();
+                let document = Arc::clone(&entry.1);
+                documents.push_back(entry);
+                return Ok(document);
+            }
+        }
+
+        // The lock isn't held while loading, so other threads can use their cached documents
+        let graph = corpus_storage.subcorpus_graph(corpus_name, vec![doc_node_name.into()])?;
+        let token_indices = get_token_indices(&graph)?;
+        let document = Arc::new(Document {
+            graph,
+            token_indices,
+        });
+
+        let mut documents = self.documents.lock().unwrap();
+        if documents.len() >= self.capacity {
+            documents.pop_front();
+        }
+        documents.push_back((doc_node_name.into(), Arc::clone(&document)));
+
+        Ok(document)
+    }
+}
+
+fn get_token_indices(
+    graph: &Graph<AnnotationComponentType>,
+) -> Result<TokenIndices, GraphAnnisError> {
+    let node_annos = graph.get_node_annos();
+
+    let mut token_indices = HashMap::new();
+
+    let Some(order_storage) = graph.get_graphstorage_as_ref(&DEFAULT_ORDERING_COMPONENT) else {
+        return Ok(token_indices);
+    };
+
+    for first_token_id in order_storage.source_nodes() {
+        let first_token_id = first_token_id?;
+
+        if order_storage.has_ingoing_edges(first_token_id)? {
+            continue;
+        }
+
+        let token_ids = iter::once(Ok(first_token_id)).chain(order_storage.find_connected(
+            first_token_id,
+            1,
+            Bound::Unbounded,
+        ));
+
+        for (index, token_id) in token_ids.enumerate() {
+            if let Some(token_name) = node_annos.get_value_for_item(&token_id?, &NODE_NAME_KEY)? {
+                token_indices.insert(token_name.into_owned(), index);
+            }
+        }
+    }
+
+    Ok(token_indices)
 }
 
 fn get_edge_anno(
@@ -469,6 +1227,199 @@ fn get_edge_anno(
     Ok(None)
 }
 
+#[allow(clippy::too_many_arguments)]
+fn get_traversal_anno(
+    corpus_storage: &CorpusStorage,
+    corpus_name: &str,
+    match_node_names: &[String],
+    edge_type: &EdgeType,
+    traversal: &Traversal,
+    item: TraversalItem,
+    anno_key: &AnnoKey,
+    documents: &DocumentCache,
+) -> Result<Option<String>, GraphAnnisError> {
+    let get_node_name = |index: &usize| match_node_names.get(*index);
+
+    let Some(start_node_name) = traversal.node_indices().first().and_then(get_node_name) else {
+        return Ok(None);
+    };
+
+    // Pointing edges may lead to nodes far away from the match, so the whole document is needed
+    let document = documents.get(
+        corpus_storage,
+        corpus_name,
+        name::get_doc_node_name(start_node_name),
+    )?;
+    let graph = &document.graph;
+
+    let start_node_id = name::node_name_to_node_id(graph, start_node_name)?;
+    let target_node_id = match traversal {
+        Traversal::Path {
+            target_node_index, ..
+        } => match get_node_name(target_node_index) {
+            Some(target_node_name) => Some(name::node_name_to_node_id(graph, target_node_name)?),
+            None => return Ok(None),
+        },
+        _ => None,
+    };
+
+    // Components of the same type and name may exist in multiple layers, so they are traversed
+    // separately, without mixing edges of different layers
+    for component in graph.get_all_components(Some(edge_type.ctype.into()), Some(&edge_type.name)) {
+        let Some(storage) = graph.get_graphstorage_as_ref(&component) else {
+            continue;
+        };
+
+        let Some((edges, node_ids)) =
+            traverse(&document, storage, start_node_id, target_node_id, traversal)?
+        else {
+            continue;
+        };
+
+        let mut values = Vec::new();
+
+        match item {
+            TraversalItem::Nodes => {
+                for node_id in node_ids {
+                    if let Some(anno) = anno::get_anno(graph, node_id, anno_key)? {
+                        values.push(anno);
+                    }
+                }
+            }
+            TraversalItem::Edges => {
+                for edge in edges {
+                    if let Some(anno) = storage
+                        .get_anno_storage()
+                        .get_value_for_item(&edge, anno_key)?
+                    {
+                        values.push(anno.into());
+                    }
+                }
+            }
+        }
+
+        if !values.is_empty() {
+            return Ok(Some(values.join(" ")));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Edges followed in a [`Traversal`] within a single component, together with the nodes reached.
+type TraversalResult = (Vec<Edge>, Vec<NodeID>);
+
+fn traverse(
+    document: &Document,
+    storage: &dyn GraphStorage,
+    start_node_id: NodeID,
+    target_node_id: Option<NodeID>,
+    traversal: &Traversal,
+) -> Result<Option<TraversalResult>, GraphAnnisError> {
+    let get_parent_edge = |node_id: NodeID| -> Result<Option<Edge>, GraphAnnisError> {
+        match storage.get_ingoing_edges(node_id).next() {
+            Some(parent_node_id) => Ok(Some((parent_node_id?, node_id).into())),
+            None => Ok(None),
+        }
+    };
+
+    let get_child_edges = |node_id: NodeID| -> Result<Vec<Edge>, GraphAnnisError> {
+        storage
+            .get_outgoing_edges(node_id)
+            .map(|child_node_id| Ok((node_id, child_node_id?).into()))
+            .collect()
+    };
+
+    Ok(Some(match traversal {
+        Traversal::Parent { .. } => match get_parent_edge(start_node_id)? {
+            Some(edge) => (vec![edge.clone()], vec![edge.source]),
+            None => return Ok(None),
+        },
+        Traversal::Ancestor { distance, .. } => {
+            let mut edges = Vec::new();
+            let mut node_id = start_node_id;
+
+            for _ in 0..*distance {
+                let Some(edge) = get_parent_edge(node_id)? else {
+                    return Ok(None);
+                };
+
+                node_id = edge.source;
+                edges.push(edge);
+            }
+
+            edges.reverse();
+            (edges, vec![node_id])
+        }
+        Traversal::Children { .. } => {
+            let graph_helper = GraphHelper::new(&document.graph);
+            let node_annos = document.graph.get_node_annos();
+
+            let mut edges_with_token_index = Vec::new();
+
+            for edge in get_child_edges(start_node_id)? {
+                let token_index = match graph_helper.get_covered_token_id(edge.target)? {
+                    Some(token_id) => node_annos
+                        .get_value_for_item(&token_id, &NODE_NAME_KEY)?
+                        .and_then(|token_name| document.token_indices.get(&*token_name).copied()),
+                    None => None,
+                };
+
+                edges_with_token_index.push((token_index, edge));
+            }
+
+            edges_with_token_index.sort_by_key(|(token_index, _)| *token_index);
+
+            let edges: Vec<_> = edges_with_token_index
+                .into_iter()
+                .map(|(_, edge)| edge)
+                .collect();
+            let node_ids = edges.iter().map(|edge| edge.target).collect();
+
+            (edges, node_ids)
+        }
+        Traversal::Path { .. } => {
+            let Some(target_node_id) = target_node_id else {
+                return Ok(None);
+            };
+
+            // Breadth-first search, remembering the edge by which each node was reached first
+            let mut parent_edges = HashMap::new();
+            let mut queue = VecDeque::from([start_node_id]);
+
+            while let Some(node_id) = queue.pop_front() {
+                if node_id == target_node_id {
+                    break;
+                }
+
+                for edge in get_child_edges(node_id)? {
+                    if edge.target != start_node_id && !parent_edges.contains_key(&edge.target) {
+                        queue.push_back(edge.target);
+                        parent_edges.insert(edge.target, edge);
+                    }
+                }
+            }
+
+            let mut edges = Vec::new();
+            let mut node_id = target_node_id;
+
+            while node_id != start_node_id {
+                let Some(edge) = parent_edges.remove(&node_id) else {
+                    return Ok(None);
+                };
+
+                node_id = edge.source;
+                edges.push(edge);
+            }
+
+            edges.reverse();
+            let node_ids = edges.iter().skip(1).map(|edge| edge.source).collect();
+
+            (edges, node_ids)
+        }
+    }))
+}
+
 fn get_parts(
     corpus_storage: &CorpusStorage,
     corpus_name: &str,
@@ -486,8 +1437,10 @@ fn get_parts(
         segmentation,
         left_context,
         right_context,
+        context_unit,
         anno_key,
         primary_node_indices,
+        formatting,
     } = export_data;
 
     let primary_node_indices: Vec<_> = {
@@ -522,13 +1475,64 @@ fn get_parts(
         _ => None,
     };
 
-    let subgraph = corpus_storage.subgraph(
-        corpus_name,
-        match_node_names.clone(),
-        *left_context,
-        *right_context,
-        segmentation,
-    )?;
+    let enclosing_node_names = match context_unit {
+        Some(context_unit) => {
+            get_enclosing_node_names(corpus_storage, corpus_name, &match_node_names, context_unit)?
+        }
+        None => vec![None; match_node_names.len()],
+    };
+
+    // The subgraph of a node includes all tokens covered by the node, so the tokens of the
+    // enclosing nodes make up the context without any additional context. Match nodes without an
+    // enclosing node fall back to the numeric context, whose tokens are added in the same way.
+    let subgraph = if enclosing_node_names.iter().all(Option::is_none) {
+        corpus_storage.subgraph(
+            corpus_name,
+            match_node_names.clone(),
+            *left_context,
+            *right_context,
+            segmentation,
+        )?
+    } else {
+        let mut context_node_names = Vec::new();
+        let mut fallback_node_names = Vec::new();
+
+        for (match_node_name, enclosing_node_name) in
+            match_node_names.iter().zip(enclosing_node_names)
+        {
+            match enclosing_node_name {
+                Some(enclosing_node_name) => {
+                    if !context_node_names.contains(&enclosing_node_name) {
+                        context_node_names.push(enclosing_node_name);
+                    }
+                }
+                None => fallback_node_names.push(match_node_name.clone()),
+            }
+        }
+
+        if !fallback_node_names.is_empty() {
+            context_node_names.extend(get_context_token_names(
+                corpus_storage,
+                corpus_name,
+                fallback_node_names,
+                *left_context,
+                *right_context,
+                segmentation.clone(),
+            )?);
+        }
+
+        corpus_storage.subgraph(
+            corpus_name,
+            match_node_names
+                .iter()
+                .cloned()
+                .chain(context_node_names)
+                .collect(),
+            0,
+            0,
+            segmentation,
+        )?
+    };
 
     let graph_helper = GraphHelper::new(&subgraph);
     let gap_storage = subgraph.get_graphstorage_as_ref(&GAP_ORDERING_COMPONENT);
@@ -595,20 +1599,8 @@ fn get_parts(
             parts.push(TextPart::Gap);
         }
 
-        let get_segment_node_id = |token_id: &NodeID| {
-            graph_helper
-                .get_covering_node_ids(*token_id)
-                .find_map(|node_id| {
-                    node_id
-                        .and_then(|node_id| {
-                            node_annos
-                                .has_value_for_item(&node_id, segment_anno_key)
-                                .map(|has_segment| has_segment.then_some(node_id))
-                        })
-                        .transpose()
-                })
-                .transpose()
-        };
+        let get_segment_node_id =
+            |token_id: &NodeID| graph_helper.get_segment_node_id(*token_id, segment_anno_key);
 
         for group in group_by(&chain.token_ids, get_segment_node_id) {
             let (segment_node_id, token_ids) = group?;
@@ -634,54 +1626,52 @@ fn get_parts(
                 })
                 .transpose()?;
 
-            current_part = Some(match (current_part.take(), match_node_index) {
-                (
-                    Some(TextPart::Match {
+            let continues_current_part = match (&current_part, match_node_index) {
+                (Some(TextPart::Match { index, .. }), Some(match_node_index)) => {
+                    *index == match_node_index
+                }
+                (Some(TextPart::Context { .. }), None) => true,
+                _ => false,
+            };
+
+            if !continues_current_part {
+                parts.extend(current_part.take());
+            }
+
+            let part = current_part.get_or_insert_with(|| {
+                let whitespace = formatting.restore_whitespace.then(Vec::new);
+
+                match match_node_index {
+                    Some(index) => TextPart::Match {
                         index,
-                        mut segments,
-                    }),
-                    Some(match_node_index),
-                ) if index == match_node_index => TextPart::Match {
-                    index,
-                    segments: {
-                        segments.extend(segment);
-                        segments
+                        segments: Vec::new(),
+                        whitespace,
                     },
-                },
-                (Some(TextPart::Match { index, segments }), Some(match_node_index)) => {
-                    parts.push(TextPart::Match { index, segments });
-                    TextPart::Match {
-                        index: match_node_index,
-                        segments: segment.into_iter().collect(),
-                    }
-                }
-                (Some(TextPart::Match { index, segments }), None) => {
-                    parts.push(TextPart::Match { index, segments });
-                    TextPart::Context {
-                        segments: segment.into_iter().collect(),
-                    }
-                }
-                (Some(TextPart::Context { segments }), Some(match_node_index)) => {
-                    parts.push(TextPart::Context { segments });
-                    TextPart::Match {
-                        index: match_node_index,
-                        segments: segment.into_iter().collect(),
-                    }
-                }
-                (Some(TextPart::Context { mut segments }), None) => TextPart::Context {
-                    segments: {
-                        segments.extend(segment);
-                        segments
+                    None => TextPart::Context {
+                        segments: Vec::new(),
+                        whitespace,
                     },
-                },
-                (_, Some(index)) => TextPart::Match {
-                    index,
-                    segments: segment.into_iter().collect(),
-                },
-                (_, None) => TextPart::Context {
-                    segments: segment.into_iter().collect(),
-                },
+                }
             });
+
+            if let Some(segment) = segment {
+                let segment_whitespace = if formatting.restore_whitespace {
+                    let last_token_id = token_ids.last().expect("group should not be empty");
+
+                    Some(
+                        node_annos
+                            .get_value_for_item(last_token_id, &TOK_WHITESPACE_AFTER_ANNO_KEY)?
+                            .map_or_else(
+                                || formatting.segment_separator.clone(),
+                                |w| w.into_owned(),
+                            ),
+                    )
+                } else {
+                    None
+                };
+
+                part.push_segment(segment, segment_whitespace);
+            }
         }
 
         if let Some(part) = current_part.take() {
@@ -696,6 +1686,84 @@ fn get_parts(
     Ok(parts)
 }
 
+/// Returns the name of the node with the given annotation that encloses each of the match nodes.
+///
+/// For each match node, this is the first node with the annotation that covers the first token
+/// covered by the match node in the order of the text, or [None] if there is no such node.
+fn get_enclosing_node_names(
+    corpus_storage: &CorpusStorage,
+    corpus_name: &str,
+    match_node_names: &[String],
+    anno_key: &AnnoKey,
+) -> Result<Vec<Option<String>>, GraphAnnisError> {
+    let graph = corpus_storage.subgraph(corpus_name, match_node_names.to_vec(), 0, 0, None)?;
+    let graph_helper = GraphHelper::new(&graph);
+    let node_annos = graph.get_node_annos();
+
+    let mut enclosing_node_names = Vec::with_capacity(match_node_names.len());
+
+    for node_name in match_node_names {
+        let node_id = name::node_name_to_node_id(&graph, node_name)?;
+        let mut enclosing_node_name = None;
+
+        if let Some(token_id) = graph_helper.get_first_covered_token_id(node_id)? {
+            for covering_node_id in graph_helper.get_covering_node_ids(token_id) {
+                let covering_node_id = covering_node_id?;
+
+                if node_annos.has_value_for_item(&covering_node_id, anno_key)? {
+                    enclosing_node_name = node_annos
+                        .get_value_for_item(&covering_node_id, &NODE_NAME_KEY)?
+                        .map(|name| name.into_owned());
+
+                    break;
+                }
+            }
+        }
+
+        enclosing_node_names.push(enclosing_node_name);
+    }
+
+    Ok(enclosing_node_names)
+}
+
+/// Returns the names of the tokens within the given context around the given nodes.
+fn get_context_token_names(
+    corpus_storage: &CorpusStorage,
+    corpus_name: &str,
+    node_names: Vec<String>,
+    left_context: usize,
+    right_context: usize,
+    segmentation: Option<String>,
+) -> Result<Vec<String>, GraphAnnisError> {
+    let graph = corpus_storage.subgraph(
+        corpus_name,
+        node_names,
+        left_context,
+        right_context,
+        segmentation,
+    )?;
+    let graph_helper = GraphHelper::new(&graph);
+    let node_annos = graph.get_node_annos();
+
+    let mut token_names = Vec::new();
+
+    for node_id in node_annos.exact_anno_search(
+        Some(&TOKEN_ANNO_KEY.ns),
+        &TOKEN_ANNO_KEY.name,
+        ValueSearch::Any,
+    ) {
+        let node_id = node_id?.node;
+
+        if graph_helper.is_token(node_id)?
+            && let Some(token_name) = node_annos.get_value_for_item(&node_id, &NODE_NAME_KEY)?
+        {
+            token_names.push(token_name.into_owned());
+        }
+    }
+
+    Ok(token_names)
+}
+
 struct GraphHelper<'a> {
     graph: &'a Graph<AnnotationComponentType>,
     coverage_storages: Vec<&'a dyn GraphStorage>,
@@ -744,6 +1812,26 @@ impl<'a> GraphHelper<'a> {
         Ok(None)
     }
 
+    /// Returns the ID of the first token in the order of the text covered by the given node, if any.
+    fn get_first_covered_token_id(
+        &self,
+        node_id: NodeID,
+    ) -> Result<Option<NodeID>, GraphAnnisError> {
+        let Some(covered_token_id) = self.get_covered_token_id(node_id)? else {
+            return Ok(None);
+        };
+
+        for token_id in self.get_connected_node_ids_in_order(covered_token_id) {
+            let token_id = token_id?;
+
+            if self.is_covering_node_id(node_id, token_id)? {
+                return Ok(Some(token_id));
+            }
+        }
+
+        Ok(Some(covered_token_id))
+    }
+
     fn get_connected_node_ids_in_order(
         &self,
         node_id: NodeID,
@@ -776,6 +1864,29 @@ impl<'a> GraphHelper<'a> {
         )
     }
 
+    /// Returns the ID of the segmentation node covering the given token, if any.
+    ///
+    /// Segmentation nodes are recognized by having an annotation with the given key.
+    fn get_segment_node_id(
+        &self,
+        token_id: NodeID,
+        segment_anno_key: &AnnoKey,
+    ) -> Result<Option<NodeID>, GraphAnnisCoreError> {
+        let node_annos = self.graph.get_node_annos();
+
+        self.get_covering_node_ids(token_id)
+            .find_map(|node_id| {
+                node_id
+                    .and_then(|node_id| {
+                        node_annos
+                            .has_value_for_item(&node_id, segment_anno_key)
+                            .map(|has_segment| has_segment.then_some(node_id))
+                    })
+                    .transpose()
+            })
+            .transpose()
+    }
+
     fn is_covering_node_id(
         &self,
         source: NodeID,
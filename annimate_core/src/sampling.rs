@@ -0,0 +1,261 @@
+use rand::SeedableRng;
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::seq::index;
+
+use crate::ExportSampling;
+
+/// Selects matches according to an [`ExportSampling`].
+///
+/// Uses a random number generator with a fixed algorithm, so that the same seed always yields the
+/// same sample.
+#[derive(Debug)]
+pub(crate) struct Sampler {
+    sampling: ExportSampling,
+    rng: Option<Xoshiro256PlusPlus>,
+}
+
+impl Sampler {
+    pub(crate) fn new(sampling: ExportSampling) -> Self {
+        Self {
+            sampling,
+            rng: sampling.random_seed.map(Xoshiro256PlusPlus::seed_from_u64),
+        }
+    }
+
+    /// Returns the number of matches that need to be found in all corpora together, or [None] if
+    /// all matches are needed.
+    ///
+    /// Random samples need all matches, since any of them can be selected.
+    pub(crate) fn total_search_limit(&self) -> Option<usize> {
+        if self.rng.is_some() {
+            return None;
+        }
+
+        self.sampling
+            .limit
+            .map(|limit| self.sampling.offset.saturating_add(limit))
+    }
+
+    /// Returns the number of matches that need to be found in a single corpus, given the number
+    /// of matches still needed in total, or [None] if all matches are needed.
+    pub(crate) fn search_limit(&self, remaining_total: Option<usize>) -> Option<usize> {
+        if self.rng.is_some() {
+            return None;
+        }
+
+        match (self.sampling.limit_per_corpus, remaining_total) {
+            (Some(limit_per_corpus), Some(remaining_total)) => {
+                Some(limit_per_corpus.min(remaining_total))
+            }
+            (limit_per_corpus, remaining_total) => limit_per_corpus.or(remaining_total),
+        }
+    }
+
+    /// Searches a single corpus using `search` with the limit given by
+    /// [`search_limit`](Self::search_limit), then reduces the number of matches still needed in
+    /// total by the number of matches found.
+    ///
+    /// Once no more matches are needed, the corpus isn't searched at all.
+    pub(crate) fn search_corpus<T, E, F>(
+        &self,
+        remaining_total: &mut Option<usize>,
+        search: F,
+    ) -> Result<Vec<T>, E>
+    where
+        F: FnOnce(Option<usize>) -> Result<Vec<T>, E>,
+    {
+        if *remaining_total == Some(0) {
+            return Ok(Vec::new());
+        }
+
+        let items = search(self.search_limit(*remaining_total))?;
+
+        if let Some(remaining_total) = remaining_total {
+            *remaining_total = remaining_total.saturating_sub(items.len());
+        }
+
+        Ok(items)
+    }
+
+    /// Selects matches from the matches of a single corpus.
+    pub(crate) fn select_per_corpus<T>(&mut self, items: Vec<T>) -> Vec<T> {
+        self.select(items, 0, self.sampling.limit_per_corpus)
+    }
+
+    /// Selects matches from the matches of all corpora, after they were selected per corpus.
+    pub(crate) fn select_total<T>(&mut self, items: Vec<T>) -> Vec<T> {
+        self.select(items, self.sampling.offset, self.sampling.limit)
+    }
+
+    fn select<T>(&mut self, items: Vec<T>, offset: usize, limit: Option<usize>) -> Vec<T> {
+        let items = items.into_iter().skip(offset);
+
+        match (limit, &mut self.rng) {
+            (None, _) => items.collect(),
+            (Some(limit), None) => items.take(limit).collect(),
+            (Some(limit), Some(rng)) => {
+                let items: Vec<_> = items.collect();
+                if limit >= items.len() {
+                    return items;
+                }
+
+                let mut selected = vec![false; items.len()];
+                for i in index::sample(rng, items.len(), limit) {
+                    selected[i] = true;
+                }
+
+                items
+                    .into_iter()
+                    .zip(selected)
+                    .filter_map(|(item, selected)| selected.then_some(item))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_sampling() {
+        let mut sampler = Sampler::new(ExportSampling::default());
+
+        assert_eq!(sampler.select_per_corpus((0..5).collect()), [0, 1, 2, 3, 4]);
+        assert_eq!(sampler.select_total((0..5).collect()), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn limit_and_offset() {
+        let mut sampler = Sampler::new(ExportSampling {
+            offset: 2,
+            limit: Some(3),
+            limit_per_corpus: Some(4),
+            random_seed: None,
+        });
+
+        assert_eq!(sampler.select_per_corpus((0..10).collect()), [0, 1, 2, 3]);
+        assert_eq!(sampler.select_total((0..10).collect()), [2, 3, 4]);
+        assert_eq!(sampler.select_total((0..4).collect()), [2, 3]);
+        assert_eq!(sampler.select_total((0..2).collect()), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn search_limits() {
+        let sampler = Sampler::new(ExportSampling {
+            offset: 2,
+            limit: Some(3),
+            limit_per_corpus: Some(4),
+            random_seed: None,
+        });
+
+        assert_eq!(sampler.total_search_limit(), Some(5));
+        assert_eq!(sampler.search_limit(Some(5)), Some(4));
+        assert_eq!(sampler.search_limit(Some(1)), Some(1));
+        assert_eq!(sampler.search_limit(None), Some(4));
+
+        let sampler = Sampler::new(ExportSampling {
+            limit: Some(3),
+            ..ExportSampling::default()
+        });
+
+        assert_eq!(sampler.total_search_limit(), Some(3));
+        assert_eq!(sampler.search_limit(Some(3)), Some(3));
+        assert_eq!(sampler.search_limit(None), None);
+    }
+
+    #[test]
+    fn search_corpus_stops_once_enough_matches_found() {
+        let sampler = Sampler::new(ExportSampling {
+            limit: Some(3),
+            ..ExportSampling::default()
+        });
+
+        let mut remaining = sampler.total_search_limit();
+        let mut searched = Vec::new();
+
+        for (corpus, count) in [("a", 2), ("b", 1), ("c", 5)] {
+            sampler
+                .search_corpus(&mut remaining, |limit| {
+                    searched.push((corpus, limit));
+                    Ok::<_, ()>(vec![(); count.min(limit.unwrap_or(count))])
+                })
+                .unwrap();
+        }
+
+        assert_eq!(searched, [("a", Some(3)), ("b", Some(1))]);
+        assert_eq!(remaining, Some(0));
+    }
+
+    #[test]
+    fn search_limits_random_sample() {
+        let sampler = Sampler::new(ExportSampling {
+            limit: Some(3),
+            limit_per_corpus: Some(4),
+            random_seed: Some(42),
+            ..ExportSampling::default()
+        });
+
+        assert_eq!(sampler.total_search_limit(), None);
+        assert_eq!(sampler.search_limit(Some(3)), None);
+    }
+
+    #[test]
+    fn random_sample_preserves_order() {
+        let mut sampler = Sampler::new(ExportSampling {
+            limit: Some(10),
+            random_seed: Some(42),
+            ..ExportSampling::default()
+        });
+
+        let selected = sampler.select_total((0..100).collect());
+
+        assert_eq!(selected.len(), 10);
+        assert!(selected.is_sorted());
+    }
+
+    #[test]
+    fn random_sample_is_reproducible() {
+        let sampling = ExportSampling {
+            offset: 5,
+            limit: Some(10),
+            limit_per_corpus: Some(50),
+            random_seed: Some(42),
+        };
+
+        let select = || {
+            let mut sampler = Sampler::new(sampling);
+            let items = sampler.select_per_corpus((0..100).collect());
+            sampler.select_total(items)
+        };
+
+        assert_eq!(select(), select());
+    }
+
+    #[test]
+    fn random_sample_depends_on_seed() {
+        let select = |seed| {
+            Sampler::new(ExportSampling {
+                limit: Some(10),
+                random_seed: Some(seed),
+                ..ExportSampling::default()
+            })
+            .select_total((0..100).collect::<Vec<_>>())
+        };
+
+        assert_ne!(select(1), select(2));
+    }
+
+    #[test]
+    fn random_sample_with_limit_exceeding_count() {
+        let mut sampler = Sampler::new(ExportSampling {
+            offset: 1,
+            limit: Some(10),
+            random_seed: Some(42),
+            ..ExportSampling::default()
+        });
+
+        assert_eq!(sampler.select_total((0..5).collect()), [1, 2, 3, 4]);
+    }
+}
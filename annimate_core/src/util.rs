@@ -1,6 +1,6 @@
-use std::fs;
 use std::io::{self, ErrorKind};
-use std::path::Path;
+use std::path::{self, Component, Path, PathBuf};
+use std::{fs, iter};
 
 use tempfile::{NamedTempFile, PersistError};
 
@@ -115,6 +115,42 @@ where
     Ok(())
 }
 
+/// Returns a path leading to `path` from the directory `base`.
+///
+/// Both paths are made absolute first, without accessing the file system. If there is no such
+/// relative path, e.g. because `path` is on another drive on Windows, `path` is returned unchanged.
+pub(crate) fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let (Ok(path_abs), Ok(base_abs)) = (path::absolute(path), path::absolute(base)) else {
+        return path.into();
+    };
+
+    let path_components: Vec<_> = path_abs.components().collect();
+    let base_components: Vec<_> = base_abs.components().collect();
+
+    let common_len = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(p, b)| p == b)
+        .count();
+
+    let base_remainder = &base_components[common_len..];
+
+    // Without resolving symlinks, we cannot know where `..` in `base` leads to
+    if common_len == 0 || base_remainder.contains(&Component::ParentDir) {
+        return path.into();
+    }
+
+    let relative_path: PathBuf = iter::repeat_n(Component::ParentDir, base_remainder.len())
+        .chain(path_components[common_len..].iter().copied())
+        .collect();
+
+    if relative_path.as_os_str().is_empty() {
+        Component::CurDir.as_os_str().into()
+    } else {
+        relative_path
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,4 +285,52 @@ mod tests {
             assert!(!path.exists());
         }
     }
+
+    mod relative_path {
+        use super::*;
+
+        #[test]
+        fn descendant() {
+            assert_eq!(
+                relative_path(Path::new("/a/b/c/d.csv"), Path::new("/a/b")),
+                Path::new("c/d.csv")
+            );
+        }
+
+        #[test]
+        fn sibling() {
+            assert_eq!(
+                relative_path(Path::new("/a/c/d.csv"), Path::new("/a/b")),
+                Path::new("../c/d.csv")
+            );
+        }
+
+        #[test]
+        fn same() {
+            assert_eq!(
+                relative_path(Path::new("/a/b"), Path::new("/a/b")),
+                Path::new(".")
+            );
+        }
+
+        #[test]
+        fn relative_to_current_dir() {
+            let current_dir = std::env::current_dir().unwrap();
+
+            assert_eq!(
+                relative_path(Path::new("b/c.csv"), &current_dir.join("d")),
+                Path::new("../b/c.csv")
+            );
+        }
+
+        // On Windows, `..` is resolved lexically when making a path absolute
+        #[cfg(unix)]
+        #[test]
+        fn parent_dir_in_base() {
+            assert_eq!(
+                relative_path(Path::new("/a/c/d.csv"), Path::new("/a/b/../e")),
+                Path::new("/a/c/d.csv")
+            );
+        }
+    }
 }
@@ -0,0 +1,156 @@
+use std::fs;
+use std::path::Path;
+
+use annimate_core::{
+    AnnoKey, AnnoKeyOrDefault, EdgeType, ExportableEdgeComponentType, Project, ProjectContext,
+    ProjectExportColumn, ProjectExportFormat, ProjectFormatOptions, ProjectQuery, QueryLanguage,
+    Storage,
+};
+use itertools::Itertools;
+
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/check_project/db");
+
+#[test]
+fn valid_project() {
+    let storage = storage("valid_project");
+
+    let diagnostics = storage
+        .check_project(&project(
+            None,
+            &["subtok.demo"],
+            vec![query(
+                "pos=\"DT\"",
+                vec![
+                    ProjectExportColumn::Number,
+                    match_anno("grammar", "lemma", 0),
+                    ProjectExportColumn::MatchInContext {
+                        segmentation: Some("diplomatic".into()),
+                        anno_key: Some(AnnoKeyOrDefault::Default),
+                        context: ProjectContext::Symmetric(2),
+                        context_unit: None,
+                        primary_node_indices: vec![0],
+                        segment_separator: None,
+                        gap_marker: None,
+                        match_brackets: None,
+                        restore_whitespace: false,
+                    },
+                ],
+            )],
+        ))
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "{diagnostics:#?}");
+}
+
+#[test]
+fn project_with_problems() {
+    let storage = storage("project_with_problems");
+
+    let diagnostics = storage
+        .check_project(&project(
+            Some("Missing Corpus Set"),
+            &["subtok.demo", "missing.corpus"],
+            vec![
+                query(
+                    "pos=\"DT\"",
+                    vec![
+                        ProjectExportColumn::Number,
+                        match_anno("grammar", "missing", 1),
+                        ProjectExportColumn::AnnoCorpus {
+                            anno_key: None,
+                            value_type: None,
+                        },
+                        ProjectExportColumn::MatchInContext {
+                            segmentation: Some("missing".into()),
+                            anno_key: Some(AnnoKeyOrDefault::Default),
+                            context: ProjectContext::Symmetric(2),
+                            context_unit: None,
+                            primary_node_indices: vec![0],
+                            segment_separator: None,
+                            gap_marker: None,
+                            match_brackets: None,
+                            restore_whitespace: false,
+                        },
+                        ProjectExportColumn::AnnoEdge {
+                            edge_type: Some(EdgeType {
+                                ctype: ExportableEdgeComponentType::Dominance,
+                                name: "missing".into(),
+                            }),
+                            anno_key: Some(AnnoKey {
+                                ns: "".into(),
+                                name: "func".into(),
+                            }),
+                            source_node_index: Some(0),
+                            target_node_index: Some(0),
+                            value_type: None,
+                        },
+                    ],
+                ),
+                query("pos=", vec![ProjectExportColumn::Number]),
+                query("", vec![]),
+            ],
+        ))
+        .unwrap();
+
+    assert!(diagnostics.has_errors());
+
+    let messages = diagnostics
+        .errors
+        .iter()
+        .chain(&diagnostics.warnings)
+        .map(ToString::to_string)
+        .join("\n");
+
+    insta::assert_debug_snapshot!(diagnostics);
+    insta::assert_snapshot!("project_with_problems_messages", messages);
+}
+
+fn storage(name: &str) -> Storage {
+    let db_dir = Path::new(DB_DIR).join(name);
+    let _ = fs::remove_dir_all(&db_dir);
+
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+    storage
+        .import_corpora(
+            vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    storage
+}
+
+fn project(corpus_set: Option<&str>, corpus_names: &[&str], queries: Vec<ProjectQuery>) -> Project {
+    Project {
+        corpus_set: corpus_set.map(Into::into),
+        corpus_names: corpus_names.iter().map(ToString::to_string).collect(),
+        queries,
+        query_timeout: None,
+        export_format: ProjectExportFormat::Csv,
+        output_path: None,
+        format_options: ProjectFormatOptions::default(),
+    }
+}
+
+fn query(aql_query: &str, export_columns: Vec<ProjectExportColumn>) -> ProjectQuery {
+    ProjectQuery {
+        name: String::new(),
+        aql_query: aql_query.into(),
+        query_language: QueryLanguage::AQL,
+        export_columns,
+    }
+}
+
+fn match_anno(ns: &str, name: &str, node_index: u32) -> ProjectExportColumn {
+    ProjectExportColumn::AnnoMatch {
+        anno_key: Some(AnnoKey {
+            ns: ns.into(),
+            name: name.into(),
+        }),
+        node_index: Some(node_index),
+        aggregation: None,
+        value_type: None,
+    }
+}
@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use annimate_core::{AnnimateError, CountStatusEvent, QueryLanguage, Storage};
+
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/count_matches/db");
+
+macro_rules! count_matches_test {
+    ($(
+        $name:ident: {
+            corpus_paths: $corpus_paths:expr,
+            corpus_names: $corpus_names:expr,
+            aql_query: $aql_query:expr,
+            query_language: $query_language:expr,
+            by_document: $by_document:expr,
+        }
+    )*) => {
+        $(
+            #[test]
+            fn $name() {
+                let storage = import_storage(stringify!($name), &$corpus_paths);
+
+                let counts = storage
+                    .count_matches(
+                        &$corpus_names,
+                        $aql_query,
+                        {
+                            use QueryLanguage::*;
+                            $query_language
+                        },
+                        $by_document,
+                        None,
+                        |_| (),
+                        || false,
+                    )
+                    .unwrap();
+
+                insta::assert_yaml_snapshot!(counts);
+            }
+        )*
+    };
+}
+
+count_matches_test! {
+    subtok_by_corpus: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo2", "subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        by_document: false,
+    }
+    subtok_by_document: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo2", "subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        by_document: true,
+    }
+    subtok_no_matches: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"XYZ\"",
+        query_language: AQL,
+        by_document: true,
+    }
+}
+
+#[test]
+fn count_reports_status() {
+    let storage = import_storage(
+        "count_reports_status",
+        &["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+    );
+    let events = RefCell::new(Vec::new());
+
+    storage
+        .count_matches(
+            &["subtok.demo", "subtok.demo2"],
+            "tok",
+            QueryLanguage::AQL,
+            false,
+            None,
+            |event| events.borrow_mut().push(event),
+            || false,
+        )
+        .unwrap();
+
+    insta::assert_yaml_snapshot!(events.into_inner());
+}
+
+#[test]
+fn count_cancelled() {
+    let storage = import_storage("count_cancelled", &["subtok.demo_relANNIS.zip"]);
+
+    let result = storage.count_matches(
+        &["subtok.demo"],
+        "tok",
+        QueryLanguage::AQL,
+        false,
+        None,
+        |_: CountStatusEvent| (),
+        || true,
+    );
+
+    assert!(matches!(result, Err(AnnimateError::Cancelled)));
+}
+
+#[test]
+fn count_timed_out() {
+    let storage = import_storage("count_timed_out", &["subtok.demo_relANNIS.zip"]);
+
+    for by_document in [false, true] {
+        let result = storage.count_matches(
+            &["subtok.demo"],
+            "tok",
+            QueryLanguage::AQL,
+            by_document,
+            Some(Duration::ZERO),
+            |_: CountStatusEvent| (),
+            || false,
+        );
+
+        assert!(
+            matches!(
+                &result,
+                Err(AnnimateError::QueryTimedOut { corpus_name }) if corpus_name == "subtok.demo"
+            ),
+            "{result:?}"
+        );
+    }
+}
+
+fn import_storage(name: &str, corpus_paths: &[&str]) -> Storage {
+    let db_dir = Path::new(DB_DIR).join(name);
+
+    let _ = fs::remove_dir_all(&db_dir);
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+
+    storage
+        .import_corpora(
+            corpus_paths
+                .iter()
+                .map(|p| Path::new(DATA_DIR).join(p))
+                .collect(),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    storage
+}
@@ -1,14 +1,21 @@
 use std::cell::Cell;
-use std::fs;
+use std::fs::{self, File};
+use std::io;
 use std::path::Path;
+use std::time::Duration;
 
 use annimate_core::{
-    AnnimateError, AnnoKey, AnnoKeyOrDefault, CsvExportConfig, EdgeType, ExportConfig, ExportData,
-    ExportDataText, ExportDataValue, ExportFormat, ExportStatusEvent, ExportableEdgeComponentType,
-    QueryLanguage, QueryNodePropertyKey, Storage, TableExportColumn,
+    AnnimateError, AnnoAggregation, AnnoKey, AnnoKeyOrDefault, BatchExportTarget, CsvDialect,
+    CsvExportConfig, EdgeType, ExportConfig, ExportData, ExportDataText, ExportDataValue,
+    ExportFormat, ExportSampling, ExportStatusEvent, ExportableEdgeComponentType, FrequencySplit,
+    HtmlExportConfig, JsonlExportConfig, NamedExportConfig, NodePosition, OdsExportConfig,
+    Parallelism, QueryLanguage, QueryNodePropertyKey, Storage, TableExportColumn, TableExportMode,
+    TextFormatting, Traversal, TraversalItem, VERSION_INFO, ValueType, XlsxExportConfig,
+    XlsxOptions,
 };
 use itertools::Itertools;
 use serde::Serialize;
+use zip::{CompressionMethod, ZipArchive};
 
 const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
 const DB_DIR: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/tests/export_matches/db");
@@ -22,6 +29,9 @@ macro_rules! export_matches_test {
             aql_query: $aql_query:expr,
             query_language: $query_language:expr,
             export_columns: [$($export_columns:expr,)*],
+            $(export_format: $export_format:ident,)?
+            $(mode: $mode:expr,)?
+            $(sampling: $sampling:expr,)?
         }
     )*) => {
         $(
@@ -43,12 +53,24 @@ macro_rules! export_matches_test {
                         #[allow(unused_imports)]
                         use TestExportData::*;
                         #[allow(unused_imports)]
+                        use TestNodePosition::*;
+                        #[allow(unused_imports)]
                         use TestQueryNodePropertyKey::*;
                         #[allow(unused_imports)]
                         use TestTableExportColumn::*;
 
                         vec![$($export_columns,)*]
                     },
+                    export_format: export_matches_test!(@export_format $($export_format)?),
+                    mode: {
+                        #[allow(unused_imports)]
+                        use TestFrequencySplit::*;
+                        #[allow(unused_imports)]
+                        use TestTableExportMode::*;
+
+                        export_matches_test!(@mode $($mode)?)
+                    },
+                    sampling: export_matches_test!(@sampling $($sampling)?),
                 };
 
                 let db_dir = Path::new(DB_DIR).join(stringify!($name));
@@ -57,7 +79,9 @@ macro_rules! export_matches_test {
                 let storage = Storage::from_db_dir(db_dir).unwrap();
 
                 fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
-                let output_file = Path::new(OUTPUT_DIR).join(concat!(stringify!($name), ".csv"));
+                let output_file = Path::new(OUTPUT_DIR)
+                    .join(stringify!($name))
+                    .with_extension(test_data.export_format.extension());
                 let _ = fs::remove_file(&output_file);
 
                 storage
@@ -78,14 +102,17 @@ macro_rules! export_matches_test {
                             corpus_names: test_data.corpus_names.into_iter().cloned().map_into().collect_vec(),
                             aql_query: test_data.aql_query.into(),
                             query_language: test_data.query_language,
-                            format: ExportFormat::Csv(CsvExportConfig {
-                                columns: test_data
+                            timeout: None,
+                            format: test_data.export_format.into_export_format(
+                                test_data
                                     .export_columns
                                     .clone()
                                     .into_iter()
                                     .map_into()
                                     .collect(),
-                            }),
+                                test_data.mode.into(),
+                            ),
+                            sampling: test_data.sampling.into(),
                         },
                         &output_file,
                         |_| (),
@@ -93,7 +120,10 @@ macro_rules! export_matches_test {
                     )
                     .unwrap();
 
-                let output = fs::read_to_string(output_file).unwrap();
+                let output = test_data
+                    .export_format
+                    .read_output(&output_file)
+                    .replace(VERSION_INFO.annimate_version, "[version]");
 
                 insta::with_settings!(
                     {
@@ -105,6 +135,13 @@ macro_rules! export_matches_test {
             }
         )*
     };
+
+    (@export_format) => { TestExportFormat::Csv };
+    (@export_format $export_format:ident) => { TestExportFormat::$export_format };
+    (@mode) => { Matches };
+    (@mode $mode:expr) => { $mode };
+    (@sampling) => { TestSampling::default() };
+    (@sampling $sampling:expr) => { $sampling };
 }
 
 export_matches_test! {
@@ -127,14 +164,17 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("annis", "tok"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "norm"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("structure", "line"),
                 index: 0,
+                aggregation: None,
             })),
         ],
     }
@@ -148,10 +188,12 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "norm"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "norm"),
                 index: 1,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::QueryNodeProperty {
                 query_node_property_key: Fragment,
@@ -187,13 +229,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 4,
                 right_context: 4,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -213,13 +258,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: AnnoKey(("annis", "node_name")),
                 left_context: 4,
                 right_context: 4,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -239,13 +287,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: Some("diplomatic"),
                 anno_key: Default,
                 left_context: 4,
                 right_context: 4,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -265,13 +316,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: Some("diplomatic"),
                 anno_key: AnnoKey(("annis", "node_name")),
                 left_context: 4,
                 right_context: 4,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -291,13 +345,179 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: Some("norm"),
                 anno_key: Default,
                 left_context: 4,
                 right_context: 4,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_context_unit: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("structure", "line"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: Some(("structure", "line")),
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_context_unit_fallback: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: Some(("structure", "nonexistent")),
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_context_unit_partial_fallback: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "tok=\"T\" .1,2 tok",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: Some(("structure", "rend")),
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_text_formatting: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 4,
+                right_context: 4,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: Some(TestTextFormatting {
+                    segment_separator: "|",
+                    gap_marker: "[...]",
+                    match_brackets: Some(("<<", ">>")),
+                    restore_whitespace: false,
+                }),
+            })),
+        ],
+    }
+    subtok_restore_whitespace: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 4,
+                right_context: 4,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: Some(TestTextFormatting {
+                    segment_separator: "|",
+                    gap_marker: "(...)",
+                    match_brackets: None,
+                    restore_whitespace: true,
+                }),
+            })),
+        ],
+    }
+    subtok_covered_text_segmentation: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "line",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: None,
+            })),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: Some("diplomatic"),
+                anno_key: Default,
+                formatting: None,
+            })),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: Some("norm"),
+                anno_key: Default,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_covered_text_formatting: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "line",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: Some(TestTextFormatting {
+                    segment_separator: "|",
+                    gap_marker: "(...)",
+                    match_brackets: None,
+                    restore_whitespace: false,
+                }),
+            })),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: Some(TestTextFormatting {
+                    segment_separator: "|",
+                    gap_marker: "(...)",
+                    match_brackets: None,
+                    restore_whitespace: true,
+                }),
             })),
         ],
     }
@@ -317,17 +537,21 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 1,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -347,17 +571,21 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "pos"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "pos"),
                 index: 1,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -372,7 +600,9 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
                 primary_node_indices: Some(&[0, 1]),
+                formatting: None,
             })),
         ],
     }
@@ -385,13 +615,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("grammar", "lemma"),
                 index: 0,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: Some("norm"),
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -413,9 +646,176 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_sampling_limit_offset: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        sampling: TestSampling {
+            offset: 1,
+            limit: Some(2),
+            ..TestSampling::default()
+        },
+    }
+    subtok_sampling_limit_per_corpus: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo", "subtok.demo2"],
+        aql_query: "pos=\"DT\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CorpusAnno {
+                anno_key: ("", "language"),
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        sampling: TestSampling {
+            limit_per_corpus: Some(1),
+            ..TestSampling::default()
+        },
+    }
+    subtok_sampling_random: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo", "subtok.demo2"],
+        aql_query: "tok",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CorpusAnno {
+                anno_key: ("", "language"),
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        sampling: TestSampling {
+            limit: Some(5),
+            limit_per_corpus: Some(10),
+            random_seed: Some(42),
+            ..TestSampling::default()
+        },
+    }
+    subtok_frequencies: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo", "subtok.demo2"],
+        aql_query: "pos",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "pos"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        mode: Frequencies(None),
+    }
+    subtok_frequencies_by_corpus: {
+        corpus_paths: ["subtok.demo_relANNIS.zip", "subtok.demo2_relANNIS.zip"],
+        corpus_names: ["subtok.demo", "subtok.demo2"],
+        aql_query: "pos",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "pos"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        mode: Frequencies(Corpus),
+    }
+    subtok_frequencies_by_document: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "pos"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
+        mode: Frequencies(Document),
     }
     subtok_renamed: {
         corpus_paths: ["subtok.demo_renamed_graphml.zip"],
@@ -435,7 +835,9 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 1,
                 right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -470,10 +872,12 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("annis", "tok"),
                 index: 1,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("annis", "tok"),
                 index: 3,
+                aggregation: None,
             })),
             Data(Value(TestExportDataValue::EdgeAnno {
                 edge_type: (Pointing, "dep"),
@@ -483,29 +887,230 @@ export_matches_test! {
             })),
         ],
     }
-    pcc2_primary_node: {
+    pcc2_traversal_anno_dominance: {
         corpus_paths: ["pcc2_v7_relANNIS.zip"],
         corpus_names: ["pcc2"],
-        aql_query: "Sent _i_ NP",
+        aql_query: "cat=\"NP\" _l_ pos=\"ART\"",
         query_language: AQL,
         export_columns: [
             Number,
-            Data(Value(TestExportDataValue::CorpusAnno {
-                anno_key: ("", "language"),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: None,
             })),
-            Data(Value(TestExportDataValue::DocumentAnno {
-                anno_key: ("annis", "doc"),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Parent { index: 1 },
+                item: TestTraversalItem::Nodes,
+                anno_key: ("tiger", "cat"),
             })),
-            Data(Value(TestExportDataValue::MatchNodeAnno {
-                anno_key: ("exmaralda", "Inf-Stat"),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Parent { index: 1 },
+                item: TestTraversalItem::Edges,
+                anno_key: ("tiger", "func"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Ancestor {
+                    index: 0,
+                    distance: 2,
+                },
+                item: TestTraversalItem::Nodes,
+                anno_key: ("tiger", "cat"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Ancestor {
+                    index: 0,
+                    distance: 2,
+                },
+                item: TestTraversalItem::Edges,
+                anno_key: ("tiger", "func"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Children { index: 0 },
+                item: TestTraversalItem::Edges,
+                anno_key: ("tiger", "func"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Dominance, "edge"),
+                traversal: TestTraversal::Path {
+                    source_node_index: 0,
+                    target_node_index: 1,
+                },
+                item: TestTraversalItem::Edges,
+                anno_key: ("tiger", "func"),
+            })),
+        ],
+    }
+    pcc2_traversal_anno_pointing: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "cat=\"NP\" _l_ pos=\"ART\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("annis", "tok"),
+                index: 1,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Pointing, "dep"),
+                traversal: TestTraversal::Parent { index: 1 },
+                item: TestTraversalItem::Nodes,
+                anno_key: ("annis", "tok"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Pointing, "dep"),
+                traversal: TestTraversal::Parent { index: 1 },
+                item: TestTraversalItem::Edges,
+                anno_key: ("dep", "func"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Pointing, "dep"),
+                traversal: TestTraversal::Ancestor {
+                    index: 1,
+                    distance: 2,
+                },
+                item: TestTraversalItem::Nodes,
+                anno_key: ("annis", "tok"),
+            })),
+            Data(Value(TestExportDataValue::TraversalAnno {
+                edge_type: (Pointing, "dep"),
+                traversal: TestTraversal::Ancestor {
+                    index: 1,
+                    distance: 2,
+                },
+                item: TestTraversalItem::Edges,
+                anno_key: ("dep", "func"),
+            })),
+        ],
+    }
+    pcc2_covered_text: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "cat=\"S\" > cat=\"NP\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 1,
+                segmentation: None,
+                anno_key: Default,
+                formatting: None,
+            })),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 1,
+                segmentation: None,
+                anno_key: AnnoKey(("tiger", "pos")),
+                formatting: None,
+            })),
+        ],
+    }
+    pcc2_match_node_position: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "cat=\"NP\" _l_ pos=\"ART\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Value(TestExportDataValue::MatchNodePosition {
+                index: 0,
+                position: TokenIndex,
+            })),
+            Data(Value(TestExportDataValue::MatchNodePosition {
+                index: 0,
+                position: FirstTokenIndex,
+            })),
+            Data(Value(TestExportDataValue::MatchNodePosition {
+                index: 0,
+                position: LastTokenIndex,
+            })),
+            Data(Value(TestExportDataValue::MatchNodePosition {
                 index: 1,
+                position: TokenIndex,
+            })),
+            Data(Value(TestExportDataValue::MatchNodePosition {
+                index: 1,
+                position: NodeName,
+            })),
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: None,
+            })),
+        ],
+    }
+    pcc2_match_node_anno_aggregation: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "cat=\"NP\" _l_ pos=\"ART\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CoveredText {
+                index: 0,
+                segmentation: None,
+                anno_key: Default,
+                formatting: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "pos"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "pos"),
+                index: 0,
+                aggregation: Some(TestAnnoAggregation::Distinct { separator: ", " }),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "pos"),
+                index: 0,
+                aggregation: Some(TestAnnoAggregation::All { separator: " " }),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "cat"),
+                index: 0,
+                aggregation: Some(TestAnnoAggregation::All { separator: " " }),
+            })),
+        ],
+    }
+    pcc2_primary_node: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "Sent _i_ NP",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::CorpusAnno {
+                anno_key: ("", "language"),
+            })),
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("exmaralda", "Inf-Stat"),
+                index: 1,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 10,
                 right_context: 10,
+                context_unit: None,
                 primary_node_indices: Some(&[1]),
+                formatting: None,
             })),
         ],
     }
@@ -525,13 +1130,16 @@ export_matches_test! {
             Data(Value(TestExportDataValue::MatchNodeAnno {
                 anno_key: ("exmaralda", "Inf-Stat"),
                 index: 1,
+                aggregation: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 10,
                 right_context: 10,
+                context_unit: None,
                 primary_node_indices: Some(&[1, 0]),
+                formatting: None,
             })),
         ],
     }
@@ -547,21 +1155,27 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 0,
                 right_context: 0,
+                context_unit: None,
                 primary_node_indices: Some(&[]),
+                formatting: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 0,
                 right_context: 0,
+                context_unit: None,
                 primary_node_indices: Some(&[1]),
+                formatting: None,
             })),
             Data(Text(TestExportDataText {
                 segmentation: None,
                 anno_key: Default,
                 left_context: 0,
                 right_context: 0,
+                context_unit: None,
                 primary_node_indices: Some(&[0, 1]),
+                formatting: None,
             })),
         ],
     }
@@ -577,7 +1191,9 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 2,
                 right_context: 2,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
             })),
         ],
     }
@@ -593,9 +1209,241 @@ export_matches_test! {
                 anno_key: Default,
                 left_context: 2,
                 right_context: 2,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+    }
+    subtok_jsonl: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\" . pos=\"NN\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 1,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "norm"),
+                index: 1,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: Some(&[1]),
+                formatting: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: Some("norm"),
+                anno_key: Default,
+                left_context: 0,
+                right_context: 0,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        export_format: Jsonl,
+    }
+    subtok_html: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\" . pos=\"NN\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 1,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: Some("norm"),
+                anno_key: Default,
+                left_context: 0,
+                right_context: 0,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        export_format: Html,
+    }
+    pcc2_html: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "cat=\"NP\" _l_ pos=\"ART\" & #2 . pos=\"ADJA\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "cat"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 2,
+                right_context: 2,
+                context_unit: None,
+                primary_node_indices: Some(&[1, 2]),
+                formatting: None,
+            })),
+        ],
+        export_format: Html,
+    }
+    subtok_ods: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\" . pos=\"NN\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::DocumentAnno {
+                anno_key: ("annis", "doc"),
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 1,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
                 primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        export_format: Ods,
+    }
+    subtok_ods_frequencies: {
+        corpus_paths: ["subtok.demo_relANNIS.zip"],
+        corpus_names: ["subtok.demo"],
+        aql_query: "pos=\"DT\" . pos=\"NN\"",
+        query_language: AQL,
+        export_columns: [
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("grammar", "lemma"),
+                index: 1,
+                aggregation: None,
+            })),
+        ],
+        export_format: Ods,
+        mode: Frequencies(None),
+    }
+    pcc2_formula_prefixes: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "tok=\"-\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "lemma"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "morph"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        export_format: CsvEscapeFormulas,
+    }
+    pcc2_formula_prefixes_xlsx: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "tok=\"-\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "lemma"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: ("tiger", "morph"),
+                index: 0,
+                aggregation: None,
+            })),
+            Data(Text(TestExportDataText {
+                segmentation: None,
+                anno_key: Default,
+                left_context: 1,
+                right_context: 1,
+                context_unit: None,
+                primary_node_indices: None,
+                formatting: None,
+            })),
+        ],
+        export_format: Xlsx,
+    }
+    pcc2_xlsx_value_types: {
+        corpus_paths: ["pcc2_v7_relANNIS.zip"],
+        corpus_names: ["pcc2"],
+        aql_query: "tok=\"-\"",
+        query_language: AQL,
+        export_columns: [
+            Number,
+            Data(Value(TestExportDataValue::MatchNodePosition {
+                index: 0,
+                position: TestNodePosition::TokenIndex,
             })),
+            TypedData(
+                Value(TestExportDataValue::MatchNodePosition {
+                    index: 0,
+                    position: TestNodePosition::LastTokenIndex,
+                }),
+                ValueType::Text,
+            ),
+            TypedData(
+                Value(TestExportDataValue::MatchNodeAnno {
+                    anno_key: ("tiger", "lemma"),
+                    index: 0,
+                    aggregation: None,
+                }),
+                ValueType::Integer,
+            ),
         ],
+        export_format: Xlsx,
     }
 }
 
@@ -623,9 +1471,13 @@ fn export_cancelled_before_matches_found() {
             corpus_names: vec!["subtok.demo".into()],
             aql_query: "tok".into(),
             query_language: QueryLanguage::AQL,
+            timeout: None,
             format: ExportFormat::Csv(CsvExportConfig {
                 columns: vec![TableExportColumn::Number],
+                mode: TableExportMode::Matches,
+                dialect: CsvDialect::default(),
             }),
+            sampling: ExportSampling::default(),
         },
         &output_file,
         |_| (),
@@ -662,9 +1514,13 @@ fn export_cancelled_after_matches_found() {
             corpus_names: vec!["subtok.demo".into()],
             aql_query: "tok".into(),
             query_language: QueryLanguage::AQL,
+            timeout: None,
             format: ExportFormat::Csv(CsvExportConfig {
                 columns: vec![TableExportColumn::Number],
+                mode: TableExportMode::Matches,
+                dialect: CsvDialect::default(),
             }),
+            sampling: ExportSampling::default(),
         },
         &output_file,
         |event| {
@@ -681,6 +1537,273 @@ fn export_cancelled_after_matches_found() {
     assert!(!output_file.try_exists().unwrap());
 }
 
+#[test]
+fn export_timed_out() {
+    let db_dir = Path::new(DB_DIR).join("export_timed_out");
+
+    let _ = fs::remove_dir_all(&db_dir);
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let output_file = Path::new(OUTPUT_DIR).join("export_timed_out.csv");
+    let _ = fs::remove_file(&output_file);
+
+    storage
+        .import_corpora(
+            vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    let result = storage.export_matches(
+        ExportConfig {
+            corpus_names: vec!["subtok.demo".into()],
+            aql_query: "tok".into(),
+            query_language: QueryLanguage::AQL,
+            timeout: Some(Duration::ZERO),
+            format: ExportFormat::Csv(CsvExportConfig {
+                columns: vec![TableExportColumn::Number],
+                mode: TableExportMode::Matches,
+                dialect: CsvDialect::default(),
+            }),
+            sampling: ExportSampling::default(),
+        },
+        &output_file,
+        |_| (),
+        || false,
+    );
+
+    assert!(matches!(
+        result,
+        Err(AnnimateError::QueryTimedOut { corpus_name }) if corpus_name == "subtok.demo"
+    ));
+    assert!(!output_file.try_exists().unwrap());
+}
+
+#[test]
+fn export_parallel_same_as_sequential() {
+    let db_dir = Path::new(DB_DIR).join("export_parallel_same_as_sequential");
+
+    let _ = fs::remove_dir_all(&db_dir);
+
+    Storage::from_db_dir(db_dir.clone())
+        .unwrap()
+        .import_corpora(
+            ["pcc2_v7_relANNIS.zip", "subtok.demo_relANNIS.zip"]
+                .into_iter()
+                .map(|p| Path::new(DATA_DIR).join(p))
+                .collect(),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+
+    let export = |parallelism: Parallelism| {
+        let output_file = Path::new(OUTPUT_DIR)
+            .join(format!(
+                "export_parallel_same_as_sequential_{parallelism:?}"
+            ))
+            .with_extension("csv");
+
+        Storage::from_db_dir(db_dir.clone())
+            .unwrap()
+            .with_parallelism(parallelism)
+            .export_matches(
+                ExportConfig {
+                    corpus_names: vec!["pcc2".into(), "subtok.demo".into()],
+                    aql_query: "tok".into(),
+                    query_language: QueryLanguage::AQL,
+                    timeout: None,
+                    format: ExportFormat::Csv(CsvExportConfig {
+                        columns: vec![
+                            TableExportColumn::Number,
+                            TableExportColumn::Data(
+                                ExportData::Text(ExportDataText {
+                                    left_context: 2,
+                                    right_context: 2,
+                                    context_unit: None,
+                                    segmentation: None,
+                                    anno_key: AnnoKeyOrDefault::Default,
+                                    primary_node_indices: None,
+                                    formatting: TextFormatting::default(),
+                                }),
+                                ValueType::Auto,
+                            ),
+                        ],
+                        mode: TableExportMode::Matches,
+                        dialect: CsvDialect::default(),
+                    }),
+                    sampling: ExportSampling::default(),
+                },
+                &output_file,
+                |_| (),
+                || false,
+            )
+            .unwrap();
+
+        fs::read_to_string(output_file).unwrap()
+    };
+
+    let sequential = export(Parallelism::Sequential);
+    let parallel = export(Parallelism::Parallel);
+
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn export_batch_directory() {
+    let db_dir = Path::new(DB_DIR).join("export_batch_directory");
+
+    let _ = fs::remove_dir_all(&db_dir);
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+
+    let output_dir = Path::new(OUTPUT_DIR).join("export_batch_directory");
+    let _ = fs::remove_dir_all(&output_dir);
+    fs::create_dir_all(&output_dir).unwrap();
+
+    storage
+        .import_corpora(
+            vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    let query_indices = Cell::new(Vec::new());
+
+    let output_files = storage
+        .export_batch(
+            vec![
+                batch_export_config("tokens", "tok", TestExportFormat::Csv),
+                batch_export_config("", "pos=\"DT\"", TestExportFormat::Xlsx),
+            ],
+            BatchExportTarget::Directory(output_dir.clone()),
+            |event| {
+                let mut indices = query_indices.take();
+                if !indices.contains(&event.query_index) {
+                    indices.push(event.query_index);
+                }
+                query_indices.set(indices);
+            },
+            || false,
+        )
+        .unwrap();
+
+    assert_eq!(
+        output_files,
+        [
+            output_dir.join("tokens.csv"),
+            output_dir.join("Query 2.xlsx")
+        ]
+    );
+    assert!(output_files.iter().all(|f| f.try_exists().unwrap()));
+    assert_eq!(query_indices.take(), [0, 1]);
+}
+
+#[test]
+fn export_batch_workbook() {
+    let db_dir = Path::new(DB_DIR).join("export_batch_workbook");
+
+    let _ = fs::remove_dir_all(&db_dir);
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let output_file = Path::new(OUTPUT_DIR).join("export_batch_workbook.xlsx");
+    let _ = fs::remove_file(&output_file);
+
+    storage
+        .import_corpora(
+            vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    let output_files = storage
+        .export_batch(
+            vec![
+                batch_export_config("determiners", "pos=\"DT\"", TestExportFormat::Xlsx),
+                batch_export_config("", "pos=\"NN\"", TestExportFormat::Xlsx),
+            ],
+            BatchExportTarget::Workbook(output_file.clone()),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    assert_eq!(output_files, vec![output_file.clone()]);
+
+    let mut archive = ZipArchive::new(File::open(&output_file).unwrap()).unwrap();
+    let output = [
+        "xl/workbook.xml",
+        "xl/worksheets/sheet3.xml",
+        "xl/sharedStrings.xml",
+    ]
+    .map(|name| io::read_to_string(archive.by_name(name).unwrap()).unwrap())
+    .join("\n")
+    .replace(VERSION_INFO.annimate_version, "[version]");
+
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn export_batch_workbook_requires_xlsx() {
+    let db_dir = Path::new(DB_DIR).join("export_batch_workbook_requires_xlsx");
+
+    let _ = fs::remove_dir_all(&db_dir);
+    let storage = Storage::from_db_dir(db_dir).unwrap();
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let output_file = Path::new(OUTPUT_DIR).join("export_batch_workbook_requires_xlsx.xlsx");
+    let _ = fs::remove_file(&output_file);
+
+    storage
+        .import_corpora(
+            vec![Path::new(DATA_DIR).join("subtok.demo_relANNIS.zip")],
+            |_| (),
+            || false,
+        )
+        .unwrap();
+
+    let result = storage.export_batch(
+        vec![
+            batch_export_config("", "tok", TestExportFormat::Xlsx),
+            batch_export_config("", "tok", TestExportFormat::Csv),
+        ],
+        BatchExportTarget::Workbook(output_file.clone()),
+        |_| (),
+        || false,
+    );
+
+    assert!(matches!(
+        result,
+        Err(AnnimateError::BatchWorkbookRequiresXlsx)
+    ));
+    assert!(!output_file.try_exists().unwrap());
+}
+
+fn batch_export_config(
+    name: &str,
+    aql_query: &str,
+    export_format: TestExportFormat,
+) -> NamedExportConfig {
+    NamedExportConfig {
+        name: name.into(),
+        config: ExportConfig {
+            corpus_names: vec!["subtok.demo".into()],
+            aql_query: aql_query.into(),
+            query_language: QueryLanguage::AQL,
+            timeout: None,
+            format: export_format
+                .into_export_format(vec![TableExportColumn::Number], TableExportMode::Matches),
+            sampling: ExportSampling::default(),
+        },
+    }
+}
+
 #[derive(Serialize)]
 struct TestData {
     corpus_paths: &'static [&'static str],
@@ -688,12 +1811,167 @@ struct TestData {
     aql_query: &'static str,
     query_language: QueryLanguage,
     export_columns: Vec<TestTableExportColumn>,
+    #[serde(skip_serializing_if = "TestExportFormat::is_csv")]
+    export_format: TestExportFormat,
+    #[serde(skip_serializing_if = "TestTableExportMode::is_matches")]
+    mode: TestTableExportMode,
+    #[serde(skip_serializing_if = "TestSampling::is_default")]
+    sampling: TestSampling,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize)]
+struct TestSampling {
+    offset: usize,
+    limit: Option<usize>,
+    limit_per_corpus: Option<usize>,
+    random_seed: Option<u64>,
+}
+
+impl TestSampling {
+    fn is_default(&self) -> bool {
+        *self == TestSampling::default()
+    }
+}
+
+impl From<TestSampling> for ExportSampling {
+    fn from(sampling: TestSampling) -> ExportSampling {
+        ExportSampling {
+            offset: sampling.offset,
+            limit: sampling.limit,
+            limit_per_corpus: sampling.limit_per_corpus,
+            random_seed: sampling.random_seed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+enum TestExportFormat {
+    Csv,
+    CsvEscapeFormulas,
+    Jsonl,
+    Xlsx,
+    Html,
+    Ods,
+}
+
+impl TestExportFormat {
+    fn is_csv(&self) -> bool {
+        matches!(self, TestExportFormat::Csv)
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            TestExportFormat::Csv | TestExportFormat::CsvEscapeFormulas => "csv",
+            TestExportFormat::Jsonl => "jsonl",
+            TestExportFormat::Xlsx => "xlsx",
+            TestExportFormat::Html => "html",
+            TestExportFormat::Ods => "ods",
+        }
+    }
+
+    fn read_output(self, path: &Path) -> String {
+        match self {
+            TestExportFormat::Csv
+            | TestExportFormat::CsvEscapeFormulas
+            | TestExportFormat::Jsonl
+            | TestExportFormat::Html => fs::read_to_string(path).unwrap(),
+            TestExportFormat::Xlsx => {
+                let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+
+                let worksheet =
+                    io::read_to_string(archive.by_name("xl/worksheets/sheet1.xml").unwrap())
+                        .unwrap();
+                let shared_strings =
+                    io::read_to_string(archive.by_name("xl/sharedStrings.xml").unwrap()).unwrap();
+
+                format!("{worksheet}\n{shared_strings}")
+            }
+            TestExportFormat::Ods => {
+                let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+
+                {
+                    let mut mimetype = archive.by_index(0).unwrap();
+                    assert_eq!(mimetype.name(), "mimetype");
+                    assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+                    assert_eq!(
+                        io::read_to_string(&mut mimetype).unwrap(),
+                        "application/vnd.oasis.opendocument.spreadsheet"
+                    );
+                }
+
+                io::read_to_string(archive.by_name("content.xml").unwrap()).unwrap()
+            }
+        }
+    }
+
+    fn into_export_format(
+        self,
+        columns: Vec<TableExportColumn>,
+        mode: TableExportMode,
+    ) -> ExportFormat {
+        match self {
+            TestExportFormat::Csv => ExportFormat::Csv(CsvExportConfig {
+                columns,
+                mode,
+                dialect: CsvDialect::default(),
+            }),
+            TestExportFormat::CsvEscapeFormulas => ExportFormat::Csv(CsvExportConfig {
+                columns,
+                mode,
+                dialect: CsvDialect {
+                    escape_formulas: true,
+                    ..CsvDialect::default()
+                },
+            }),
+            TestExportFormat::Jsonl => ExportFormat::Jsonl(JsonlExportConfig { columns }),
+            TestExportFormat::Xlsx => ExportFormat::Xlsx(XlsxExportConfig {
+                columns,
+                mode,
+                options: XlsxOptions::default(),
+            }),
+            TestExportFormat::Html => ExportFormat::Html(HtmlExportConfig { columns }),
+            TestExportFormat::Ods => ExportFormat::Ods(OdsExportConfig { columns, mode }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+enum TestTableExportMode {
+    Matches,
+    Frequencies(TestFrequencySplit),
+}
+
+impl TestTableExportMode {
+    fn is_matches(&self) -> bool {
+        *self == TestTableExportMode::Matches
+    }
+}
+
+impl From<TestTableExportMode> for TableExportMode {
+    fn from(mode: TestTableExportMode) -> TableExportMode {
+        match mode {
+            TestTableExportMode::Matches => TableExportMode::Matches,
+            TestTableExportMode::Frequencies(split) => TableExportMode::Frequencies(match split {
+                TestFrequencySplit::None => FrequencySplit::None,
+                TestFrequencySplit::Corpus => FrequencySplit::Corpus,
+                TestFrequencySplit::Document => FrequencySplit::Document,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+enum TestFrequencySplit {
+    None,
+    Corpus,
+    Document,
 }
 
 #[derive(Clone, Serialize)]
 enum TestTableExportColumn {
     Number,
     Data(TestExportData),
+    TypedData(TestExportData, ValueType),
 }
 
 #[derive(Clone, Serialize)]
@@ -713,6 +1991,8 @@ enum TestExportDataValue {
     MatchNodeAnno {
         anno_key: (&'static str, &'static str),
         index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aggregation: Option<TestAnnoAggregation>,
     },
     EdgeAnno {
         edge_type: (ExportableEdgeComponentType, &'static str),
@@ -720,10 +2000,65 @@ enum TestExportDataValue {
         source_node_index: usize,
         target_node_index: usize,
     },
+    TraversalAnno {
+        edge_type: (ExportableEdgeComponentType, &'static str),
+        traversal: TestTraversal,
+        item: TestTraversalItem,
+        anno_key: (&'static str, &'static str),
+    },
     QueryNodeProperty {
         query_node_property_key: TestQueryNodePropertyKey,
         match_node_index: usize,
     },
+    CoveredText {
+        index: usize,
+        segmentation: Option<&'static str>,
+        anno_key: TestAnnoKeyOrDefault,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        formatting: Option<TestTextFormatting>,
+    },
+    MatchNodePosition {
+        index: usize,
+        position: TestNodePosition,
+    },
+}
+
+#[derive(Clone, Serialize)]
+enum TestAnnoAggregation {
+    Distinct { separator: &'static str },
+    All { separator: &'static str },
+}
+
+#[derive(Clone, Serialize)]
+enum TestNodePosition {
+    TokenIndex,
+    FirstTokenIndex,
+    LastTokenIndex,
+    NodeName,
+}
+
+#[derive(Clone, Serialize)]
+enum TestTraversal {
+    Parent {
+        index: usize,
+    },
+    Ancestor {
+        index: usize,
+        distance: usize,
+    },
+    Children {
+        index: usize,
+    },
+    Path {
+        source_node_index: usize,
+        target_node_index: usize,
+    },
+}
+
+#[derive(Clone, Serialize)]
+enum TestTraversalItem {
+    Nodes,
+    Edges,
 }
 
 #[derive(Clone, Serialize)]
@@ -738,7 +2073,19 @@ struct TestExportDataText {
     anno_key: TestAnnoKeyOrDefault,
     left_context: usize,
     right_context: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context_unit: Option<(&'static str, &'static str)>,
     primary_node_indices: Option<&'static [usize]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formatting: Option<TestTextFormatting>,
+}
+
+#[derive(Clone, Serialize)]
+struct TestTextFormatting {
+    segment_separator: &'static str,
+    gap_marker: &'static str,
+    match_brackets: Option<(&'static str, &'static str)>,
+    restore_whitespace: bool,
 }
 
 #[derive(Clone, Serialize)]
@@ -751,79 +2098,200 @@ impl From<TestTableExportColumn> for TableExportColumn {
     fn from(column: TestTableExportColumn) -> Self {
         match column {
             TestTableExportColumn::Number => TableExportColumn::Number,
-            TestTableExportColumn::Data(data) => TableExportColumn::Data(match data {
-                TestExportData::Value(TestExportDataValue::CorpusAnno {
-                    anno_key: (ns, name),
-                }) => ExportData::Value(ExportDataValue::CorpusAnno {
-                    anno_key: AnnoKey {
-                        ns: ns.into(),
-                        name: name.into(),
-                    },
-                }),
-                TestExportData::Value(TestExportDataValue::DocumentAnno {
-                    anno_key: (ns, name),
-                }) => ExportData::Value(ExportDataValue::DocumentAnno {
-                    anno_key: AnnoKey {
-                        ns: ns.into(),
-                        name: name.into(),
-                    },
-                }),
-                TestExportData::Value(TestExportDataValue::MatchNodeAnno {
-                    anno_key: (ns, name),
-                    index,
-                }) => ExportData::Value(ExportDataValue::MatchNodeAnno {
-                    anno_key: AnnoKey {
-                        ns: ns.into(),
-                        name: name.into(),
-                    },
+            TestTableExportColumn::Data(data) => {
+                TableExportColumn::Data(data.into(), ValueType::Auto)
+            }
+            TestTableExportColumn::TypedData(data, value_type) => {
+                TableExportColumn::Data(data.into(), value_type)
+            }
+        }
+    }
+}
+
+impl From<TestExportData> for ExportData {
+    fn from(data: TestExportData) -> Self {
+        match data {
+            TestExportData::Value(TestExportDataValue::CorpusAnno {
+                anno_key: (ns, name),
+            }) => ExportData::Value(ExportDataValue::CorpusAnno {
+                anno_key: AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                },
+            }),
+            TestExportData::Value(TestExportDataValue::DocumentAnno {
+                anno_key: (ns, name),
+            }) => ExportData::Value(ExportDataValue::DocumentAnno {
+                anno_key: AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                },
+            }),
+            TestExportData::Value(TestExportDataValue::MatchNodeAnno {
+                anno_key: (ns, name),
+                index,
+                aggregation,
+            }) => ExportData::Value(ExportDataValue::MatchNodeAnno {
+                anno_key: AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                },
+                index,
+                aggregation: aggregation.map(Into::into).unwrap_or_default(),
+            }),
+            TestExportData::Value(TestExportDataValue::EdgeAnno {
+                edge_type: (ctype, component_name),
+                anno_key: (ns, anno_name),
+                source_node_index,
+                target_node_index,
+            }) => ExportData::Value(ExportDataValue::EdgeAnno {
+                edge_type: EdgeType {
+                    ctype,
+                    name: component_name.into(),
+                },
+                anno_key: AnnoKey {
+                    ns: ns.into(),
+                    name: anno_name.into(),
+                },
+                source_node_index,
+                target_node_index,
+            }),
+            TestExportData::Value(TestExportDataValue::TraversalAnno {
+                edge_type: (ctype, component_name),
+                traversal,
+                item,
+                anno_key: (ns, anno_name),
+            }) => ExportData::Value(ExportDataValue::TraversalAnno {
+                edge_type: EdgeType {
+                    ctype,
+                    name: component_name.into(),
+                },
+                traversal: traversal.into(),
+                item: item.into(),
+                anno_key: AnnoKey {
+                    ns: ns.into(),
+                    name: anno_name.into(),
+                },
+            }),
+            TestExportData::Value(TestExportDataValue::QueryNodeProperty {
+                query_node_property_key,
+                match_node_index,
+            }) => ExportData::Value(ExportDataValue::QueryNodeProperty {
+                query_node_property_key: query_node_property_key.into(),
+                match_node_index,
+            }),
+            TestExportData::Value(TestExportDataValue::CoveredText {
+                index,
+                segmentation,
+                anno_key,
+                formatting,
+            }) => ExportData::Value(ExportDataValue::CoveredText {
+                index,
+                segmentation: segmentation.map(|s| s.into()),
+                anno_key: anno_key.into(),
+                formatting: formatting.map(Into::into).unwrap_or_default(),
+            }),
+            TestExportData::Value(TestExportDataValue::MatchNodePosition { index, position }) => {
+                ExportData::Value(ExportDataValue::MatchNodePosition {
                     index,
+                    position: position.into(),
+                })
+            }
+            TestExportData::Text(TestExportDataText {
+                segmentation,
+                anno_key,
+                left_context,
+                right_context,
+                context_unit,
+                primary_node_indices,
+                formatting,
+            }) => ExportData::Text(ExportDataText {
+                segmentation: segmentation.map(|s| s.into()),
+                left_context,
+                right_context,
+                context_unit: context_unit.map(|(ns, name)| AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
                 }),
-                TestExportData::Value(TestExportDataValue::EdgeAnno {
-                    edge_type: (ctype, component_name),
-                    anno_key: (ns, anno_name),
-                    source_node_index,
-                    target_node_index,
-                }) => ExportData::Value(ExportDataValue::EdgeAnno {
-                    edge_type: EdgeType {
-                        ctype,
-                        name: component_name.into(),
-                    },
-                    anno_key: AnnoKey {
-                        ns: ns.into(),
-                        name: anno_name.into(),
-                    },
-                    source_node_index,
-                    target_node_index,
-                }),
-                TestExportData::Value(TestExportDataValue::QueryNodeProperty {
-                    query_node_property_key,
-                    match_node_index,
-                }) => ExportData::Value(ExportDataValue::QueryNodeProperty {
-                    query_node_property_key: query_node_property_key.into(),
-                    match_node_index,
-                }),
-                TestExportData::Text(TestExportDataText {
-                    segmentation,
-                    anno_key,
-                    left_context,
-                    right_context,
-                    primary_node_indices,
-                }) => ExportData::Text(ExportDataText {
-                    segmentation: segmentation.map(|s| s.into()),
-                    left_context,
-                    right_context,
-                    anno_key: match anno_key {
-                        TestAnnoKeyOrDefault::AnnoKey((ns, name)) => {
-                            AnnoKeyOrDefault::AnnoKey(AnnoKey {
-                                ns: ns.into(),
-                                name: name.into(),
-                            })
-                        }
-                        TestAnnoKeyOrDefault::Default => AnnoKeyOrDefault::Default,
-                    },
-                    primary_node_indices: primary_node_indices.map(Into::into),
-                }),
+                anno_key: anno_key.into(),
+                primary_node_indices: primary_node_indices.map(Into::into),
+                formatting: formatting.map(Into::into).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+impl From<TestTextFormatting> for TextFormatting {
+    fn from(test_formatting: TestTextFormatting) -> Self {
+        TextFormatting {
+            segment_separator: test_formatting.segment_separator.into(),
+            gap_marker: test_formatting.gap_marker.into(),
+            match_brackets: test_formatting
+                .match_brackets
+                .map(|(open, close)| (open.into(), close.into())),
+            restore_whitespace: test_formatting.restore_whitespace,
+        }
+    }
+}
+
+impl From<TestAnnoKeyOrDefault> for AnnoKeyOrDefault {
+    fn from(test_anno_key: TestAnnoKeyOrDefault) -> Self {
+        match test_anno_key {
+            TestAnnoKeyOrDefault::AnnoKey((ns, name)) => AnnoKeyOrDefault::AnnoKey(AnnoKey {
+                ns: ns.into(),
+                name: name.into(),
             }),
+            TestAnnoKeyOrDefault::Default => AnnoKeyOrDefault::Default,
+        }
+    }
+}
+
+impl From<TestAnnoAggregation> for AnnoAggregation {
+    fn from(test_anno_aggregation: TestAnnoAggregation) -> Self {
+        match test_anno_aggregation {
+            TestAnnoAggregation::Distinct { separator } => AnnoAggregation::Distinct {
+                separator: separator.into(),
+            },
+            TestAnnoAggregation::All { separator } => AnnoAggregation::All {
+                separator: separator.into(),
+            },
+        }
+    }
+}
+
+impl From<TestNodePosition> for NodePosition {
+    fn from(test_node_position: TestNodePosition) -> Self {
+        match test_node_position {
+            TestNodePosition::TokenIndex => NodePosition::TokenIndex,
+            TestNodePosition::FirstTokenIndex => NodePosition::FirstTokenIndex,
+            TestNodePosition::LastTokenIndex => NodePosition::LastTokenIndex,
+            TestNodePosition::NodeName => NodePosition::NodeName,
+        }
+    }
+}
+
+impl From<TestTraversal> for Traversal {
+    fn from(test_traversal: TestTraversal) -> Self {
+        match test_traversal {
+            TestTraversal::Parent { index } => Traversal::Parent { index },
+            TestTraversal::Ancestor { index, distance } => Traversal::Ancestor { index, distance },
+            TestTraversal::Children { index } => Traversal::Children { index },
+            TestTraversal::Path {
+                source_node_index,
+                target_node_index,
+            } => Traversal::Path {
+                source_node_index,
+                target_node_index,
+            },
+        }
+    }
+}
+
+impl From<TestTraversalItem> for TraversalItem {
+    fn from(test_traversal_item: TestTraversalItem) -> Self {
+        match test_traversal_item {
+            TestTraversalItem::Nodes => TraversalItem::Nodes,
+            TestTraversalItem::Edges => TraversalItem::Edges,
         }
     }
 }
@@ -2,8 +2,11 @@ use std::fs;
 use std::path::Path;
 
 use annimate_core::{
-    AnnoKey, AnnoKeyOrDefault, EdgeType, ExportableEdgeComponentType, Project, ProjectContext,
-    ProjectExportColumn, ProjectExportFormat, QueryLanguage, QueryNodePropertyKey,
+    AnnimateError, AnnoAggregation, AnnoKey, AnnoKeyOrDefault, CsvDelimiter, CsvDialect,
+    CsvLineTerminator, CsvQuoteStyle, EdgeType, ExportableEdgeComponentType, NodePosition, Project,
+    ProjectContext, ProjectExportColumn, ProjectExportFormat, ProjectFormatOptions, ProjectQuery,
+    ProjectTraversalKind, QueryLanguage, QueryNodePropertyKey, TraversalItem, ValueType,
+    XlsxOptions,
 };
 use serde::Serialize;
 
@@ -16,8 +19,11 @@ macro_rules! project_test {
             corpus_names: $corpus_names:expr,
             aql_query: $aql_query:expr,
             query_language: $query_language:expr,
+            $(query_timeout: $query_timeout:expr,)?
             export_columns: [$($export_column:expr),*$(,)?],
             export_format: $export_format:expr,
+            $(csv_dialect: $csv_dialect:expr,)?
+            $(xlsx_options: $xlsx_options:expr,)?
         }
     )*) => { $(
         #[test]
@@ -30,10 +36,13 @@ macro_rules! project_test {
                     use QueryLanguage::*;
                     $query_language
                 },
+                query_timeout: project_test!(@optional $($query_timeout)?),
                 export_columns: {
                     #[allow(unused_imports)]
                     use ExportableEdgeComponentType::*;
                     #[allow(unused_imports)]
+                    use NodePosition::*;
+                    #[allow(unused_imports)]
                     use QueryNodePropertyKey::*;
                     #[allow(unused_imports)]
                     use TestAnnoKeyOrDefault::*;
@@ -48,6 +57,16 @@ macro_rules! project_test {
                     use TestProjectExportFormat::*;
                     $export_format
                 },
+                csv_dialect: project_test!(@optional $({
+                    #[allow(unused_imports)]
+                    use CsvDelimiter::*;
+                    #[allow(unused_imports)]
+                    use CsvLineTerminator::*;
+                    #[allow(unused_imports)]
+                    use CsvQuoteStyle::*;
+                    $csv_dialect
+                })?),
+                xlsx_options: project_test!(@optional $($xlsx_options)?),
             };
             let project = test_project.clone().into();
             let project_debug = format!("{:?}", project);
@@ -72,6 +91,9 @@ macro_rules! project_test {
             assert_eq!(project_debug, format!("{:?}", loaded_project));
         }
     )* };
+
+    (@optional) => { None };
+    (@optional $value:expr) => { Some($value) };
 }
 
 project_test! {
@@ -140,25 +162,35 @@ project_test! {
             Number,
             AnnoCorpus {
                 anno_key: None,
+                value_type: None,
             },
             AnnoDocument {
                 anno_key: None,
+                value_type: None,
             },
             AnnoMatch {
                 anno_key: None,
                 node_index: None,
+                aggregation: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: None,
                 anno_key: None,
                 source_node_index: None,
                 target_node_index: None,
+                value_type: None,
             },
             MatchInContext {
                 segmentation: None,
                 anno_key: None,
                 context: Symmetric(20),
+                context_unit: None,
                 primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
             QueryNodeProperty {
                 query_node_property_key: Variable,
@@ -175,25 +207,35 @@ project_test! {
         export_columns: [
             AnnoCorpus {
                 anno_key: Some(("ns1", "anno1")),
+                value_type: None,
             },
             AnnoDocument {
                 anno_key: Some(("ns2", "anno2")),
+                value_type: None,
             },
             AnnoMatch {
                 anno_key: Some(("ns3", "anno3")),
                 node_index: None,
+                aggregation: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: None,
                 anno_key: Some(("ns4", "anno4")),
                 source_node_index: None,
                 target_node_index: None,
+                value_type: None,
             },
             MatchInContext {
                 segmentation: None,
                 anno_key: Some(AnnoKey(("ns5", "anno5"))),
                 context: Symmetric(20),
+                context_unit: None,
                 primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
         ],
         export_format: Csv,
@@ -209,18 +251,21 @@ project_test! {
                 anno_key: None,
                 source_node_index: None,
                 target_node_index: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: Some((Dominance, "comp1")),
                 anno_key: None,
                 source_node_index: None,
                 target_node_index: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: Some((Pointing, "comp2")),
                 anno_key: None,
                 source_node_index: None,
                 target_node_index: None,
+                value_type: None,
             },
         ],
         export_format: Csv,
@@ -235,7 +280,12 @@ project_test! {
                 segmentation: Some("Test Segmentation"),
                 anno_key: None,
                 context: Symmetric(20),
+                context_unit: None,
                 primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
         ],
         export_format: Csv,
@@ -250,7 +300,12 @@ project_test! {
                 segmentation: None,
                 anno_key: Some(Default),
                 context: Symmetric(20),
+                context_unit: None,
                 primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
         ],
         export_format: Csv,
@@ -265,7 +320,187 @@ project_test! {
                 segmentation: None,
                 anno_key: None,
                 context: Asymmetric { left: 5, right: 10 },
+                context_unit: None,
                 primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_with_context_unit: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            MatchInContext {
+                segmentation: None,
+                anno_key: None,
+                context: Symmetric(20),
+                context_unit: Some(("ns", "sentence")),
+                primary_node_indices: &[],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_with_text_formatting: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            MatchInContext {
+                segmentation: None,
+                anno_key: None,
+                context: Symmetric(20),
+                context_unit: None,
+                primary_node_indices: &[],
+                segment_separator: Some(""),
+                gap_marker: Some("…"),
+                match_brackets: Some(("[[", "]]")),
+                restore_whitespace: true,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_covered_text: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            CoveredText {
+                node_index: None,
+                segmentation: None,
+                anno_key: None,
+                segment_separator: None,
+                restore_whitespace: false,
+            },
+            CoveredText {
+                node_index: Some(1),
+                segmentation: Some("Test Segmentation"),
+                anno_key: Some(AnnoKey(("ns1", "anno1"))),
+                segment_separator: None,
+                restore_whitespace: false,
+            },
+            CoveredText {
+                node_index: Some(2),
+                segmentation: Some(""),
+                anno_key: Some(Default),
+                segment_separator: Some(""),
+                restore_whitespace: true,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_traversal_anno: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            TraversalAnno {
+                edge_type: Some((Dominance, "edge")),
+                traversal: TestProjectTraversalKind::Parent,
+                distance: None,
+                node_index: Some(0),
+                target_node_index: None,
+                item: TraversalItem::Nodes,
+                anno_key: Some(("tiger", "cat")),
+                value_type: None,
+            },
+            TraversalAnno {
+                edge_type: Some((Dominance, "edge")),
+                traversal: TestProjectTraversalKind::Ancestor,
+                distance: Some(2),
+                node_index: Some(0),
+                target_node_index: None,
+                item: TraversalItem::Edges,
+                anno_key: Some(("tiger", "func")),
+                value_type: None,
+            },
+            TraversalAnno {
+                edge_type: Some((Pointing, "dep")),
+                traversal: TestProjectTraversalKind::Children,
+                distance: None,
+                node_index: Some(1),
+                target_node_index: None,
+                item: TraversalItem::Nodes,
+                anno_key: Some(("annis", "tok")),
+                value_type: None,
+            },
+            TraversalAnno {
+                edge_type: Some((Dominance, "edge")),
+                traversal: TestProjectTraversalKind::Path,
+                distance: None,
+                node_index: Some(0),
+                target_node_index: Some(1),
+                item: TraversalItem::Edges,
+                anno_key: Some(("tiger", "func")),
+                value_type: None,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_match_position: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            MatchPosition {
+                node_index: None,
+                position: TokenIndex,
+            },
+            MatchPosition {
+                node_index: Some(1),
+                position: FirstTokenIndex,
+            },
+            MatchPosition {
+                node_index: Some(2),
+                position: LastTokenIndex,
+            },
+            MatchPosition {
+                node_index: Some(3),
+                position: NodeName,
+            },
+        ],
+        export_format: Csv,
+    }
+    with_export_columns_with_aggregation: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            AnnoMatch {
+                anno_key: Some(("tiger", "pos")),
+                node_index: Some(0),
+                aggregation: Some(AnnoAggregation::First),
+                value_type: None,
+            },
+            AnnoMatch {
+                anno_key: Some(("tiger", "pos")),
+                node_index: Some(0),
+                aggregation: Some(AnnoAggregation::Distinct {
+                    separator: ", ".into(),
+                }),
+                value_type: None,
+            },
+            AnnoMatch {
+                anno_key: Some(("tiger", "pos")),
+                node_index: Some(0),
+                aggregation: Some(AnnoAggregation::All {
+                    separator: " ".into(),
+                }),
+                value_type: None,
             },
         ],
         export_format: Csv,
@@ -279,23 +514,71 @@ project_test! {
             AnnoMatch {
                 anno_key: None,
                 node_index: Some(0),
+                aggregation: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: None,
                 anno_key: None,
                 source_node_index: Some(1),
                 target_node_index: Some(2),
+                value_type: None,
             },
             MatchInContext {
                 segmentation: None,
                 anno_key: None,
                 context: Symmetric(20),
+                context_unit: None,
                 primary_node_indices: &[3, 4, 5],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
             QueryNodeProperty { query_node_property_key: Variable, match_node_index: Some(6) },
         ],
         export_format: Csv,
     }
+    with_export_columns_with_value_types: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [
+            AnnoCorpus {
+                anno_key: Some(("ns1", "anno1")),
+                value_type: Some(ValueType::Date),
+            },
+            AnnoDocument {
+                anno_key: Some(("ns2", "anno2")),
+                value_type: Some(ValueType::Auto),
+            },
+            AnnoMatch {
+                anno_key: Some(("ns3", "anno3")),
+                node_index: Some(0),
+                aggregation: None,
+                value_type: Some(ValueType::Integer),
+            },
+            AnnoEdge {
+                edge_type: Some((Dominance, "edge")),
+                anno_key: Some(("ns4", "anno4")),
+                source_node_index: Some(0),
+                target_node_index: Some(1),
+                value_type: Some(ValueType::Float),
+            },
+            TraversalAnno {
+                edge_type: Some((Dominance, "edge")),
+                traversal: TestProjectTraversalKind::Parent,
+                distance: None,
+                node_index: Some(0),
+                target_node_index: None,
+                item: TraversalItem::Nodes,
+                anno_key: Some(("ns5", "anno5")),
+                value_type: Some(ValueType::Text),
+            },
+        ],
+        export_format: Xlsx,
+    }
     with_export_format_xlsx: {
         corpus_set: None,
         corpus_names: [],
@@ -304,6 +587,22 @@ project_test! {
         export_columns: [],
         export_format: Xlsx,
     }
+    with_export_format_html: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [],
+        export_format: Html,
+    }
+    with_export_format_ods: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [],
+        export_format: Ods,
+    }
     full: {
         corpus_set: Some("Test Corpus Set"),
         corpus_names: ["Test Corpus 1", "Test Corpus 2"],
@@ -313,30 +612,261 @@ project_test! {
             Number,
             AnnoCorpus {
                 anno_key: Some(("ns1", "anno1")),
+                value_type: None,
             },
             AnnoDocument {
                 anno_key: Some(("ns2", "anno2")),
+                value_type: None,
             },
             AnnoMatch {
                 anno_key: Some(("ns3", "anno3")),
                 node_index: Some(0),
+                aggregation: None,
+                value_type: None,
             },
             AnnoEdge {
                 edge_type: Some((Dominance, "comp1")),
                 anno_key: Some(("ns4", "anno4")),
                 source_node_index: Some(1),
                 target_node_index: Some(2),
+                value_type: None,
             },
             MatchInContext {
                 segmentation: Some("Test Segmentation"),
                 anno_key: Some(AnnoKey(("ns5", "anno5"))),
                 context: Asymmetric { left: 5, right: 10 },
+                context_unit: None,
                 primary_node_indices: &[3, 4, 5],
+                segment_separator: None,
+                gap_marker: None,
+                match_brackets: None,
+                restore_whitespace: false,
             },
             QueryNodeProperty { query_node_property_key: Fragment, match_node_index: Some(6) }
         ],
         export_format: Xlsx,
     }
+    with_xlsx_options: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [],
+        export_format: Xlsx,
+        xlsx_options: TestXlsxOptions {
+            counts_sheet: true,
+            kwic_cells: true,
+        },
+    }
+    with_query_timeout: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        query_timeout: 30,
+        export_columns: [],
+        export_format: Csv,
+    }
+    with_csv_dialect: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [],
+        export_format: Csv,
+        csv_dialect: TestCsvDialect {
+            delimiter: Semicolon,
+            quote_style: Always,
+            line_terminator: Lf,
+            bom: true,
+            escape_formulas: true,
+        },
+    }
+    with_csv_dialect_default: {
+        corpus_set: None,
+        corpus_names: [],
+        aql_query: "",
+        query_language: AQL,
+        export_columns: [],
+        export_format: Csv,
+        csv_dialect: TestCsvDialect {
+            delimiter: Comma,
+            quote_style: Necessary,
+            line_terminator: Crlf,
+            bom: false,
+            escape_formulas: false,
+        },
+    }
+}
+
+#[test]
+fn with_multiple_queries() {
+    let project = Project {
+        corpus_set: Some("Test Corpus Set".into()),
+        corpus_names: vec!["Test Corpus 1".into(), "Test Corpus 2".into()],
+        queries: vec![
+            ProjectQuery {
+                name: "nouns".into(),
+                aql_query: "pos=\"NN\"".into(),
+                query_language: QueryLanguage::AQL,
+                export_columns: vec![
+                    ProjectExportColumn::Number,
+                    ProjectExportColumn::AnnoMatch {
+                        anno_key: Some(AnnoKey {
+                            ns: "".into(),
+                            name: "lemma".into(),
+                        }),
+                        node_index: Some(0),
+                        aggregation: None,
+                        value_type: None,
+                    },
+                ],
+            },
+            ProjectQuery {
+                name: "".into(),
+                aql_query: "tok".into(),
+                query_language: QueryLanguage::AQLQuirksV3,
+                export_columns: vec![],
+            },
+        ],
+        query_timeout: None,
+        export_format: ProjectExportFormat::Xlsx,
+        output_path: None,
+        format_options: ProjectFormatOptions::default(),
+    };
+    let project_debug = format!("{project:?}");
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("with_multiple_queries.anmt");
+    let _ = fs::remove_file(&project_file);
+
+    annimate_core::save_project(project, &project_file).unwrap();
+
+    let output = fs::read_to_string(&project_file).unwrap();
+    insta::with_settings!(
+        {
+            omit_expression => true,
+        },
+        { insta::assert_snapshot!(output) }
+    );
+
+    let loaded_project = annimate_core::load_project(&project_file).unwrap();
+
+    assert_eq!(project_debug, format!("{loaded_project:?}"));
+
+    let export_configs = loaded_project.into_export_configs().unwrap();
+
+    assert_eq!(
+        export_configs
+            .iter()
+            .map(|c| (c.name.as_str(), c.config.aql_query.as_str()))
+            .collect::<Vec<_>>(),
+        [("nouns", "pos=\"NN\""), ("", "tok")]
+    );
+}
+
+#[test]
+fn with_output_path() {
+    let project = Project {
+        corpus_set: None,
+        corpus_names: vec![],
+        queries: vec![],
+        query_timeout: None,
+        export_format: ProjectExportFormat::Csv,
+        output_path: Some(Path::new(OUTPUT_DIR).join("exports").join("matches.csv")),
+        format_options: ProjectFormatOptions::default(),
+    };
+    let project_debug = format!("{project:?}");
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("with_output_path.anmt");
+    let _ = fs::remove_file(&project_file);
+
+    annimate_core::save_project(project, &project_file).unwrap();
+
+    let output = fs::read_to_string(&project_file).unwrap();
+    insta::with_settings!(
+        {
+            omit_expression => true,
+        },
+        { insta::assert_snapshot!(output) }
+    );
+
+    let loaded_project = annimate_core::load_project(&project_file).unwrap();
+
+    assert_eq!(project_debug, format!("{loaded_project:?}"));
+}
+
+#[test]
+fn column_toml_round_trip() {
+    let columns = [
+        ProjectExportColumn::CoveredText {
+            node_index: Some(1),
+            segmentation: None,
+            anno_key: Some(AnnoKeyOrDefault::Default),
+            segment_separator: Some("".into()),
+            restore_whitespace: true,
+        },
+        ProjectExportColumn::MatchPosition {
+            node_index: Some(0),
+            position: NodePosition::TokenIndex,
+        },
+        ProjectExportColumn::TraversalAnno {
+            edge_type: Some(EdgeType {
+                ctype: ExportableEdgeComponentType::Dominance,
+                name: "edge".into(),
+            }),
+            traversal: ProjectTraversalKind::Ancestor,
+            distance: Some(2),
+            node_index: Some(0),
+            target_node_index: None,
+            item: TraversalItem::Nodes,
+            anno_key: Some(AnnoKey {
+                ns: "tiger".into(),
+                name: "cat".into(),
+            }),
+            value_type: Some(ValueType::Text),
+        },
+    ];
+
+    for column in columns {
+        let toml = column.to_toml();
+        let loaded_column = ProjectExportColumn::from_toml(&toml).unwrap();
+
+        assert_eq!(
+            format!("{loaded_column:?}"),
+            format!("{column:?}"),
+            "{toml}"
+        );
+    }
+}
+
+#[test]
+fn query_toml_round_trip() {
+    let query = ProjectQuery {
+        name: "nouns".into(),
+        aql_query: "pos=\"NN\"".into(),
+        query_language: QueryLanguage::AQLQuirksV3,
+        export_columns: vec![
+            ProjectExportColumn::Number,
+            ProjectExportColumn::MatchPosition {
+                node_index: Some(0),
+                position: NodePosition::TokenIndex,
+            },
+        ],
+    };
+
+    let toml = query.to_toml();
+    let loaded_query = ProjectQuery::from_toml(&toml).unwrap();
+
+    assert_eq!(format!("{loaded_query:?}"), format!("{query:?}"), "{toml}");
+}
+
+#[test]
+fn column_from_invalid_toml() {
+    let result = ProjectExportColumn::from_toml("type = \"unknown\"");
+
+    assert!(matches!(result, Err(AnnimateError::FailedToReadProject(_))));
 }
 
 #[test]
@@ -363,14 +893,104 @@ fn migrate_from_v1() {
     );
 }
 
+#[test]
+fn migrate_from_v2() {
+    // A v2 file has no `format-options` table and a single query without a name.
+    // Loading it should use the default options and move the query into `queries`.
+    let v2_content = include_str!("data/project_v2.anmt");
+
+    fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+    let project_file = Path::new(OUTPUT_DIR).join("migrate_from_v2.anmt");
+    fs::write(&project_file, v2_content).unwrap();
+
+    let project = annimate_core::load_project(&project_file).unwrap();
+
+    let output_file = Path::new(OUTPUT_DIR).join("migrate_from_v2_output.anmt");
+    annimate_core::save_project(project, &output_file).unwrap();
+
+    let output = fs::read_to_string(&output_file).unwrap();
+    insta::with_settings!(
+        {
+            omit_expression => true,
+        },
+        { insta::assert_snapshot!(output) }
+    );
+}
+
+macro_rules! invalid_project_test {
+    ($(
+        $name:ident: $content:expr,
+    )*) => {
+        $(
+            #[test]
+            fn $name() {
+                fs::create_dir_all(Path::new(OUTPUT_DIR)).unwrap();
+                let project_file =
+                    Path::new(OUTPUT_DIR).join(concat!(stringify!($name), ".anmt"));
+                fs::write(&project_file, $content).unwrap();
+
+                let result = annimate_core::load_project(&project_file);
+
+                assert!(
+                    matches!(result, Err(AnnimateError::FailedToReadProject(_))),
+                    "{result:?}"
+                );
+            }
+        )*
+    };
+}
+
+invalid_project_test! {
+    v3_with_v2_layout: r#"
+format-version = 3
+
+[project]
+corpora = ["Test Corpus"]
+query = "Test AQL query"
+query-language = "aql"
+export-format = "csv"
+
+[[project.columns]]
+type = "number"
+"#,
+    v2_with_v3_column: r#"
+format-version = 2
+
+[project]
+corpora = ["Test Corpus"]
+query = "Test AQL query"
+query-language = "aql"
+export-format = "csv"
+
+[[project.columns]]
+type = "covered-text"
+node-index = 0
+"#,
+    v2_with_v3_export_format: r#"
+format-version = 2
+
+[project]
+corpora = ["Test Corpus"]
+query = "Test AQL query"
+query-language = "aql"
+export-format = "html"
+"#,
+}
+
 #[derive(Clone, Serialize)]
 struct TestProject {
     corpus_set: Option<&'static str>,
     corpus_names: &'static [&'static str],
     aql_query: &'static str,
     query_language: QueryLanguage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query_timeout: Option<u32>,
     export_columns: Vec<TestProjectExportColumn>,
     export_format: TestProjectExportFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    csv_dialect: Option<TestCsvDialect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xlsx_options: Option<TestXlsxOptions>,
 }
 
 #[derive(Clone, Serialize)]
@@ -378,30 +998,74 @@ enum TestProjectExportColumn {
     Number,
     AnnoCorpus {
         anno_key: Option<(&'static str, &'static str)>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoDocument {
         anno_key: Option<(&'static str, &'static str)>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoMatch {
         anno_key: Option<(&'static str, &'static str)>,
         node_index: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aggregation: Option<AnnoAggregation>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoEdge {
         edge_type: Option<(ExportableEdgeComponentType, &'static str)>,
         anno_key: Option<(&'static str, &'static str)>,
         source_node_index: Option<u32>,
         target_node_index: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     MatchInContext {
         segmentation: Option<&'static str>,
         anno_key: Option<TestAnnoKeyOrDefault>,
         context: TestProjectContext,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context_unit: Option<(&'static str, &'static str)>,
         primary_node_indices: &'static [u32],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        segment_separator: Option<&'static str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gap_marker: Option<&'static str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        match_brackets: Option<(&'static str, &'static str)>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        restore_whitespace: bool,
     },
     QueryNodeProperty {
         query_node_property_key: QueryNodePropertyKey,
         match_node_index: Option<u32>,
     },
+    CoveredText {
+        node_index: Option<u32>,
+        segmentation: Option<&'static str>,
+        anno_key: Option<TestAnnoKeyOrDefault>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        segment_separator: Option<&'static str>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        restore_whitespace: bool,
+    },
+    MatchPosition {
+        node_index: Option<u32>,
+        position: NodePosition,
+    },
+    TraversalAnno {
+        edge_type: Option<(ExportableEdgeComponentType, &'static str)>,
+        traversal: TestProjectTraversalKind,
+        distance: Option<u32>,
+        node_index: Option<u32>,
+        target_node_index: Option<u32>,
+        item: TraversalItem,
+        anno_key: Option<(&'static str, &'static str)>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
+    },
 }
 
 #[derive(Clone, Serialize)]
@@ -416,10 +1080,35 @@ enum TestProjectContext {
     Asymmetric { left: u32, right: u32 },
 }
 
+#[derive(Clone, Serialize)]
+enum TestProjectTraversalKind {
+    Parent,
+    Ancestor,
+    Children,
+    Path,
+}
+
 #[derive(Clone, Serialize)]
 enum TestProjectExportFormat {
     Csv,
     Xlsx,
+    Html,
+    Ods,
+}
+
+#[derive(Clone, Serialize)]
+struct TestCsvDialect {
+    delimiter: CsvDelimiter,
+    quote_style: CsvQuoteStyle,
+    line_terminator: CsvLineTerminator,
+    bom: bool,
+    escape_formulas: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct TestXlsxOptions {
+    counts_sheet: bool,
+    kwic_cells: bool,
 }
 
 impl From<TestProject> for Project {
@@ -431,14 +1120,26 @@ impl From<TestProject> for Project {
                 .iter()
                 .map(ToString::to_string)
                 .collect(),
-            aql_query: test_project.aql_query.to_string(),
-            query_language: test_project.query_language,
-            export_columns: test_project
-                .export_columns
-                .into_iter()
-                .map(Into::into)
-                .collect(),
+            queries: vec![ProjectQuery {
+                name: String::new(),
+                aql_query: test_project.aql_query.to_string(),
+                query_language: test_project.query_language,
+                export_columns: test_project
+                    .export_columns
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            }],
+            query_timeout: test_project.query_timeout,
             export_format: test_project.export_format.into(),
+            output_path: None,
+            format_options: ProjectFormatOptions {
+                csv: test_project.csv_dialect.map(Into::into).unwrap_or_default(),
+                xlsx: test_project
+                    .xlsx_options
+                    .map(Into::into)
+                    .unwrap_or_default(),
+            },
         }
     }
 }
@@ -447,35 +1148,46 @@ impl From<TestProjectExportColumn> for ProjectExportColumn {
     fn from(test_export_column: TestProjectExportColumn) -> Self {
         match test_export_column {
             TestProjectExportColumn::Number => ProjectExportColumn::Number,
-            TestProjectExportColumn::AnnoCorpus { anno_key } => ProjectExportColumn::AnnoCorpus {
+            TestProjectExportColumn::AnnoCorpus {
+                anno_key,
+                value_type,
+            } => ProjectExportColumn::AnnoCorpus {
                 anno_key: anno_key.map(|(ns, name)| AnnoKey {
                     ns: ns.into(),
                     name: name.into(),
                 }),
+                value_type,
+            },
+            TestProjectExportColumn::AnnoDocument {
+                anno_key,
+                value_type,
+            } => ProjectExportColumn::AnnoDocument {
+                anno_key: anno_key.map(|(ns, name)| AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                }),
+                value_type,
             },
-            TestProjectExportColumn::AnnoDocument { anno_key } => {
-                ProjectExportColumn::AnnoDocument {
-                    anno_key: anno_key.map(|(ns, name)| AnnoKey {
-                        ns: ns.into(),
-                        name: name.into(),
-                    }),
-                }
-            }
             TestProjectExportColumn::AnnoMatch {
                 anno_key,
                 node_index,
+                aggregation,
+                value_type,
             } => ProjectExportColumn::AnnoMatch {
                 anno_key: anno_key.map(|(ns, name)| AnnoKey {
                     ns: ns.into(),
                     name: name.into(),
                 }),
                 node_index,
+                aggregation,
+                value_type,
             },
             TestProjectExportColumn::AnnoEdge {
                 edge_type,
                 anno_key,
                 source_node_index,
                 target_node_index,
+                value_type,
             } => ProjectExportColumn::AnnoEdge {
                 edge_type: edge_type.map(|(ctype, name)| EdgeType {
                     ctype,
@@ -487,25 +1199,31 @@ impl From<TestProjectExportColumn> for ProjectExportColumn {
                 }),
                 source_node_index,
                 target_node_index,
+                value_type,
             },
             TestProjectExportColumn::MatchInContext {
                 segmentation,
                 context,
+                context_unit,
                 anno_key,
                 primary_node_indices,
+                segment_separator,
+                gap_marker,
+                match_brackets,
+                restore_whitespace,
             } => ProjectExportColumn::MatchInContext {
                 segmentation: segmentation.map(|s| s.to_string()),
-                anno_key: anno_key.map(|anno_key| match anno_key {
-                    TestAnnoKeyOrDefault::AnnoKey((ns, name)) => {
-                        AnnoKeyOrDefault::AnnoKey(AnnoKey {
-                            ns: ns.into(),
-                            name: name.into(),
-                        })
-                    }
-                    TestAnnoKeyOrDefault::Default => AnnoKeyOrDefault::Default,
-                }),
+                anno_key: anno_key.map(Into::into),
                 context: context.into(),
+                context_unit: context_unit.map(|(ns, name)| AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                }),
                 primary_node_indices: primary_node_indices.into(),
+                segment_separator: segment_separator.map(Into::into),
+                gap_marker: gap_marker.map(Into::into),
+                match_brackets: match_brackets.map(|(open, close)| (open.into(), close.into())),
+                restore_whitespace,
             },
             TestProjectExportColumn::QueryNodeProperty {
                 query_node_property_key,
@@ -514,6 +1232,74 @@ impl From<TestProjectExportColumn> for ProjectExportColumn {
                 query_node_property_key,
                 match_node_index,
             },
+            TestProjectExportColumn::CoveredText {
+                node_index,
+                segmentation,
+                anno_key,
+                segment_separator,
+                restore_whitespace,
+            } => ProjectExportColumn::CoveredText {
+                node_index,
+                segmentation: segmentation.map(|s| s.to_string()),
+                anno_key: anno_key.map(Into::into),
+                segment_separator: segment_separator.map(|s| s.to_string()),
+                restore_whitespace,
+            },
+            TestProjectExportColumn::MatchPosition {
+                node_index,
+                position,
+            } => ProjectExportColumn::MatchPosition {
+                node_index,
+                position,
+            },
+            TestProjectExportColumn::TraversalAnno {
+                edge_type,
+                traversal,
+                distance,
+                node_index,
+                target_node_index,
+                item,
+                anno_key,
+                value_type,
+            } => ProjectExportColumn::TraversalAnno {
+                edge_type: edge_type.map(|(ctype, name)| EdgeType {
+                    ctype,
+                    name: name.into(),
+                }),
+                traversal: traversal.into(),
+                distance,
+                node_index,
+                target_node_index,
+                item,
+                anno_key: anno_key.map(|(ns, name)| AnnoKey {
+                    ns: ns.into(),
+                    name: name.into(),
+                }),
+                value_type,
+            },
+        }
+    }
+}
+
+impl From<TestProjectTraversalKind> for ProjectTraversalKind {
+    fn from(test_traversal_kind: TestProjectTraversalKind) -> Self {
+        match test_traversal_kind {
+            TestProjectTraversalKind::Parent => ProjectTraversalKind::Parent,
+            TestProjectTraversalKind::Ancestor => ProjectTraversalKind::Ancestor,
+            TestProjectTraversalKind::Children => ProjectTraversalKind::Children,
+            TestProjectTraversalKind::Path => ProjectTraversalKind::Path,
+        }
+    }
+}
+
+impl From<TestAnnoKeyOrDefault> for AnnoKeyOrDefault {
+    fn from(test_anno_key: TestAnnoKeyOrDefault) -> Self {
+        match test_anno_key {
+            TestAnnoKeyOrDefault::AnnoKey((ns, name)) => AnnoKeyOrDefault::AnnoKey(AnnoKey {
+                ns: ns.into(),
+                name: name.into(),
+            }),
+            TestAnnoKeyOrDefault::Default => AnnoKeyOrDefault::Default,
         }
     }
 }
@@ -534,6 +1320,29 @@ impl From<TestProjectExportFormat> for ProjectExportFormat {
         match test_export_format {
             TestProjectExportFormat::Csv => ProjectExportFormat::Csv,
             TestProjectExportFormat::Xlsx => ProjectExportFormat::Xlsx,
+            TestProjectExportFormat::Html => ProjectExportFormat::Html,
+            TestProjectExportFormat::Ods => ProjectExportFormat::Ods,
+        }
+    }
+}
+
+impl From<TestXlsxOptions> for XlsxOptions {
+    fn from(test_xlsx_options: TestXlsxOptions) -> Self {
+        XlsxOptions {
+            counts_sheet: test_xlsx_options.counts_sheet,
+            kwic_cells: test_xlsx_options.kwic_cells,
+        }
+    }
+}
+
+impl From<TestCsvDialect> for CsvDialect {
+    fn from(test_csv_dialect: TestCsvDialect) -> Self {
+        CsvDialect {
+            delimiter: test_csv_dialect.delimiter,
+            quote_style: test_csv_dialect.quote_style,
+            line_terminator: test_csv_dialect.line_terminator,
+            bom: test_csv_dialect.bom,
+            escape_formulas: test_csv_dialect.escape_formulas,
         }
     }
 }
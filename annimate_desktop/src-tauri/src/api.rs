@@ -1,15 +1,17 @@
 #![allow(clippy::too_many_arguments)]
 
+use std::iter;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use annimate_core::{
-    AnnoKey, AnnoKeyOrDefault, Corpora, CsvExportConfig, EdgeType, ExportConfig, ExportData,
-    ExportDataText, ExportDataValue, ExportStatusEvent, ExportableEdgeType, ExportableNodeAnnoKeys,
-    ImportStatusEvent, QueryAnalysisResult, QueryLanguage, QueryNode, QueryNodePropertyKey,
-    QueryNodes, TableExportColumn, XlsxExportConfig,
+    AnnoAggregation, AnnoKey, AnnoKeyOrDefault, Corpora, CountStatusEvent, CsvDelimiter,
+    CsvLineTerminator, CsvQuoteStyle, EdgeType, ExportConfig, ExportStatusEvent,
+    ExportableEdgeType, ExportableNodeAnnoKeys, ImportStatusEvent, MatchCounts,
+    QueryAnalysisResult, QueryLanguage, QueryNode, QueryNodePropertyKey, QueryNodes, ValueType,
 };
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -71,6 +73,57 @@ pub(crate) async fn delete_corpus_set(
     .await?
 }
 
+#[tauri::command]
+pub(crate) async fn count_matches(
+    state: tauri::State<'_, AppState>,
+    event_channel: Channel<CountStatusEvent>,
+    window: WebviewWindow,
+    corpus_names: Vec<String>,
+    aql_query: String,
+    query_language: QueryLanguage,
+    by_document: bool,
+    query_timeout: Option<u32>,
+) -> Result<MatchCounts, Error> {
+    // Suspend preloading to avoid slowdown due to parallel loads
+    let preloader = state.preloader.wait().await.clone()?;
+    let _guard = preloader.suspend();
+
+    let storage = state.storage.wait().await.clone()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let cancel_requested = Arc::new(AtomicBool::new(false));
+
+        let _guard = EventHandlerGuard::new(
+            &window,
+            window.once("count_cancel_requested", {
+                let cancel_requested = Arc::clone(&cancel_requested);
+                move |_| {
+                    cancel_requested.store(true, Ordering::Relaxed);
+                }
+            }),
+        );
+
+        Ok(storage.count_matches(
+            &corpus_names,
+            &aql_query,
+            query_language,
+            by_document,
+            query_timeout.map(|secs| Duration::from_secs(secs.into())),
+            {
+                let cancel_requested = Arc::clone(&cancel_requested);
+                move |status_event| {
+                    if event_channel.send(status_event).is_err() {
+                        // Defensive: `send` shouldn't fail in practice, but cancel just in case
+                        cancel_requested.store(true, Ordering::Relaxed);
+                    }
+                }
+            },
+            || cancel_requested.load(Ordering::Relaxed),
+        )?)
+    })
+    .await?
+}
+
 #[tauri::command]
 pub(crate) async fn export_matches(
     state: tauri::State<'_, AppState>,
@@ -252,11 +305,25 @@ pub(crate) async fn load_project(
     let project =
         tauri::async_runtime::spawn_blocking(|| annimate_core::load_project(input_file)).await??;
 
+    // Only the first query can be configured in the app, the others are kept as they are
+    let mut queries = project.queries.into_iter();
+
+    let query = queries
+        .next()
+        .unwrap_or_else(|| annimate_core::ProjectQuery {
+            name: String::new(),
+            aql_query: String::new(),
+            query_language: QueryLanguage::AQL,
+            export_columns: Vec::new(),
+        });
+
+    let other_queries = queries.map(|query| query.to_toml()).collect();
+
     let query_nodes: Vec<Vec<QueryNode>> = {
         let storage = state.storage.wait().await.clone()?;
 
         storage
-            .query_nodes(&project.aql_query, project.query_language)?
+            .query_nodes(&query.aql_query, query.query_language)?
             .valid()
             .map(|query_nodes| query_nodes.into())
             .unwrap_or_default()
@@ -278,35 +345,52 @@ pub(crate) async fn load_project(
 
     Ok(Project {
         corpus_set: project.corpus_set.unwrap_or_default(),
+        output_path: project.output_path,
+        query_name: query.name,
+        other_queries,
         spec: ExportSpec {
             corpus_names: project.corpus_names,
-            aql_query: project.aql_query,
-            query_language: project.query_language,
-            export_columns: project
+            aql_query: query.aql_query,
+            query_language: query.query_language,
+            query_timeout: project.query_timeout,
+            export_columns: query
                 .export_columns
                 .into_iter()
                 .map(|c| {
-                    Ok::<_, ConversionError>(match c {
+                    Ok::<_, Error>(match c {
                         annimate_core::ProjectExportColumn::Number => ExportColumn::Number,
-                        annimate_core::ProjectExportColumn::AnnoCorpus { anno_key } => {
-                            ExportColumn::AnnoCorpus { anno_key }
-                        }
-                        annimate_core::ProjectExportColumn::AnnoDocument { anno_key } => {
-                            ExportColumn::AnnoDocument { anno_key }
-                        }
+                        annimate_core::ProjectExportColumn::AnnoCorpus {
+                            anno_key,
+                            value_type,
+                        } => ExportColumn::AnnoCorpus {
+                            anno_key,
+                            value_type,
+                        },
+                        annimate_core::ProjectExportColumn::AnnoDocument {
+                            anno_key,
+                            value_type,
+                        } => ExportColumn::AnnoDocument {
+                            anno_key,
+                            value_type,
+                        },
                         annimate_core::ProjectExportColumn::AnnoMatch {
                             anno_key,
                             node_index,
+                            aggregation,
+                            value_type,
                         } => ExportColumn::AnnoMatch {
                             anno_key_or_query_node_property_key: anno_key
                                 .map(|key| AnnoKeyOrQueryNodePropertyKey::AnnoKey { key }),
                             node_ref: node_index.map(try_get_query_node_ref).transpose()?,
+                            aggregation,
+                            value_type,
                         },
                         annimate_core::ProjectExportColumn::AnnoEdge {
                             edge_type,
                             anno_key,
                             source_node_index,
                             target_node_index,
+                            value_type,
                         } => ExportColumn::AnnoEdge {
                             edge_type,
                             anno_key,
@@ -316,12 +400,18 @@ pub(crate) async fn load_project(
                             target_node_ref: target_node_index
                                 .map(try_get_query_node_ref)
                                 .transpose()?,
+                            value_type,
                         },
                         annimate_core::ProjectExportColumn::MatchInContext {
                             segmentation,
                             anno_key,
                             context,
+                            context_unit,
                             primary_node_indices,
+                            segment_separator,
+                            gap_marker,
+                            match_brackets,
+                            restore_whitespace,
                         } => {
                             let (context, context_right_override) = match context {
                                 annimate_core::ProjectContext::Symmetric(size) => {
@@ -347,9 +437,14 @@ pub(crate) async fn load_project(
                                 anno_key,
                                 context,
                                 context_right_override,
+                                context_unit,
                                 primary_node_refs,
                                 secondary_node_refs,
                                 segmentation,
+                                segment_separator,
+                                gap_marker,
+                                match_brackets,
+                                restore_whitespace,
                             }
                         }
                         annimate_core::ProjectExportColumn::QueryNodeProperty {
@@ -362,14 +457,30 @@ pub(crate) async fn load_project(
                                 },
                             ),
                             node_ref: match_node_index.map(try_get_query_node_ref).transpose()?,
+                            aggregation: None,
+                            value_type: None,
                         },
+                        column @ annimate_core::ProjectExportColumn::CoveredText { .. } => {
+                            ExportColumn::unsupported("covered-text", &column)
+                        }
+                        column @ annimate_core::ProjectExportColumn::MatchPosition { .. } => {
+                            ExportColumn::unsupported("match-position", &column)
+                        }
+                        column @ annimate_core::ProjectExportColumn::TraversalAnno { .. } => {
+                            ExportColumn::unsupported("traversal-annotation", &column)
+                        }
                     })
                 })
                 .try_collect()?,
             export_format: match project.export_format {
                 annimate_core::ProjectExportFormat::Csv => ExportFormat::Csv,
                 annimate_core::ProjectExportFormat::Xlsx => ExportFormat::Xlsx,
+                annimate_core::ProjectExportFormat::Jsonl => ExportFormat::Jsonl,
+                annimate_core::ProjectExportFormat::Html => ExportFormat::Html,
+                annimate_core::ProjectExportFormat::Ods => ExportFormat::Ods,
             },
+            csv_dialect: project.format_options.csv.into(),
+            xlsx_options: project.format_options.xlsx.into(),
         },
     })
 }
@@ -390,96 +501,7 @@ pub(crate) async fn rename_corpus_set(
 
 #[tauri::command]
 pub(crate) async fn save_project(project: Project, output_file: PathBuf) -> Result<(), Error> {
-    let to_node_index = |n: QueryNodeRef| -> Result<u32, ConversionError> {
-        n.index.try_into().map_err(|_| ConversionError)
-    };
-
-    let project = annimate_core::Project {
-        corpus_set: (!project.corpus_set.is_empty()).then_some(project.corpus_set),
-        corpus_names: project.spec.corpus_names,
-        aql_query: project.spec.aql_query,
-        query_language: project.spec.query_language,
-        export_columns: project
-            .spec
-            .export_columns
-            .into_iter()
-            .map(|c| {
-                Ok::<_, ConversionError>(match c {
-                    ExportColumn::Number => annimate_core::ProjectExportColumn::Number,
-                    ExportColumn::AnnoCorpus { anno_key } => {
-                        annimate_core::ProjectExportColumn::AnnoCorpus { anno_key }
-                    }
-                    ExportColumn::AnnoDocument { anno_key } => {
-                        annimate_core::ProjectExportColumn::AnnoDocument { anno_key }
-                    }
-                    ExportColumn::AnnoMatch {
-                        anno_key_or_query_node_property_key,
-                        node_ref,
-                    } => {
-                        let node_index = node_ref.map(to_node_index).transpose()?;
-
-                        match anno_key_or_query_node_property_key {
-                            None => annimate_core::ProjectExportColumn::AnnoMatch {
-                                anno_key: None,
-                                node_index,
-                            },
-                            Some(AnnoKeyOrQueryNodePropertyKey::AnnoKey { key }) => {
-                                annimate_core::ProjectExportColumn::AnnoMatch {
-                                    anno_key: Some(key),
-                                    node_index,
-                                }
-                            }
-                            Some(AnnoKeyOrQueryNodePropertyKey::QueryNodePropertyKey { key }) => {
-                                annimate_core::ProjectExportColumn::QueryNodeProperty {
-                                    query_node_property_key: key,
-                                    match_node_index: node_index,
-                                }
-                            }
-                        }
-                    }
-                    ExportColumn::AnnoEdge {
-                        edge_type,
-                        anno_key,
-                        source_node_ref,
-                        target_node_ref,
-                    } => annimate_core::ProjectExportColumn::AnnoEdge {
-                        edge_type,
-                        anno_key,
-                        source_node_index: source_node_ref.map(to_node_index).transpose()?,
-                        target_node_index: target_node_ref.map(to_node_index).transpose()?,
-                    },
-                    ExportColumn::MatchInContext {
-                        anno_key,
-                        context,
-                        context_right_override,
-                        primary_node_refs,
-                        secondary_node_refs: _,
-                        segmentation,
-                    } => annimate_core::ProjectExportColumn::MatchInContext {
-                        segmentation,
-                        anno_key,
-                        context: match context_right_override {
-                            Some(context_right) => annimate_core::ProjectContext::Asymmetric {
-                                left: context.try_into().map_err(|_| ConversionError)?,
-                                right: context_right.try_into().map_err(|_| ConversionError)?,
-                            },
-                            None => annimate_core::ProjectContext::Symmetric(
-                                context.try_into().map_err(|_| ConversionError)?,
-                            ),
-                        },
-                        primary_node_indices: primary_node_refs
-                            .into_iter()
-                            .map(to_node_index)
-                            .try_collect()?,
-                    },
-                })
-            })
-            .try_collect()?,
-        export_format: match project.spec.export_format {
-            ExportFormat::Csv => annimate_core::ProjectExportFormat::Csv,
-            ExportFormat::Xlsx => annimate_core::ProjectExportFormat::Xlsx,
-        },
-    };
+    let project = project.try_into()?;
 
     tauri::async_runtime::spawn_blocking(|| Ok(annimate_core::save_project(project, output_file)?))
         .await?
@@ -528,6 +550,12 @@ pub(crate) async fn validate_query(
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Project {
     corpus_set: String,
+    #[serde(default)]
+    output_path: Option<PathBuf>,
+    #[serde(default)]
+    query_name: String,
+    #[serde(default)]
+    other_queries: Vec<String>,
     spec: ExportSpec,
 }
 
@@ -537,33 +565,67 @@ pub(crate) struct ExportSpec {
     corpus_names: Vec<String>,
     aql_query: String,
     query_language: QueryLanguage,
+    #[serde(default)]
+    query_timeout: Option<u32>,
     export_columns: Vec<ExportColumn>,
     export_format: ExportFormat,
+    #[serde(default)]
+    csv_dialect: CsvDialect,
+    #[serde(default)]
+    xlsx_options: XlsxOptions,
 }
 
 impl TryFrom<ExportSpec> for ExportConfig {
+    type Error = Error;
+
+    fn try_from(spec: ExportSpec) -> Result<ExportConfig, Error> {
+        let project: annimate_core::Project = Project {
+            corpus_set: String::new(),
+            output_path: None,
+            query_name: String::new(),
+            other_queries: Vec::new(),
+            spec,
+        }
+        .try_into()?;
+
+        Ok(project.into_export_config()?)
+    }
+}
+
+impl TryFrom<Project> for annimate_core::Project {
     type Error = ConversionError;
 
-    fn try_from(spec: ExportSpec) -> Result<ExportConfig, ConversionError> {
-        Ok(ExportConfig {
-            corpus_names: spec.corpus_names,
-            aql_query: spec.aql_query,
-            query_language: spec.query_language,
-            format: {
-                let columns = spec
+    fn try_from(project: Project) -> Result<annimate_core::Project, ConversionError> {
+        Ok(annimate_core::Project {
+            corpus_set: (!project.corpus_set.is_empty()).then_some(project.corpus_set),
+            corpus_names: project.spec.corpus_names,
+            queries: iter::once(Ok(annimate_core::ProjectQuery {
+                name: project.query_name,
+                aql_query: project.spec.aql_query,
+                query_language: project.spec.query_language,
+                export_columns: project
+                    .spec
                     .export_columns
                     .into_iter()
-                    .map(|c| c.try_into())
-                    .try_collect()?;
-
-                match spec.export_format {
-                    ExportFormat::Csv => {
-                        annimate_core::ExportFormat::Csv(CsvExportConfig { columns })
-                    }
-                    ExportFormat::Xlsx => {
-                        annimate_core::ExportFormat::Xlsx(XlsxExportConfig { columns })
-                    }
-                }
+                    .map(TryInto::try_into)
+                    .try_collect()?,
+            }))
+            .chain(project.other_queries.iter().map(|query| {
+                annimate_core::ProjectQuery::from_toml(query).map_err(|_| ConversionError)
+            }))
+            .try_collect()?,
+            query_timeout: project.spec.query_timeout,
+            export_format: match project.spec.export_format {
+                ExportFormat::Csv => annimate_core::ProjectExportFormat::Csv,
+                ExportFormat::Xlsx => annimate_core::ProjectExportFormat::Xlsx,
+                ExportFormat::Jsonl => annimate_core::ProjectExportFormat::Jsonl,
+                ExportFormat::Html => annimate_core::ProjectExportFormat::Html,
+                ExportFormat::Ods => annimate_core::ProjectExportFormat::Ods,
+            },
+            output_path: project.output_path,
+            format_options: annimate_core::ProjectFormatOptions {
+                csv: project.spec.csv_dialect.into(),
+                xlsx: project.spec.xlsx_options.into(),
             },
         })
     }
@@ -580,16 +642,24 @@ pub(crate) enum ExportColumn {
     AnnoCorpus {
         #[serde(skip_serializing_if = "Option::is_none")]
         anno_key: Option<AnnoKey>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoDocument {
         #[serde(skip_serializing_if = "Option::is_none")]
         anno_key: Option<AnnoKey>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoMatch {
         #[serde(skip_serializing_if = "Option::is_none")]
         anno_key_or_query_node_property_key: Option<AnnoKeyOrQueryNodePropertyKey>,
         #[serde(skip_serializing_if = "Option::is_none")]
         node_ref: Option<QueryNodeRef>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aggregation: Option<AnnoAggregation>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     AnnoEdge {
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -600,6 +670,8 @@ pub(crate) enum ExportColumn {
         source_node_ref: Option<QueryNodeRef>,
         #[serde(skip_serializing_if = "Option::is_none")]
         target_node_ref: Option<QueryNodeRef>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueType>,
     },
     MatchInContext {
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -607,13 +679,38 @@ pub(crate) enum ExportColumn {
         context: usize,
         #[serde(skip_serializing_if = "Option::is_none")]
         context_right_override: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context_unit: Option<AnnoKey>,
         primary_node_refs: Vec<QueryNodeRef>,
         secondary_node_refs: Vec<QueryNodeRef>,
         #[serde(skip_serializing_if = "Option::is_none")]
         segmentation: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        segment_separator: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gap_marker: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        match_brackets: Option<(String, String)>,
+        #[serde(default)]
+        restore_whitespace: bool,
+    },
+    // Columns that cannot be configured in the app yet are kept as they appear in the project
+    // file, so that they are still exported and are not lost when saving the project
+    Unsupported {
+        column_type: String,
+        definition: String,
     },
 }
 
+impl ExportColumn {
+    fn unsupported(column_type: &str, column: &annimate_core::ProjectExportColumn) -> ExportColumn {
+        ExportColumn::Unsupported {
+            column_type: column_type.into(),
+            definition: column.to_toml(),
+        }
+    }
+}
+
 // Query node properties are a niche feature, so rather than giving them their own
 // column type as in `annimate_core`, the UI offers them as additional options of the
 // match-annotation column. An `AnnoMatch` column therefore maps to either
@@ -632,43 +729,60 @@ pub(crate) struct QueryNodeRef {
     variables: Vec<String>,
 }
 
-impl TryFrom<ExportColumn> for TableExportColumn {
+impl TryFrom<ExportColumn> for annimate_core::ProjectExportColumn {
     type Error = ConversionError;
 
-    fn try_from(export_column: ExportColumn) -> Result<TableExportColumn, ConversionError> {
+    fn try_from(
+        export_column: ExportColumn,
+    ) -> Result<annimate_core::ProjectExportColumn, ConversionError> {
+        let to_node_index = |n: QueryNodeRef| -> Result<u32, ConversionError> {
+            n.index.try_into().map_err(|_| ConversionError)
+        };
+
         Ok(match export_column {
-            ExportColumn::Number => TableExportColumn::Number,
-            ExportColumn::AnnoCorpus { anno_key } => {
-                TableExportColumn::Data(ExportData::Value(ExportDataValue::CorpusAnno {
-                    anno_key: anno_key.ok_or(ConversionError)?,
-                }))
-            }
-            ExportColumn::AnnoDocument { anno_key } => {
-                TableExportColumn::Data(ExportData::Value(ExportDataValue::DocumentAnno {
-                    anno_key: anno_key.ok_or(ConversionError)?,
-                }))
-            }
+            ExportColumn::Number => annimate_core::ProjectExportColumn::Number,
+            ExportColumn::AnnoCorpus {
+                anno_key,
+                value_type,
+            } => annimate_core::ProjectExportColumn::AnnoCorpus {
+                anno_key,
+                value_type,
+            },
+            ExportColumn::AnnoDocument {
+                anno_key,
+                value_type,
+            } => annimate_core::ProjectExportColumn::AnnoDocument {
+                anno_key,
+                value_type,
+            },
             ExportColumn::AnnoMatch {
                 anno_key_or_query_node_property_key,
                 node_ref,
+                aggregation,
+                value_type,
             } => {
-                let index = node_ref.ok_or(ConversionError)?.index;
+                let node_index = node_ref.map(to_node_index).transpose()?;
 
                 match anno_key_or_query_node_property_key {
-                    None => return Err(ConversionError),
+                    None => annimate_core::ProjectExportColumn::AnnoMatch {
+                        anno_key: None,
+                        node_index,
+                        aggregation,
+                        value_type,
+                    },
                     Some(AnnoKeyOrQueryNodePropertyKey::AnnoKey { key }) => {
-                        TableExportColumn::Data(ExportData::Value(ExportDataValue::MatchNodeAnno {
-                            anno_key: key,
-                            index,
-                        }))
+                        annimate_core::ProjectExportColumn::AnnoMatch {
+                            anno_key: Some(key),
+                            node_index,
+                            aggregation,
+                            value_type,
+                        }
                     }
                     Some(AnnoKeyOrQueryNodePropertyKey::QueryNodePropertyKey { key }) => {
-                        TableExportColumn::Data(ExportData::Value(
-                            ExportDataValue::QueryNodeProperty {
-                                query_node_property_key: key,
-                                match_node_index: index,
-                            },
-                        ))
+                        annimate_core::ProjectExportColumn::QueryNodeProperty {
+                            query_node_property_key: key,
+                            match_node_index: node_index,
+                        }
                     }
                 }
             }
@@ -677,31 +791,52 @@ impl TryFrom<ExportColumn> for TableExportColumn {
                 anno_key,
                 source_node_ref,
                 target_node_ref,
-            } => TableExportColumn::Data(ExportData::Value(ExportDataValue::EdgeAnno {
-                edge_type: edge_type.ok_or(ConversionError)?,
-                anno_key: anno_key.ok_or(ConversionError)?,
-                source_node_index: source_node_ref.ok_or(ConversionError)?.index,
-                target_node_index: target_node_ref.ok_or(ConversionError)?.index,
-            })),
+                value_type,
+            } => annimate_core::ProjectExportColumn::AnnoEdge {
+                edge_type,
+                anno_key,
+                source_node_index: source_node_ref.map(to_node_index).transpose()?,
+                target_node_index: target_node_ref.map(to_node_index).transpose()?,
+                value_type,
+            },
             ExportColumn::MatchInContext {
                 anno_key,
                 context,
                 context_right_override,
+                context_unit,
                 primary_node_refs,
                 secondary_node_refs: _,
                 segmentation,
-            } => TableExportColumn::Data(ExportData::Text(ExportDataText {
-                segmentation: {
-                    let segmentation = segmentation.ok_or(ConversionError)?;
-                    (!segmentation.is_empty()).then_some(segmentation)
+                segment_separator,
+                gap_marker,
+                match_brackets,
+                restore_whitespace,
+            } => annimate_core::ProjectExportColumn::MatchInContext {
+                segmentation,
+                anno_key,
+                context: match context_right_override {
+                    Some(context_right) => annimate_core::ProjectContext::Asymmetric {
+                        left: context.try_into().map_err(|_| ConversionError)?,
+                        right: context_right.try_into().map_err(|_| ConversionError)?,
+                    },
+                    None => annimate_core::ProjectContext::Symmetric(
+                        context.try_into().map_err(|_| ConversionError)?,
+                    ),
                 },
-                left_context: context,
-                right_context: context_right_override.unwrap_or(context),
-                anno_key: anno_key.ok_or(ConversionError)?,
-                primary_node_indices: Some(
-                    primary_node_refs.into_iter().map(|n| n.index).collect(),
-                ),
-            })),
+                context_unit,
+                primary_node_indices: primary_node_refs
+                    .into_iter()
+                    .map(to_node_index)
+                    .try_collect()?,
+                segment_separator,
+                gap_marker,
+                match_brackets,
+                restore_whitespace,
+            },
+            ExportColumn::Unsupported { definition, .. } => {
+                annimate_core::ProjectExportColumn::from_toml(&definition)
+                    .map_err(|_| ConversionError)?
+            }
         })
     }
 }
@@ -711,6 +846,68 @@ impl TryFrom<ExportColumn> for TableExportColumn {
 pub(crate) enum ExportFormat {
     Csv,
     Xlsx,
+    Jsonl,
+    Html,
+    Ods,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CsvDialect {
+    delimiter: CsvDelimiter,
+    quote_style: CsvQuoteStyle,
+    line_terminator: CsvLineTerminator,
+    bom: bool,
+    escape_formulas: bool,
+}
+
+impl From<annimate_core::CsvDialect> for CsvDialect {
+    fn from(dialect: annimate_core::CsvDialect) -> CsvDialect {
+        CsvDialect {
+            delimiter: dialect.delimiter,
+            quote_style: dialect.quote_style,
+            line_terminator: dialect.line_terminator,
+            bom: dialect.bom,
+            escape_formulas: dialect.escape_formulas,
+        }
+    }
+}
+
+impl From<CsvDialect> for annimate_core::CsvDialect {
+    fn from(dialect: CsvDialect) -> annimate_core::CsvDialect {
+        annimate_core::CsvDialect {
+            delimiter: dialect.delimiter,
+            quote_style: dialect.quote_style,
+            line_terminator: dialect.line_terminator,
+            bom: dialect.bom,
+            escape_formulas: dialect.escape_formulas,
+        }
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct XlsxOptions {
+    counts_sheet: bool,
+    kwic_cells: bool,
+}
+
+impl From<annimate_core::XlsxOptions> for XlsxOptions {
+    fn from(options: annimate_core::XlsxOptions) -> XlsxOptions {
+        XlsxOptions {
+            counts_sheet: options.counts_sheet,
+            kwic_cells: options.kwic_cells,
+        }
+    }
+}
+
+impl From<XlsxOptions> for annimate_core::XlsxOptions {
+    fn from(options: XlsxOptions) -> annimate_core::XlsxOptions {
+        annimate_core::XlsxOptions {
+            counts_sheet: options.counts_sheet,
+            kwic_cells: options.kwic_cells,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -57,6 +57,7 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             api::add_corpora_to_set,
             api::clear_cache,
+            api::count_matches,
             api::create_corpus_set,
             api::delete_corpus,
             api::delete_corpus_set,
@@ -28,9 +28,10 @@ import {
   TooltipTrigger,
 } from '@/components/ui/tooltip';
 import { useToast } from '@/components/ui/use-toast';
-import { ExportColumnType } from '@/lib/api-types';
+import { ExportColumn } from '@/lib/api-types';
 import {
   ExportColumnItem,
+  NewExportColumnType,
   useAddExportColumn,
   useExportColumnItems,
   useIsExporting,
@@ -43,7 +44,7 @@ import { cn } from '@/lib/utils';
 import { GripVertical, Plus, Trash2 } from 'lucide-react';
 import { FC, PropsWithChildren, useEffect, useRef } from 'react';
 
-const COLUMN_TYPE_TO_NAME: Record<ExportColumnType, string> = {
+const COLUMN_TYPE_TO_NAME: Record<NewExportColumnType, string> = {
   number: 'Number',
   anno_corpus: 'Corpus metadata',
   anno_document: 'Document metadata',
@@ -52,6 +53,17 @@ const COLUMN_TYPE_TO_NAME: Record<ExportColumnType, string> = {
   match_in_context: 'Match in context',
 };
 
+const UNSUPPORTED_COLUMN_TYPE_TO_NAME: Partial<Record<string, string>> = {
+  'covered-text': 'Covered text',
+  'match-position': 'Match position',
+  'traversal-annotation': 'Traversal annotation',
+};
+
+const getColumnName = (column: ExportColumn): string =>
+  column.type === 'unsupported'
+    ? (UNSUPPORTED_COLUMN_TYPE_TO_NAME[column.columnType] ?? column.columnType)
+    : COLUMN_TYPE_TO_NAME[column.type];
+
 export const ExportColumnList: FC = () => {
   const exportColumns = useExportColumnItems();
   const addExportColumn = useAddExportColumn();
@@ -85,7 +97,7 @@ export const ExportColumnList: FC = () => {
                 'anno_edge',
                 'number',
               ] as const
-            ).map((columnType: ExportColumnType) => (
+            ).map((columnType: NewExportColumnType) => (
               <CardMenuItem
                 key={columnType}
                 columnType={columnType}
@@ -189,6 +201,7 @@ const ExportColumnListItem: FC<ExportColumnListItemProps> = ({
           item.type === 'anno_edge',
         'border-column-match-in-context-600 ring-column-match-in-context-600':
           item.type === 'match_in_context',
+        'border-gray-500 ring-gray-500': item.type === 'unsupported',
         'opacity-30': isPlaceholder,
         ring: isOverlay,
       })}
@@ -214,6 +227,7 @@ const ExportColumnListItem: FC<ExportColumnListItemProps> = ({
                 item.type === 'anno_edge',
               'text-column-match-in-context-800 dark:text-column-match-in-context-600':
                 item.type === 'match_in_context',
+              'text-gray-700 dark:text-gray-400': item.type === 'unsupported',
               'cursor-grab': !reorderDisabled,
               'cursor-grabbing': isOverlay || isPlaceholder,
             })}
@@ -222,7 +236,7 @@ const ExportColumnListItem: FC<ExportColumnListItemProps> = ({
             {...dragHandleListeners}
             tabIndex={-1}
           >
-            {COLUMN_TYPE_TO_NAME[item.type]}
+            {getColumnName(item)}
           </p>
           {item.type === 'anno_corpus' && (
             <AnnoCorpusColumn
@@ -280,6 +294,12 @@ const ExportColumnListItem: FC<ExportColumnListItemProps> = ({
               }
             />
           )}
+          {item.type === 'unsupported' && (
+            <p className="text-muted-foreground pb-2 text-sm">
+              This column cannot be configured in the app yet. It is exported
+              and saved with the project as defined in the project file.
+            </p>
+          )}
         </div>
 
         <div className="flex items-center gap-2">
@@ -328,7 +348,7 @@ const getExportColumnId = (exportColumn: ExportColumnItem): string =>
   `${exportColumn.id}`;
 
 type CardMenuItemProps = PropsWithChildren<{
-  columnType: ExportColumnType;
+  columnType: NewExportColumnType;
   onClick?: () => void;
 }>;
 
@@ -19,6 +19,9 @@ export const ExportFormatSelect: FC = () => {
       options={[
         { caption: 'CSV', value: 'csv' },
         { caption: 'Excel', value: 'xlsx' },
+        { caption: 'JSON Lines', value: 'jsonl' },
+        { caption: 'HTML', value: 'html' },
+        { caption: 'ODS', value: 'ods' },
       ]}
       value={exportFormat}
     />
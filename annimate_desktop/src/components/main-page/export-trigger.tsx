@@ -12,6 +12,8 @@ import {
   useExportMatches,
   useExportPreflight,
   useGetExportFormat,
+  useGetOutputPath,
+  useSetOutputPath,
 } from '@/lib/store';
 import { cn, formatPercentage } from '@/lib/utils';
 import { CheckSquare2, File, Folder, Hourglass, Info, X } from 'lucide-react';
@@ -19,11 +21,16 @@ import { CheckSquare2, File, Folder, Hourglass, Info, X } from 'lucide-react';
 const EXPORT_FORMAT_FILTERS = {
   csv: [{ name: 'Comma-separated values (*.csv)', extensions: ['csv'] }],
   xlsx: [{ name: 'Excel (*.xlsx)', extensions: ['xlsx'] }],
+  jsonl: [{ name: 'JSON Lines (*.jsonl)', extensions: ['jsonl'] }],
+  html: [{ name: 'HTML (*.html)', extensions: ['html'] }],
+  ods: [{ name: 'OpenDocument Spreadsheet (*.ods)', extensions: ['ods'] }],
 };
 
 export const ExportTrigger = () => {
   const { canExport, impediments } = useExportPreflight();
   const getExportFormat = useGetExportFormat();
+  const getOutputPath = useGetOutputPath();
+  const setOutputPath = useSetOutputPath();
   const {
     mutation: { isPending: isExporting, mutate: exportMatches },
     state,
@@ -89,7 +96,7 @@ export const ExportTrigger = () => {
             onClick={async () => {
               const exportFormat = getExportFormat();
               const outputFile = await save({
-                defaultPath: await documentDir(),
+                defaultPath: getOutputPath() ?? (await documentDir()),
                 filters: EXPORT_FORMAT_FILTERS[exportFormat],
                 title: 'Export to file',
               });
@@ -112,6 +119,8 @@ export const ExportTrigger = () => {
                       }
                     },
                     onSuccess: (_, { outputFile }) => {
+                      setOutputPath(outputFile);
+
                       toast({
                         description: (
                           <div className="flex gap-8">
@@ -124,6 +124,23 @@ export const Window: FC = () => {
                               title: 'Project partially loaded',
                               variant: 'warning',
                             });
+                          } else if (result.otherQueryCount > 0) {
+                            toast({
+                              description: (
+                                <p>
+                                  The app shows only the first query of the
+                                  project. The other {result.otherQueryCount}{' '}
+                                  {result.otherQueryCount > 1
+                                    ? 'queries are'
+                                    : 'query is'}{' '}
+                                  kept when saving the project and can be
+                                  exported with annimate-cli.
+                                </p>
+                              ),
+                              duration: 15000,
+                              title: 'Project with multiple queries',
+                              variant: 'warning',
+                            });
                           }
                         },
                         onError: (error: Error) => {
@@ -12,6 +12,7 @@ import {
   QueryValidationError,
   CheckOptions,
   Corpora,
+  CountStatusEvent,
   DownloadEvent,
   DownloadOptions,
   ExportSpec,
@@ -21,6 +22,7 @@ import {
   ImportCorpus,
   ImportCorpusResult,
   ImportStatusEvent,
+  MatchCounts,
   OpenDialogOptions,
   OpenDialogReturn,
   Project,
@@ -262,6 +264,8 @@ const makeExportableEdgeTypes = (count: number): ExportableEdgeType[] => {
   return exportableEdgeTypes;
 };
 
+const countCancelRequestedListeners: Set<() => void> = new Set();
+
 const exportCancelRequestedListeners: Set<() => void> = new Set();
 
 const importCancelRequestedListeners: Set<() => void> = new Set();
@@ -611,6 +615,87 @@ export const exportMatches = async (
   }
 };
 
+export const countMatches = async (
+  params: {
+    corpusNames: string[];
+    aqlQuery: string;
+    queryLanguage: QueryLanguage;
+    byDocument: boolean;
+    queryTimeout: number | null;
+  },
+  handlers: {
+    onEvent?: (event: CountStatusEvent) => void;
+  } = {},
+): Promise<MatchCounts> => {
+  logAction('Count', COLOR_CUSTOM_COMMAND, params);
+
+  let cancelRequested = false;
+
+  const unsubscribe = subscribeToCountCancelRequestedEvent(() => {
+    cancelRequested = true;
+  });
+
+  try {
+    handlers.onEvent?.({ type: 'started' });
+
+    const corpusNames = [...params.corpusNames].sort();
+
+    for (let i = 0; i < corpusNames.length; i++) {
+      if (cancelRequested) throw new CancelledError();
+      handlers.onEvent?.({
+        type: 'corpora_searched',
+        count: i,
+        totalCount: corpusNames.length,
+      });
+
+      await sleep(500);
+    }
+
+    if (cancelRequested) throw new CancelledError();
+    handlers.onEvent?.({
+      type: 'corpora_searched',
+      count: corpusNames.length,
+      totalCount: corpusNames.length,
+    });
+
+    return {
+      corpora: corpusNames.map((corpusName) => {
+        const matchCount = getMatchCountForCorpus(corpusName);
+        const documentNodeName = `${corpusName}/doc1`;
+
+        return {
+          corpusName,
+          matchCount,
+          documentCount: matchCount > 0 ? 1 : 0,
+          documents: params.byDocument
+            ? matchCount > 0
+              ? [{ documentNodeName, matchCount }]
+              : []
+            : null,
+        };
+      }),
+    };
+  } finally {
+    unsubscribe();
+  }
+};
+
+export const emitCountCancelRequestedEvent = async () => {
+  logAction('Emit count cancel requested event', COLOR_CUSTOM_COMMAND);
+
+  countCancelRequestedListeners.forEach((l) => l());
+};
+
+const subscribeToCountCancelRequestedEvent = (
+  callback: () => void,
+): UnlistenFn => {
+  countCancelRequestedListeners.add(callback);
+
+  return () => {
+    countCancelRequestedListeners.delete(callback);
+  };
+};
+
 export const emitExportCancelRequestedEvent = async () => {
   logAction('Emit export cancel requested event', COLOR_CUSTOM_COMMAND);
 
@@ -896,10 +981,11 @@ const subscribeToImportCancelRequestedEvent = (
 };
 
 // Special cases:
-// - `inputFile`='fail':           Error
-// - `inputFile`='empty':          Empty project
-// - `inputFile`='invalid-query':  Project with invalid query
-// - `inputFile`='missing-corpus': Project with missing corpus
+// - `inputFile`='fail':             Error
+// - `inputFile`='empty':            Empty project
+// - `inputFile`='invalid-query':    Project with invalid query
+// - `inputFile`='missing-corpus':   Project with missing corpus
+// - `inputFile`='multiple-queries': Project with multiple queries
 export const loadProject = async (params: {
   inputFile: string;
 }): Promise<Project> => {
@@ -912,12 +998,27 @@ export const loadProject = async (params: {
   if (params.inputFile === 'empty') {
     return {
       corpusSet: '',
+      outputPath: null,
+      queryName: '',
+      otherQueries: [],
       spec: {
         corpusNames: [],
         aqlQuery: '',
         queryLanguage: 'AQLQuirksV3',
+        queryTimeout: null,
         exportColumns: [],
         exportFormat: 'xlsx',
+        csvDialect: {
+          delimiter: 'comma',
+          quoteStyle: 'necessary',
+          lineTerminator: 'crlf',
+          bom: false,
+          escapeFormulas: false,
+        },
+        xlsxOptions: {
+          countsSheet: false,
+          kwicCells: false,
+        },
       },
     };
   }
@@ -925,10 +1026,14 @@ export const loadProject = async (params: {
   if (params.inputFile === 'invalid-query') {
     return {
       corpusSet: CORPUS_SET_WORKING,
+      outputPath: null,
+      queryName: '',
+      otherQueries: [],
       spec: {
         corpusNames: [CORPUS_MULTIPLE_SEGMENTATIONS],
         aqlQuery: 'invalid!!',
         queryLanguage: 'AQLQuirksV3',
+        queryTimeout: null,
         exportColumns: [
           { type: 'number' },
           { type: 'anno_corpus', annoKey: { ns: NS_CORPUS, name: 'anno_1' } },
@@ -966,12 +1071,29 @@ export const loadProject = async (params: {
           },
         ],
         exportFormat: 'xlsx',
+        csvDialect: {
+          delimiter: 'comma',
+          quoteStyle: 'necessary',
+          lineTerminator: 'crlf',
+          bom: false,
+          escapeFormulas: false,
+        },
+        xlsxOptions: {
+          countsSheet: false,
+          kwicCells: false,
+        },
       },
     };
   }
 
   return {
     corpusSet: CORPUS_SET_WORKING,
+    outputPath: null,
+    queryName: '',
+    otherQueries:
+      params.inputFile === 'multiple-queries'
+        ? ['name = "other"\nquery = "tok"\nquery-language = "aql"\n']
+        : [],
     spec: {
       corpusNames: [
         CORPUS_MULTIPLE_SEGMENTATIONS,
@@ -981,6 +1103,7 @@ export const loadProject = async (params: {
       ],
       aqlQuery: 'foo="bar" & baz="qux"',
       queryLanguage: 'AQLQuirksV3',
+      queryTimeout: null,
       exportColumns: [
         { type: 'number' },
         { type: 'anno_corpus', annoKey: { ns: NS_CORPUS, name: 'anno_1' } },
@@ -1022,8 +1145,24 @@ export const loadProject = async (params: {
           secondaryNodeRefs: [{ index: 0, variables: ['1'] }],
           segmentation: SEGMENTATION1,
         },
+        {
+          type: 'unsupported',
+          columnType: 'covered-text',
+          definition: 'type = "covered-text"\nnode-index = 0\n',
+        },
       ],
       exportFormat: 'xlsx',
+      csvDialect: {
+        delimiter: 'comma',
+        quoteStyle: 'necessary',
+        lineTerminator: 'crlf',
+        bom: false,
+        escapeFormulas: false,
+      },
+      xlsxOptions: {
+        countsSheet: false,
+        kwicCells: false,
+      },
     },
   };
 };
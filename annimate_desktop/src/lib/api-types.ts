@@ -53,6 +53,17 @@ export type AnnoKey = {
 
 export type QueryNodePropertyKey = 'fragment' | 'variable';
 
+export type AnnoAggregation =
+  | {
+      mode: 'first';
+    }
+  | {
+      mode: 'distinct' | 'all';
+      separator: string;
+    };
+
+export type ValueType = 'auto' | 'text' | 'integer' | 'float' | 'date';
+
 export type AnnoKeyOrQueryNodePropertyKey =
   | {
       type: 'anno_key';
@@ -65,6 +76,11 @@ export type AnnoKeyOrQueryNodePropertyKey =
 
 export type Project = {
   corpusSet: string;
+  outputPath: string | null;
+  queryName: string;
+  // Further queries of the project, which cannot be configured in the app yet
+  // and are kept as they appear in the project file
+  otherQueries: string[];
   spec: ExportSpec;
 };
 
@@ -72,8 +88,11 @@ export type ExportSpec = {
   corpusNames: string[];
   aqlQuery: string;
   queryLanguage: QueryLanguage;
+  queryTimeout: number | null;
   exportColumns: ExportColumn[];
   exportFormat: ExportFormat;
+  csvDialect: CsvDialect;
+  xlsxOptions: XlsxOptions;
 };
 
 export type ExportColumn =
@@ -83,15 +102,19 @@ export type ExportColumn =
   | {
       type: 'anno_corpus';
       annoKey?: AnnoKey;
+      valueType?: ValueType;
     }
   | {
       type: 'anno_document';
       annoKey?: AnnoKey;
+      valueType?: ValueType;
     }
   | {
       type: 'anno_match';
       annoKeyOrQueryNodePropertyKey?: AnnoKeyOrQueryNodePropertyKey;
       nodeRef?: QueryNodeRef;
+      aggregation?: AnnoAggregation;
+      valueType?: ValueType;
     }
   | {
       type: 'anno_edge';
@@ -99,15 +122,26 @@ export type ExportColumn =
       annoKey?: AnnoKey;
       sourceNodeRef?: QueryNodeRef;
       targetNodeRef?: QueryNodeRef;
+      valueType?: ValueType;
     }
   | {
       type: 'match_in_context';
       annoKey?: AnnoKey | 'default';
       context: number;
       contextRightOverride?: number;
+      contextUnit?: AnnoKey;
       primaryNodeRefs: QueryNodeRef[];
       secondaryNodeRefs: QueryNodeRef[];
       segmentation?: string;
+      segmentSeparator?: string;
+      gapMarker?: string;
+      matchBrackets?: [string, string];
+      restoreWhitespace?: boolean;
+    }
+  | {
+      type: 'unsupported';
+      columnType: string;
+      definition: string;
     };
 
 export type ExportColumnType = ExportColumn['type'];
@@ -116,7 +150,42 @@ export type ExportColumnData<T extends ExportColumnType> = Omit<
   'type'
 >;
 
-export type ExportFormat = 'csv' | 'xlsx';
+export type ExportFormat = 'csv' | 'xlsx' | 'jsonl' | 'html' | 'ods';
+
+export type CsvDialect = {
+  delimiter: CsvDelimiter;
+  quoteStyle: CsvQuoteStyle;
+  lineTerminator: CsvLineTerminator;
+  bom: boolean;
+  escapeFormulas: boolean;
+};
+
+export type CsvDelimiter = 'comma' | 'semicolon' | 'tab';
+
+export type CsvQuoteStyle = 'necessary' | 'always';
+
+export type CsvLineTerminator = 'crlf' | 'lf';
+
+export type XlsxOptions = {
+  countsSheet: boolean;
+  kwicCells: boolean;
+};
+
+export type MatchCounts = {
+  corpora: CorpusMatchCount[];
+};
+
+export type CorpusMatchCount = {
+  corpusName: string;
+  matchCount: number;
+  documentCount: number;
+  documents: DocumentMatchCount[] | null;
+};
+
+export type DocumentMatchCount = {
+  documentNodeName: string;
+  matchCount: number;
+};
 
 export type QueryLanguage = 'AQL' | 'AQLQuirksV3';
 
@@ -151,6 +220,10 @@ export type LineColumnIndex = {
   columnIndex: number;
 };
 
+export type CountStatusEvent =
+  | { type: 'started' }
+  | { type: 'corpora_searched'; count: number; totalCount: number };
+
 export type ExportStatusEvent =
   | { type: 'started' }
   | { type: 'corpora_searched'; count: number; totalCount: number }
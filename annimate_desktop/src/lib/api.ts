@@ -1,10 +1,12 @@
 import {
   Corpora,
+  CountStatusEvent,
   ExportSpec,
   ExportStatusEvent,
   ExportableEdgeType,
   ExportableNodeAnnoKeys,
   ImportStatusEvent,
+  MatchCounts,
   Project,
   QueryLanguage,
   QueryNodesResult,
@@ -38,6 +40,25 @@ export const addCorporaToSet = (params: {
 
 export const clearCache = (): Promise<void> => invoke('clear_cache');
 
+export const countMatches = async (
+  params: {
+    corpusNames: string[];
+    aqlQuery: string;
+    queryLanguage: QueryLanguage;
+    byDocument: boolean;
+    queryTimeout: number | null;
+  },
+  handlers: {
+    onEvent?: (event: CountStatusEvent) => void;
+  } = {},
+): Promise<MatchCounts> => {
+  const eventChannel = new Channel<CountStatusEvent>();
+  if (handlers.onEvent !== undefined) {
+    eventChannel.onmessage = handlers.onEvent;
+  }
+  return invoke('count_matches', { eventChannel, ...params });
+};
+
 export const createCorpusSet = (params: { corpusSet: string }): Promise<void> =>
   invoke('create_corpus_set', params);
 
@@ -49,6 +70,9 @@ export const deleteCorpusSet = (params: {
   deleteCorpora: boolean;
 }): Promise<void> => invoke('delete_corpus_set', params);
 
+export const emitCountCancelRequestedEvent = (): Promise<void> =>
+  emit('count_cancel_requested');
+
 export const emitExportCancelRequestedEvent = (): Promise<void> =>
   emit('export_cancel_requested');
 
@@ -3,7 +3,6 @@ import {
   AnnoKey,
   EdgeType,
   ExportColumn,
-  ExportColumnType,
   ExportableEdgeType,
   ExportableNodeAnnoKeys,
   InvokeArgs,
@@ -17,6 +16,7 @@ import {
   ExportColumnItem,
   ExportColumnUpdate,
   ExportPreflight,
+  NewExportColumnType,
   useAddExportColumn,
   useAqlQuery,
   useCorpusNamesInSelectedSet,
@@ -80,6 +80,11 @@ describe('store', () => {
     name: 'unknown_name',
   };
 
+  const OTHER_QUERY_DEFINITION =
+    'name = "second"\nquery = "tok"\nquery-language = "aql"\n';
+  const UNSUPPORTED_COLUMN_DEFINITION =
+    'type = "match-position"\nnode-index = 0\nposition = "token-index"\n';
+
   const Wrapper: FC<PropsWithChildren> = ({ children }) => {
     const queryClient = new QueryClient({
       queryCache: new SlowTrackingQueryCache(),
@@ -205,10 +210,14 @@ describe('store', () => {
         if (inputFile === 'project.anmt') {
           return {
             corpusSet: 'set1',
+            outputPath: null,
+            queryName: 'first',
+            otherQueries: [OTHER_QUERY_DEFINITION],
             spec: {
               corpusNames: ['a', 'z'],
               aqlQuery: 'valid',
               queryLanguage: 'AQLQuirksV3',
+              queryTimeout: null,
               exportColumns: [
                 {
                   type: 'number',
@@ -253,8 +262,24 @@ describe('store', () => {
                   sourceNodeRef: { index: 1, variables: ['2'] },
                   targetNodeRef: { index: 0, variables: ['1'] },
                 },
+                {
+                  type: 'unsupported',
+                  columnType: 'match-position',
+                  definition: UNSUPPORTED_COLUMN_DEFINITION,
+                },
               ],
               exportFormat: 'xlsx',
+              csvDialect: {
+                delimiter: 'comma',
+                quoteStyle: 'necessary',
+                lineTerminator: 'crlf',
+                bom: false,
+                escapeFormulas: false,
+              },
+              xlsxOptions: {
+                countsSheet: false,
+                kwicCells: false,
+              },
             },
           } satisfies Project;
         }
@@ -1334,7 +1359,7 @@ describe('store', () => {
   };
 
   type ChangeContext = {
-    addExportColumn: (type: ExportColumnType) => void;
+    addExportColumn: (type: NewExportColumnType) => void;
     removeExportColumn: (id: number) => void;
     setAqlQuery: (aqlQuery: string) => void;
     setSelectedCorpusSet: (corpusSet: string) => void;
@@ -1710,6 +1735,7 @@ describe('store', () => {
         corpusNames: ['a'],
         aqlQuery: 'valid',
         queryLanguage: 'AQLQuirksV3',
+        queryTimeout: null,
         exportColumns: [
           exportColumnContaining({
             type: 'number',
@@ -1759,6 +1785,17 @@ describe('store', () => {
           }),
         ],
         exportFormat: 'xlsx',
+        csvDialect: {
+          delimiter: 'comma',
+          quoteStyle: 'necessary',
+          lineTerminator: 'crlf',
+          bom: false,
+          escapeFormulas: false,
+        },
+        xlsxOptions: {
+          countsSheet: false,
+          kwicCells: false,
+        },
       },
       outputFile: 'out.xlsx',
     });
@@ -1792,6 +1829,7 @@ describe('store', () => {
     expect(loadProjectResult).toStrictEqual({
       corpusSet: 'set1',
       missingCorpusNames: ['z'],
+      otherQueryCount: 1,
     });
 
     await waitFor(() => {
@@ -1839,6 +1877,12 @@ describe('store', () => {
           sourceNodeRef: { index: 1, variables: ['2'] },
           targetNodeRef: { index: 0, variables: ['1'] },
         },
+        {
+          id: 10,
+          type: 'unsupported',
+          columnType: 'match-position',
+          definition: UNSUPPORTED_COLUMN_DEFINITION,
+        },
       ] satisfies ExportColumnItem[]);
       expect(result.current.exportFormat).toBe('xlsx');
       expect(result.current.canExport).toBe(true);
@@ -2007,10 +2051,14 @@ describe('store', () => {
       outputFile: 'project.anmt',
       project: {
         corpusSet: 'set1',
+        outputPath: null,
+        queryName: '',
+        otherQueries: [],
         spec: {
           corpusNames: ['a'],
           aqlQuery: 'valid',
           queryLanguage: 'AQLQuirksV3',
+          queryTimeout: null,
           exportColumns: [
             exportColumnContaining({ type: 'number' }),
             exportColumnContaining({
@@ -2055,6 +2103,17 @@ describe('store', () => {
             }),
           ],
           exportFormat: 'xlsx',
+          csvDialect: {
+            delimiter: 'comma',
+            quoteStyle: 'necessary',
+            lineTerminator: 'crlf',
+            bom: false,
+            escapeFormulas: false,
+          },
+          xlsxOptions: {
+            countsSheet: false,
+            kwicCells: false,
+          },
         },
       },
     });
@@ -3,6 +3,7 @@ import {
   AnnoKeyOrQueryNodePropertyKey,
   Corpora,
   Corpus,
+  CsvDialect,
   EdgeType,
   ExportColumn,
   ExportColumnData,
@@ -17,6 +18,7 @@ import {
   QueryNodeRef,
   QueryNodesResult,
   QueryValidationResult,
+  XlsxOptions,
 } from '@/lib/api-types';
 import {
   useDeleteCorpusMutation,
@@ -142,10 +144,18 @@ export type State = {
   aqlQuery: string;
   aqlQueryDebounced: string;
   queryLanguage: QueryLanguage;
+  queryTimeout: number | null;
 
   exportColumns: ExportColumnItem[];
   exportColumnsMaxId: number;
   exportFormat: ExportFormat;
+  csvDialect: CsvDialect;
+  xlsxOptions: XlsxOptions;
+
+  outputPath: string | null;
+
+  queryName: string;
+  otherQueries: string[];
 };
 
 type StoreContextValue = {
@@ -169,6 +179,7 @@ export const createStoreContextValue = (): StoreContextValue => ({
     aqlQuery: '',
     aqlQueryDebounced: '',
     queryLanguage: 'AQL',
+    queryTimeout: null,
 
     exportColumns: [
       {
@@ -188,11 +199,30 @@ export const createStoreContextValue = (): StoreContextValue => ({
     ],
     exportColumnsMaxId: 2,
     exportFormat: 'csv',
+    csvDialect: {
+      delimiter: 'comma',
+      quoteStyle: 'necessary',
+      lineTerminator: 'crlf',
+      bom: false,
+      escapeFormulas: false,
+    },
+    xlsxOptions: {
+      countsSheet: false,
+      kwicCells: false,
+    },
+
+    outputPath: null,
+
+    queryName: '',
+    otherQueries: [],
   })),
   aqlQueryDebounceTimeoutRef: { current: undefined },
 });
 
-const createExportColumn = (type: ExportColumnType): ExportColumn => {
+// Columns of type `unsupported` only come from loaded projects and cannot be added in the app
+export type NewExportColumnType = Exclude<ExportColumnType, 'unsupported'>;
+
+const createExportColumn = (type: NewExportColumnType): ExportColumn => {
   switch (type) {
     case 'number':
       return { type: 'number' };
@@ -354,6 +384,14 @@ const useGetQueryLanguage = (): (() => QueryLanguage) => {
   return () => getState().queryLanguage;
 };
 
+export const useQueryTimeout = (): number | null =>
+  useSelector((state) => state.queryTimeout);
+
+const useGetQueryTimeout = (): (() => number | null) => {
+  const getState = useGetState();
+  return () => getState().queryTimeout;
+};
+
 export const useExportColumnItems = (): ExportColumnItem[] => {
   const exportableNodeAnnoKeys = useExportableNodeAnnoKeys();
   const exportableEdgeTypes = useExportableEdgeTypes();
@@ -633,6 +671,27 @@ export const useGetExportFormat = (): (() => ExportFormat) => {
   return () => getState().exportFormat;
 };
 
+export const useCsvDialect = (): CsvDialect =>
+  useSelector((state) => state.csvDialect);
+
+export const useGetCsvDialect = (): (() => CsvDialect) => {
+  const getState = useGetState();
+  return () => getState().csvDialect;
+};
+
+export const useXlsxOptions = (): XlsxOptions =>
+  useSelector((state) => state.xlsxOptions);
+
+export const useGetXlsxOptions = (): (() => XlsxOptions) => {
+  const getState = useGetState();
+  return () => getState().xlsxOptions;
+};
+
+export const useGetOutputPath = (): (() => string | null) => {
+  const getState = useGetState();
+  return () => getState().outputPath;
+};
+
 export type ExportPreflight = {
   spec: ExportSpec;
 } & (
@@ -650,17 +709,23 @@ export const useExportPreflight = (): ExportPreflight => {
   const corpusNames = useSelectedCorpusNamesInSelectedSet();
   const aqlQuery = useAqlQuery();
   const queryLanguage = useQueryLanguage();
+  const queryTimeout = useQueryTimeout();
   const { data: queryValidationResult } = useQueryValidationResult();
   const exportColumns = useExportColumnItems();
   const exportFormat = useExportFormat();
+  const csvDialect = useCsvDialect();
+  const xlsxOptions = useXlsxOptions();
 
   return toExportPreflight(
     corpusNames,
     aqlQuery,
     queryLanguage,
+    queryTimeout,
     queryValidationResult ?? undefined,
     exportColumns,
     exportFormat,
+    csvDialect,
+    xlsxOptions,
   );
 };
 
@@ -673,8 +738,11 @@ const useGetExportPreflight = <Wait extends boolean = true>(
   const getQueryValidationResultQueryData =
     useGetQueryValidationResultQueryData(options);
   const getQueryLanguage = useGetQueryLanguage();
+  const getQueryTimeout = useGetQueryTimeout();
   const getExportColumns = useGetExportColumns(options);
   const getExportFormat = useGetExportFormat();
+  const getCsvDialect = useGetCsvDialect();
+  const getXlsxOptions = useGetXlsxOptions();
 
   return async () => {
     const corpusNames = await getSelectedCorpusNamesInSelectedSet();
@@ -684,16 +752,22 @@ const useGetExportPreflight = <Wait extends boolean = true>(
       aqlQuery,
       queryLanguage,
     });
+    const queryTimeout = getQueryTimeout();
     const exportColumns = await getExportColumns();
     const exportFormat = getExportFormat();
+    const csvDialect = getCsvDialect();
+    const xlsxOptions = getXlsxOptions();
 
     return toExportPreflight(
       corpusNames,
       aqlQuery,
       queryLanguage,
+      queryTimeout,
       queryValidationResult ?? undefined,
       exportColumns,
       exportFormat,
+      csvDialect,
+      xlsxOptions,
     );
   };
 };
@@ -702,9 +776,12 @@ const toExportPreflight = (
   corpusNames: string[],
   aqlQuery: string,
   queryLanguage: QueryLanguage,
+  queryTimeout: number | null,
   queryValidationResult: QueryValidationResult | undefined,
   exportColumns: ExportColumn[],
   exportFormat: ExportFormat,
+  csvDialect: CsvDialect,
+  xlsxOptions: XlsxOptions,
 ): ExportPreflight => {
   const impediments: string[] = [];
 
@@ -733,8 +810,11 @@ const toExportPreflight = (
       corpusNames,
       aqlQuery,
       queryLanguage,
+      queryTimeout,
       exportColumns,
       exportFormat,
+      csvDialect,
+      xlsxOptions,
     },
     ...(impediments.length === 0
       ? { canExport: true, impediments: undefined }
@@ -919,10 +999,19 @@ export const useSetQueryLanguage = (): ((
   return (queryLanguage: QueryLanguage) => setState({ queryLanguage });
 };
 
-export const useAddExportColumn = (): ((type: ExportColumnType) => void) => {
+export const useSetQueryTimeout = (): ((
+  queryTimeout: number | null,
+) => void) => {
   const setState = useSetState();
+  return (queryTimeout: number | null) => setState({ queryTimeout });
+};
 
-  return (type: ExportColumnType) => {
+export const useAddExportColumn = (): ((
+  type: NewExportColumnType,
+) => void) => {
+  const setState = useSetState();
+
+  return (type: NewExportColumnType) => {
     setState((state) => {
       const id = (state.exportColumnsMaxId + 1) % Number.MAX_SAFE_INTEGER;
       const newExportColumn = { id, ...createExportColumn(type) };
@@ -1139,6 +1228,21 @@ export const useSetExportFormat = (): ((
   return (exportFormat: ExportFormat) => setState({ exportFormat });
 };
 
+export const useSetCsvDialect = (): ((csvDialect: CsvDialect) => void) => {
+  const setState = useSetState();
+  return (csvDialect: CsvDialect) => setState({ csvDialect });
+};
+
+export const useSetXlsxOptions = (): ((xlsxOptions: XlsxOptions) => void) => {
+  const setState = useSetState();
+  return (xlsxOptions: XlsxOptions) => setState({ xlsxOptions });
+};
+
+export const useSetOutputPath = (): ((outputPath: string | null) => void) => {
+  const setState = useSetState();
+  return (outputPath: string | null) => setState({ outputPath });
+};
+
 // QUERIES
 
 export { useDbDirQuery as useDbDir } from '@/lib/queries';
@@ -1268,6 +1372,7 @@ export const useExportMatches = () => {
 type LoadProjectResult = {
   corpusSet: string;
   missingCorpusNames: string[];
+  otherQueryCount: number;
 };
 
 export const useLoadProject = () => {
@@ -1319,9 +1424,15 @@ export const useLoadProject = () => {
           aqlQuery: project.spec.aqlQuery,
           aqlQueryDebounced: project.spec.aqlQuery,
           queryLanguage: project.spec.queryLanguage,
+          queryTimeout: project.spec.queryTimeout,
           exportColumns,
           exportColumnsMaxId,
           exportFormat: project.spec.exportFormat,
+          csvDialect: project.spec.csvDialect,
+          xlsxOptions: project.spec.xlsxOptions,
+          outputPath: project.outputPath,
+          queryName: project.queryName,
+          otherQueries: project.otherQueries,
         };
       });
 
@@ -1330,6 +1441,7 @@ export const useLoadProject = () => {
       return {
         corpusSet: project.corpusSet,
         missingCorpusNames,
+        otherQueryCount: project.otherQueries.length,
       };
     },
   );
@@ -1374,13 +1486,17 @@ export const useRenameCorpusSet = () => {
 export const useSaveProject = () => {
   const getSelectedCorpusSet = useGetSelectedCorpusSet({ wait: false });
   const getExportPreflight = useGetExportPreflight({ wait: false });
+  const getOutputPath = useGetOutputPath();
+  const getState = useGetState();
 
   return useSaveProjectMutation(async () => {
     const corpusSet = await getSelectedCorpusSet();
     const { spec } = await getExportPreflight();
+    const outputPath = getOutputPath();
+    const { queryName, otherQueries } = getState();
 
     return {
-      project: { corpusSet, spec },
+      project: { corpusSet, outputPath, queryName, otherQueries, spec },
     };
   });
 };